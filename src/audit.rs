@@ -0,0 +1,67 @@
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use color_eyre::{eyre::WrapErr, Result};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::bank::Question;
+
+// With audit_log on, every change to a response is appended to a log next to
+// the bank (questions.json => questions.audit.jsonl), one JSON object per line.
+// The file is only ever appended to, never rewritten.
+
+#[derive(Serialize)]
+struct Entry<'a> {
+    timestamp: &'a str, // RFC 3339, UTC
+    question: Value,    // its "id", or its number in the file
+    action: &'a str,    // the mode, or "set answer", "prefill classify"...
+    field: &'a str,
+    old: Value,
+    new: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rater_id: Option<&'a str>,
+}
+
+pub fn log_path(json_path: &Path) -> PathBuf {
+    json_path.with_extension("audit.jsonl")
+}
+
+/// Appends an entry for every response that differs between before and after
+pub fn record(
+    json_path: &Path,
+    question: Value,
+    action: &str,
+    before: &Question,
+    after: &Question,
+    rater_id: Option<&str>,
+) -> Result<()> {
+    let timestamp = Utc::now().to_rfc3339();
+    let mut lines = String::new();
+    for (field, old, new) in before.changed_responses(after) {
+        let entry = Entry {
+            timestamp: &timestamp,
+            question: question.clone(),
+            action,
+            field: &field,
+            old,
+            new,
+            rater_id,
+        };
+        lines += &(serde_json::to_string(&entry)? + "\n");
+    }
+    if lines.is_empty() {
+        return Ok(());
+    }
+    let log_path = log_path(json_path);
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .and_then(|mut log| log.write_all(lines.as_bytes()))
+        .wrap_err_with(|| format!("could not write to the audit log {}", log_path.display()))
+}
@@ -0,0 +1,982 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use clap::ValueEnum;
+use color_eyre::{
+    eyre::{bail, WrapErr},
+    Result,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    audit,
+    blinding::{self, Blinding},
+    counterbalance::Counterbalance,
+    crypt, dimensions, manifest, plugins,
+    proctor::Exam,
+    signing,
+    timing::TimeSpent,
+};
+
+// Questions to be extracted from .json file
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Question {
+    pub question: String,
+    pub options: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub answer: String, // should be verbatim one of the options in options, missing in blinded banks
+    pub is_higher_order: Option<Classification>, // not always in .json file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<u8>, // how sure of is_higher_order the rater is, 50 to 100%
+    pub human_answer: Option<String>,            // not always in .json file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_path: Option<String>, // ECG, radiograph etc., relative to the .json file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Metadata>, // context for raters, shown in the right panel
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating: Option<u8>, // item quality from 1 to 5, set in rate mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adjudicated_answer: Option<String>, // final answer when human_answer disagrees with answer
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<String>, // response fields the rater chose to leave blank, ie. ["human_answer"]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Question {
+    /// Copies the rater's responses from other where this question has none
+    pub fn fill_responses(&mut self, other: &Question) {
+        self.human_answer = self.human_answer.take().or(other.human_answer.clone());
+        if self.is_higher_order.is_none() {
+            self.is_higher_order = other.is_higher_order;
+            self.confidence = other.confidence;
+        }
+        self.rating = self.rating.or(other.rating);
+        self.adjudicated_answer = self
+            .adjudicated_answer
+            .take()
+            .or(other.adjudicated_answer.clone());
+        if self.skipped.is_empty() {
+            self.skipped = other.skipped.clone();
+        }
+    }
+
+    /// Back to unanswered, unclassified and unrated
+    pub fn clear_responses(&mut self) {
+        self.human_answer = None;
+        self.is_higher_order = None;
+        self.confidence = None;
+        self.rating = None;
+        self.adjudicated_answer = None;
+        self.skipped.clear();
+    }
+
+    /// The question's "tags", a list of strings
+    pub fn tags(&self) -> Vec<&str> {
+        match self.extra.get("tags") {
+            Some(Value::Array(tags)) => tags.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Adds tag to the question's "tags", or removes it if it's there already.
+    /// True if it was added.
+    pub fn toggle_tag(&mut self, tag: &str) -> bool {
+        let mut tags: Vec<Value> = match self.extra.get("tags") {
+            Some(Value::Array(tags)) => tags.clone(),
+            _ => vec![],
+        };
+        let before = tags.len();
+        tags.retain(|t| t.as_str() != Some(tag));
+        let added = tags.len() == before;
+        if added {
+            tags.push(Value::from(tag));
+        }
+        self.extra.insert("tags".to_string(), Value::Array(tags));
+        added
+    }
+
+    /// The options struck out, from 0, saved in "eliminated" by their number
+    pub fn eliminated(&self) -> BTreeSet<usize> {
+        match self.extra.get("eliminated") {
+            Some(Value::Array(numbers)) => numbers
+                .iter()
+                .filter_map(Value::as_u64)
+                .filter_map(|number| (number as usize).checked_sub(1))
+                .collect(),
+            _ => BTreeSet::new(),
+        }
+    }
+
+    pub fn set_eliminated(&mut self, eliminated: &BTreeSet<usize>) {
+        let numbers: Vec<usize> = eliminated.iter().map(|i| i + 1).collect();
+        self.extra.insert("eliminated".to_string(), json!(numbers));
+    }
+
+    /// The options on screen, from 0: those of "shown_options" (see
+    /// distractors.rs) or all of them
+    pub fn shown_options(&self) -> Vec<usize> {
+        match self.extra.get("shown_options") {
+            Some(Value::Array(numbers)) => numbers
+                .iter()
+                .filter_map(Value::as_u64)
+                .filter_map(|number| (number as usize).checked_sub(1))
+                .filter(|&i| i < self.options.len())
+                .collect(),
+            _ => (0..self.options.len()).collect(),
+        }
+    }
+
+    pub fn set_shown_options(&mut self, shown: &[usize]) {
+        let numbers: Vec<usize> = shown.iter().map(|i| i + 1).collect();
+        self.extra
+            .insert("shown_options".to_string(), json!(numbers));
+    }
+
+    /// On exam form, from the question's "forms" (["A", "B"] or just "A").
+    /// Questions without any are on every form, eg. anchor items.
+    pub fn in_form(&self, form: &str) -> bool {
+        match self.extra.get("forms") {
+            Some(Value::Array(forms)) => forms.iter().any(|f| f.as_str() == Some(form)),
+            Some(Value::String(f)) => f == form,
+            _ => true,
+        }
+    }
+
+    /// How much it counts towards a score, its "weight" (1 without one), eg. 2
+    /// for an item that counts double
+    pub fn weight(&self) -> f64 {
+        self.extra
+            .get("weight")
+            .and_then(Value::as_f64)
+            .filter(|weight| *weight >= 0.0)
+            .unwrap_or(1.0)
+    }
+
+    /// Its "id" as text, for banks that have them
+    pub fn id(&self) -> Option<String> {
+        match self.extra.get("id")? {
+            Value::String(id) => Some(id.clone()),
+            id => Some(id.to_string()),
+        }
+    }
+
+    /// The topic of the taxonomy it's assigned to, see taxonomy.rs
+    pub fn topic(&self) -> Option<&str> {
+        self.extra.get("topic").and_then(Value::as_str)
+    }
+
+    pub fn set_topic(&mut self, topic: &str) {
+        self.extra.insert("topic".to_string(), Value::from(topic));
+    }
+
+    /// Flagged for review with "flagged": true
+    pub fn is_flagged(&self) -> bool {
+        self.extra.get("flagged") == Some(&Value::Bool(true))
+    }
+
+    /// The fields set by raters
+    pub fn responses(&self) -> [(&'static str, Value); 6] {
+        [
+            ("human_answer", json!(self.human_answer)),
+            ("is_higher_order", json!(self.is_higher_order)),
+            ("confidence", json!(self.confidence)),
+            ("rating", json!(self.rating)),
+            ("adjudicated_answer", json!(self.adjudicated_answer)),
+            ("skipped", json!(self.skipped)),
+        ]
+    }
+
+    /// (field, old, new) for every response that differs in after, for the
+    /// logs. The axes of dimensions mode and plugin responses are responses too.
+    pub fn changed_responses(&self, after: &Question) -> Vec<(String, Value, Value)> {
+        let axis = |question: &Question, field: &str| {
+            question.extra.get(field).cloned().unwrap_or_default()
+        };
+        let axes = dimensions::all()
+            .iter()
+            .map(|dimension| dimension.field.as_str())
+            .chain([plugins::FIELD])
+            .map(|field| (field, axis(self, field), axis(after, field)));
+        self.responses()
+            .into_iter()
+            .zip(after.responses())
+            .map(|((field, old), (_, new))| (field, old, new))
+            .chain(axes)
+            .filter(|(_, old, new)| old != new)
+            .map(|(field, old, new)| (field.to_string(), old, new))
+            .collect()
+    }
+
+    /// Adds the responses that differ from before to "response_history", with
+    /// the time: the first response and every change after it
+    ///
+    /// "response_history": [{ "field": "human_answer", "value": "Aspirin", "at": "2026-..." }]
+    pub fn record_history(&mut self, before: &Question, at: &str) {
+        let changes: Vec<Value> = before
+            .changed_responses(self)
+            .into_iter()
+            .map(|(field, _, value)| json!({"field": field, "value": value, "at": at}))
+            .collect();
+        if changes.is_empty() {
+            return;
+        }
+        match self.extra.get_mut("response_history") {
+            Some(Value::Array(history)) => history.extend(changes),
+            _ => {
+                self.extra
+                    .insert("response_history".to_string(), Value::Array(changes));
+            }
+        }
+    }
+
+    /// The values human_answer was given, in order, from "response_history"
+    pub fn answer_history(&self) -> Vec<&str> {
+        match self.extra.get("response_history") {
+            Some(Value::Array(history)) => history
+                .iter()
+                .filter(|entry| entry["field"] == "human_answer")
+                .filter_map(|entry| entry["value"].as_str())
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// The explanation of the answer, from the question or its metadata
+    pub fn explanation(&self) -> Option<&str> {
+        let metadata_extra = self.metadata.iter().flat_map(|metadata| &metadata.extra);
+        self.extra
+            .iter()
+            .chain(metadata_extra)
+            .filter(|(key, _)| blinding::REVEALING_FIELDS.contains(&key.as_str()))
+            .find_map(|(_, value)| value.as_str())
+    }
+
+    /// Why option i is right or wrong, from the "option_explanations" of the
+    /// question or its metadata, keyed by the option's number or its text
+    pub fn option_explanation(&self, i: usize) -> Option<&str> {
+        let metadata = self.metadata.as_ref().map(|metadata| &metadata.extra);
+        let explanations = self
+            .extra
+            .get("option_explanations")
+            .or_else(|| metadata?.get("option_explanations"))?;
+        let option = self.options.get(i)?;
+        explanations
+            .get((i + 1).to_string())
+            .or_else(|| explanations.get(option))
+            .and_then(Value::as_str)
+    }
+
+    /// Whether the rater's answer is right, None when it isn't answered or
+    /// there's no answer key
+    pub fn is_correct(&self) -> Option<bool> {
+        match &self.human_answer {
+            Some(answer) if !self.answer.is_empty() => Some(*answer == self.answer),
+            _ => None,
+        }
+    }
+
+    /// The rater's answer doesn't match the answer key
+    pub fn is_disputed(&self) -> bool {
+        self.human_answer
+            .as_ref()
+            .is_some_and(|human_answer| human_answer != &self.answer)
+    }
+}
+
+/// What the rater made of a question in classify mode, saved in
+/// "is_higher_order" as true, false or "unsure" for the ambiguous ones
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(try_from = "Value", into = "Value")]
+pub enum Classification {
+    HigherOrder,
+    LowerOrder,
+    Unsure,
+}
+
+pub const CLASSIFICATIONS: [Classification; 3] = [
+    Classification::HigherOrder,
+    Classification::LowerOrder,
+    Classification::Unsure,
+];
+
+impl TryFrom<Value> for Classification {
+    type Error = String;
+
+    fn try_from(value: Value) -> Result<Classification, String> {
+        match value {
+            Value::Bool(true) => Ok(Classification::HigherOrder),
+            Value::Bool(false) => Ok(Classification::LowerOrder),
+            Value::String(text) if text == "unsure" => Ok(Classification::Unsure),
+            other => Err(format!("{} isn't true, false or \"unsure\"", other)),
+        }
+    }
+}
+
+impl From<Classification> for Value {
+    fn from(classification: Classification) -> Value {
+        match classification {
+            Classification::HigherOrder => Value::Bool(true),
+            Classification::LowerOrder => Value::Bool(false),
+            Classification::Unsure => Value::from("unsure"),
+        }
+    }
+}
+
+// as it's saved: true, false or unsure
+impl fmt::Display for Classification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Classification::HigherOrder => write!(f, "true"),
+            Classification::LowerOrder => write!(f, "false"),
+            Classification::Unsure => write!(f, "unsure"),
+        }
+    }
+}
+
+// Optional context about a vignette, all fields can be Markdown
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Metadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub learning_objective: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+// Fields this tool doesn't know about (ids, notes from other tools...), kept so
+// saving doesn't drop them. Sorted, so saving is deterministic.
+pub type Extra = BTreeMap<String, Value>;
+
+pub type Questions = Vec<Question>;
+
+/// A question bank. On disk this is either just the list of questions (the
+/// original format) or an object with the questions plus bank level settings:
+/// { "instructions": { "answer": "..." }, "questions": [...] }
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Bank {
+    // rater instructions per mode ("answer", "classify"), replacing the built in ones
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub instructions: BTreeMap<String, String>,
+    // topics for topic mode, see taxonomy.rs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub taxonomy: Vec<String>,
+    pub questions: Questions,
+    // who answered/classified this copy of the bank
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rater_id: Option<String>,
+    // time spent answering, with track_time on, see timing.rs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_spent: Option<TimeSpent>,
+    // when a --proctored session started and ended, see proctor.rs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exam: Option<Exam>,
+    // whether a --blinded bank was checked for answer keys, see blinding.rs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blinding: Option<Blinding>,
+    // a rater's packet's order, see counterbalance.rs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counterbalance: Option<Counterbalance>,
+    #[serde(flatten)]
+    pub extra: Extra,
+    #[serde(skip)]
+    plain_list: bool, // loaded from a bare list, saved back as one if nothing else is set
+    #[serde(skip)]
+    source: Option<Value>, // the file as loaded, for KeyOrder::Preserve
+    #[serde(skip)]
+    pub key_order: KeyOrder,
+    #[serde(skip)]
+    pub signing_key: Option<String>, // signs the file on save
+}
+
+/// Order of the keys in a saved file. Either way saving the same data gives the
+/// same file, so version control diffs only show real changes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyOrder {
+    /// question, options, answer... then any other fields alphabetically
+    #[default]
+    Canonical,
+    /// the order of the file as it was loaded, new keys go last
+    Preserve,
+}
+
+/// The two on disk layouts of a bank, see `question_cli convert`
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Format {
+    /// A bare list of questions
+    List,
+    /// An object with "questions" and the bank settings (instructions, rater_id, time_spent...)
+    Bank,
+}
+
+impl Bank {
+    pub fn from_json(data: &str) -> Result<Bank> {
+        let value: Value = serde_json::from_str(data).wrap_err("JSON not parsable")?;
+        let mut bank = if value.is_array() {
+            let questions = serde_json::from_value(value.clone())
+                .wrap_err("JSON is not a list of questions")?;
+            Bank {
+                questions,
+                plain_list: true,
+                ..Default::default()
+            }
+        } else {
+            serde_json::from_value(value.clone()).wrap_err("JSON is not a question bank")?
+        };
+        // it wouldn't match once saved, a new one is made when signing, see signing.rs
+        bank.extra.remove("signature");
+        bank.source = Some(value);
+        Ok(bank)
+    }
+
+    /// The file as saved, signed when there is a signing key
+    pub fn to_json(&self) -> Result<String> {
+        let mut value = self.to_value()?;
+        if let Some(key) = &self.signing_key {
+            signing::sign(&mut value, key)?;
+        }
+        to_pretty(&value)
+    }
+
+    /// The changes made since the file was loaded, as an RFC 6902 JSON Patch
+    pub fn patch(&self) -> Result<json_patch::Patch> {
+        let source = self.source.clone().unwrap_or_default();
+        Ok(json_patch::diff(&source, &self.to_value()?))
+    }
+
+    // set fields that only fit in the object layout
+    fn has_settings(&self) -> bool {
+        !self.instructions.is_empty()
+            || !self.taxonomy.is_empty()
+            || self.rater_id.is_some()
+            || self.time_spent.is_some()
+            || self.exam.is_some()
+            || self.blinding.is_some()
+            || self.counterbalance.is_some()
+            || self.signing_key.is_some()
+    }
+
+    fn to_value(&self) -> Result<Value> {
+        let mut value = if self.plain_list && !self.has_settings() && self.extra.is_empty() {
+            serde_json::to_value(&self.questions)
+        } else {
+            serde_json::to_value(self)
+        }
+        .wrap_err("Failed to serialize JSON while saving.")?;
+        if let (KeyOrder::Preserve, Some(source)) = (self.key_order, &self.source) {
+            reorder(&mut value, source);
+        }
+        Ok(value)
+    }
+
+    /// Changes the layout used when saving. A plain list can't hold bank
+    /// settings, so converting a bank that has some to a list is an error.
+    pub fn set_format(&mut self, format: Format) -> Result<()> {
+        if format == Format::List && self.has_settings() {
+            bail!("this bank has settings (instructions, rater_id, time_spent...) which a plain list can't hold");
+        }
+        self.plain_list = format == Format::List;
+        Ok(())
+    }
+}
+
+/// Pretty printed with two space indents and a final newline
+pub fn to_pretty(value: &Value) -> Result<String> {
+    let json =
+        serde_json::to_string_pretty(value).wrap_err("Failed to serialize JSON while saving.")?;
+    Ok(json + "\n")
+}
+
+// puts the keys of value in the order they have in source, new keys go last
+fn reorder(value: &mut Value, source: &Value) {
+    match (value, source) {
+        (Value::Object(map), Value::Object(source)) => {
+            let mut ordered = Map::new();
+            for key in source.keys() {
+                if let Some(item) = map.remove(key) {
+                    ordered.insert(key.clone(), item);
+                }
+            }
+            ordered.append(map);
+            for (key, item) in ordered.iter_mut() {
+                if let Some(source) = source.get(key) {
+                    reorder(item, source);
+                }
+            }
+            *map = ordered;
+        }
+        (Value::Array(items), Value::Array(source)) => {
+            for (item, source) in items.iter_mut().zip(source) {
+                reorder(item, source);
+            }
+        }
+        _ => {}
+    }
+}
+
+// "-" as a path is stdin/stdout, for piping through stats, convert, set...
+fn is_std_stream(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+/// load a .json question bank, "-" reads it from stdin
+pub fn load_json(json_path: &Path) -> Result<Bank> {
+    let data = read_json(json_path)?;
+    Bank::from_json(&data).with_context(|| format!("in {}", json_path.display()))
+}
+
+/// The JSON text of a bank, decrypted if it's a .age file
+pub fn read_json(json_path: &Path) -> Result<String> {
+    if is_std_stream(json_path) {
+        return io::read_to_string(io::stdin()).wrap_err("could not read stdin");
+    }
+    let data = fs::read(json_path)
+        .with_context(|| format!("could not read file: {}", json_path.display()))?;
+    if crypt::is_encrypted(json_path) {
+        return crypt::decrypt(json_path, &data)
+            .with_context(|| format!("in {}", json_path.display()));
+    }
+    String::from_utf8(data).with_context(|| format!("{} isn't text", json_path.display()))
+}
+
+/// save .json file to a specified path, "-" writes it to stdout
+pub fn save_json(json_path: &Path, bank: &Bank) -> Result<()> {
+    let new_data = bank.to_json()?;
+    if is_std_stream(json_path) {
+        write!(io::stdout(), "{}", new_data).wrap_err("Failed to write JSON to stdout.")?;
+        return Ok(());
+    }
+    if crypt::is_encrypted(json_path) {
+        let encrypted = crypt::encrypt(json_path, &new_data)?;
+        fs::write(json_path, encrypted).wrap_err("Failed to write JSON to file.")?;
+        return Ok(());
+    }
+    fs::write(json_path, new_data).wrap_err("Failed to write JSON to file.")?;
+    Ok(())
+}
+
+/// Where the questions of a session went when one of its files was reloaded
+/// or merged with a different number of questions
+#[derive(Debug, Default)]
+pub struct Moved {
+    start: usize, // the file's first question, as an absolute() index
+    old_len: usize,
+    new_len: usize,
+}
+
+impl Moved {
+    /// The absolute() index a question has now, None if it was one of the
+    /// file's last questions and isn't there anymore
+    pub fn absolute(&self, index: usize) -> Option<usize> {
+        match index.checked_sub(self.start) {
+            None => Some(index),
+            Some(offset) if offset < self.old_len => (offset < self.new_len).then_some(index),
+            Some(_) => Some(index + self.new_len - self.old_len),
+        }
+    }
+}
+
+/// Banks opened together and gone through as one continuous list of
+/// questions, every question is saved back to the file it came from.
+#[derive(Debug, Default)]
+pub struct Banks {
+    files: Vec<BankFile>,
+    audit: bool, // log every change to a response, see audit.rs
+    // the questions gone through when filtered (--only-unanswered...), as
+    // indexes into all of them. Every other index is into this list.
+    view: Option<Vec<usize>>,
+}
+
+#[derive(Debug)]
+struct BankFile {
+    json_path: PathBuf,
+    bank: Bank,
+    modified: Option<SystemTime>, // when it was loaded or saved, to notice other programs changing it
+}
+
+impl BankFile {
+    fn load(json_path: &Path) -> Result<BankFile> {
+        Ok(BankFile {
+            json_path: json_path.to_path_buf(),
+            bank: load_json(json_path)?,
+            modified: modified(json_path),
+        })
+    }
+}
+
+fn modified(json_path: &Path) -> Option<SystemTime> {
+    fs::metadata(json_path).and_then(|m| m.modified()).ok()
+}
+
+impl Banks {
+    pub fn load(json_paths: &[PathBuf]) -> Result<Banks> {
+        let files = json_paths
+            .iter()
+            .map(|json_path| BankFile::load(json_path))
+            .collect::<Result<_>>()?;
+        Ok(Banks {
+            files,
+            audit: false,
+            view: None,
+        })
+    }
+
+    pub fn save(&mut self) -> Result<()> {
+        for file in &mut self.files {
+            save_json(&file.json_path, &file.bank)
+                .with_context(|| format!("could not save {}", file.json_path.display()))?;
+            file.modified = modified(&file.json_path);
+        }
+        Ok(())
+    }
+
+    /// A file changed on disk by something else (ie. synced by Dropbox) since
+    /// it was loaded or saved, saving now would overwrite those changes
+    pub fn changed_on_disk(&self) -> Option<&Path> {
+        self.files
+            .iter()
+            .find(|file| modified(&file.json_path) != file.modified)
+            .map(|file| file.json_path.as_path())
+    }
+
+    /// Replaces a file's questions with the version on disk, dropping the
+    /// responses made here
+    pub fn reload(&mut self, json_path: &Path) -> Result<Moved> {
+        let Some(at) = self
+            .files
+            .iter()
+            .position(|file| file.json_path == json_path)
+        else {
+            return Ok(Moved::default());
+        };
+        let mut disk = BankFile::load(json_path)?;
+        let file = &self.files[at];
+        disk.bank.rater_id = file.bank.rater_id.clone().or(disk.bank.rater_id);
+        disk.bank.key_order = file.bank.key_order;
+        self.replace(at, disk)
+    }
+
+    /// Takes the version on disk, adding the responses made here for questions
+    /// that have none there. Questions are matched by their id, or by their
+    /// stem for those without one, each taking the responses of one question.
+    pub fn merge(&mut self, json_path: &Path) -> Result<Moved> {
+        let Some(at) = self
+            .files
+            .iter()
+            .position(|file| file.json_path == json_path)
+        else {
+            return Ok(Moved::default());
+        };
+        let mut disk = BankFile::load(json_path)?;
+        let file = &self.files[at];
+        let mut taken = vec![false; file.bank.questions.len()];
+        for question in &mut disk.bank.questions {
+            let id = question.id();
+            let unused = |i: &usize| !taken[*i];
+            let ours = &file.bank.questions;
+            let found = (0..ours.len())
+                .filter(unused)
+                .find(|&i| id.is_some() && ours[i].id() == id)
+                .or_else(|| {
+                    (0..ours.len())
+                        .filter(unused)
+                        .find(|&i| ours[i].question == question.question)
+                });
+            if let Some(i) = found {
+                taken[i] = true;
+                question.fill_responses(&ours[i]);
+            }
+        }
+        disk.bank.rater_id = file.bank.rater_id.clone().or(disk.bank.rater_id);
+        disk.bank.key_order = file.bank.key_order;
+        self.replace(at, disk)
+    }
+
+    // swaps in the version on disk, unless the session would be left without
+    // questions. A reloaded file can have fewer of them, or more.
+    fn replace(&mut self, at: usize, disk: BankFile) -> Result<Moved> {
+        let moved = Moved {
+            start: self.files[..at]
+                .iter()
+                .map(|file| file.bank.questions.len())
+                .sum(),
+            old_len: self.files[at].bank.questions.len(),
+            new_len: disk.bank.questions.len(),
+        };
+        let old = std::mem::replace(&mut self.files[at], disk);
+        let old_view = self.view.clone();
+        if let Some(view) = &mut self.view {
+            *view = view
+                .iter()
+                .filter_map(|&index| moved.absolute(index))
+                .collect();
+        }
+        if self.is_empty() {
+            let json_path = old.json_path.display().to_string();
+            self.files[at] = old;
+            self.view = old_view;
+            bail!("{} has no questions left for this session", json_path);
+        }
+        Ok(moved)
+    }
+
+    /// Keeps the questions here, the next save overwrites the version on disk
+    pub fn keep(&mut self, json_path: &Path) {
+        for file in &mut self.files {
+            if file.json_path == json_path {
+                file.modified = modified(json_path);
+            }
+        }
+    }
+
+    /// The files of the session, in order
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|file| file.json_path.as_path())
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = (&Path, &Bank)> {
+        self.files
+            .iter()
+            .map(|file| (file.json_path.as_path(), &file.bank))
+    }
+
+    /// Warnings for files that don't match their manifest, see manifest.rs
+    pub fn check_manifests(&self) -> Result<Vec<String>> {
+        let mut warnings = vec![];
+        for (json_path, bank) in self.files() {
+            warnings.extend(manifest::check(json_path, bank)?);
+        }
+        Ok(warnings)
+    }
+
+    /// Number of questions gone through, across the files
+    pub fn len(&self) -> usize {
+        match &self.view {
+            Some(view) => view.len(),
+            None => self.total_len(),
+        }
+    }
+
+    // including the questions filtered out
+    fn total_len(&self) -> usize {
+        self.files
+            .iter()
+            .map(|file| file.bank.questions.len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn questions(&self) -> impl Iterator<Item = &Question> + Clone {
+        (0..self.len()).map(|index| self.question(index))
+    }
+
+    /// Only goes through the questions keep is true for
+    pub fn filter(&mut self, keep: impl Fn(&Question) -> bool) {
+        let view = (0..self.len())
+            .filter(|&index| keep(self.question(index)))
+            .map(|index| self.absolute(index))
+            .collect();
+        self.view = Some(view);
+    }
+
+    /// The index of a question among all of them, filtered out or not
+    pub fn absolute(&self, index: usize) -> usize {
+        match &self.view {
+            Some(view) => view[index],
+            None => index,
+        }
+    }
+
+    /// Goes through the questions in this order, as absolute() indexes
+    pub fn set_view(&mut self, view: Vec<usize>) {
+        self.view = Some(view);
+    }
+
+    /// The index of a question given by absolute(), if it isn't filtered out
+    pub fn position(&self, absolute: usize) -> Option<usize> {
+        match &self.view {
+            Some(view) => view.iter().position(|&index| index == absolute),
+            None => (absolute < self.len()).then_some(absolute),
+        }
+    }
+
+    pub fn question(&self, index: usize) -> &Question {
+        let (file, index) = self.locate(index);
+        &self.files[file].bank.questions[index]
+    }
+
+    /// The question's image, image_path is relative to its .json file
+    pub fn image_path(&self, index: usize) -> Option<PathBuf> {
+        let (file, i) = self.locate(index);
+        let file = &self.files[file];
+        let image_path = file.bank.questions[i].image_path.as_ref()?;
+        let json_dir = file.json_path.parent().unwrap_or(Path::new(""));
+        Some(json_dir.join(image_path))
+    }
+
+    /// Changes a question's responses through change, which returns whether
+    /// it changed anything. Changes are written to the audit log when it's on.
+    pub fn update(
+        &mut self,
+        index: usize,
+        action: &str,
+        change: impl FnOnce(&mut Question) -> bool,
+    ) -> Result<bool> {
+        let (file, i) = self.locate(index);
+        let file = &mut self.files[file];
+        let before = file.bank.questions[i].clone();
+        let changed = change(&mut file.bank.questions[i]);
+        if changed && self.audit {
+            // questions are identified by their "id" field, or their number in the file
+            let after = &file.bank.questions[i];
+            let id = after.extra.get("id").cloned().unwrap_or(json!(i + 1));
+            audit::record(
+                &file.json_path,
+                id,
+                action,
+                &before,
+                after,
+                file.bank.rater_id.as_deref(),
+            )?;
+        }
+        Ok(changed)
+    }
+
+    pub fn set_audit(&mut self, audit: bool) {
+        self.audit = audit;
+    }
+
+    /// The file and bank a question comes from, for its instructions and image
+    pub fn source(&self, index: usize) -> (&Path, &Bank) {
+        let file = &self.files[self.locate(index).0];
+        (&file.json_path, &file.bank)
+    }
+
+    /// Adds a topic to the taxonomy of the question's bank
+    pub fn add_topic(&mut self, index: usize, topic: &str) {
+        let file = self.locate(index).0;
+        self.files[file].bank.taxonomy.push(topic.to_string());
+    }
+
+    /// The file a question comes from and its "id" (or its number in the file),
+    /// as the notes name it
+    pub fn question_id(&self, index: usize) -> (&Path, String) {
+        let (file, i) = self.locate(index);
+        let file = &self.files[file];
+        let id = match file.bank.questions[i].extra.get("id") {
+            Some(Value::String(id)) => id.clone(),
+            Some(id) => id.to_string(),
+            None => (i + 1).to_string(),
+        };
+        (&file.json_path, id)
+    }
+
+    /// Writes the changes made to the file as a JSON Patch. With several files
+    /// the paths would be ambiguous, so that's an error.
+    pub fn write_patch(&self, patch_path: &Path) -> Result<()> {
+        let [file] = self.files.as_slice() else {
+            bail!("--patch-out only works with a single file");
+        };
+        let patch = serde_json::to_string_pretty(&file.bank.patch()?)?;
+        fs::write(patch_path, patch + "\n")
+            .wrap_err_with(|| format!("could not write {}", patch_path.display()))
+    }
+
+    pub fn set_key_order(&mut self, key_order: KeyOrder) {
+        for file in &mut self.files {
+            file.bank.key_order = key_order;
+        }
+    }
+
+    pub fn set_signing_key(&mut self, key: &str) {
+        for file in &mut self.files {
+            file.bank.signing_key = Some(key.to_string());
+        }
+    }
+
+    pub fn set_rater_id(&mut self, rater_id: &str) {
+        for file in &mut self.files {
+            file.bank.rater_id = Some(rater_id.to_string());
+        }
+    }
+
+    /// Counts a new session in every file's time_spent
+    pub fn start_timing(&mut self) {
+        for file in &mut self.files {
+            file.bank
+                .time_spent
+                .get_or_insert_with(Default::default)
+                .sessions += 1;
+        }
+    }
+
+    /// Adds time spent on a question to its file's time_spent
+    pub fn add_time(&mut self, index: usize, active: Duration, idle: Duration) {
+        let (file, i) = self.locate(index);
+        let bank = &mut self.files[file].bank;
+        let time_spent = bank.time_spent.get_or_insert_with(Default::default);
+        time_spent.dwell_seconds.resize(bank.questions.len(), 0.0);
+        time_spent.dwell_seconds[i] += active.as_secs_f64();
+        time_spent.active_seconds += active.as_secs_f64();
+        time_spent.idle_seconds += idle.as_secs_f64();
+    }
+
+    /// Checks blinded banks for answer keys and explanations, recording the
+    /// result in each. Returns the files that failed and their questions.
+    pub fn check_blinding(&mut self, time: &str) -> Vec<(&Path, Vec<usize>)> {
+        let mut failed = vec![];
+        for file in &mut self.files {
+            let blinding = blinding::check(&file.bank, time);
+            if !blinding.passed {
+                failed.push((file.json_path.as_path(), blinding.questions.clone()));
+            }
+            file.bank.blinding = Some(blinding);
+        }
+        failed
+    }
+
+    /// Stamps the start of a proctored session, a resumed exam keeps its first start
+    pub fn start_exam(&mut self, time: &str) {
+        for file in &mut self.files {
+            let exam = file.bank.exam.get_or_insert_with(|| Exam {
+                started: time.to_string(),
+                ended: None,
+            });
+            exam.ended = None;
+        }
+    }
+
+    /// Stamps the end of a proctored session
+    pub fn end_exam(&mut self, time: &str) {
+        for file in &mut self.files {
+            if let Some(exam) = &mut file.bank.exam {
+                exam.ended = Some(time.to_string());
+            }
+        }
+    }
+
+    // (file, index within that file) of a question
+    fn locate(&self, index: usize) -> (usize, usize) {
+        let mut index = self.absolute(index);
+        for (i, file) in self.files.iter().enumerate() {
+            if index < file.bank.questions.len() {
+                return (i, index);
+            }
+            index -= file.bank.questions.len();
+        }
+        panic!("question index out of range")
+    }
+}
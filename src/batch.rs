@@ -0,0 +1,111 @@
+use std::{fs::File, io, path::Path, str::FromStr};
+
+use color_eyre::{
+    eyre::{bail, WrapErr},
+    Result,
+};
+use serde::Deserialize;
+
+use crate::{bank::Banks, mode::Mode};
+
+/// A response given without the interface, with --set 12=3 or as a CSV row
+#[derive(Debug, Clone, Deserialize)]
+pub struct Assignment {
+    pub question: usize, // numbered from 1, as in the interface
+    pub value: String,
+}
+
+impl FromStr for Assignment {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Assignment, String> {
+        let (question, value) = text
+            .split_once('=')
+            .ok_or_else(|| format!("expected QUESTION=VALUE, got '{}'", text))?;
+        let question = question
+            .trim()
+            .parse()
+            .map_err(|_| format!("'{}' is not a question number", question))?;
+        Ok(Assignment {
+            question,
+            value: value.trim().to_string(),
+        })
+    }
+}
+
+/// Reads assignments from a CSV file with "question" and "value" columns
+pub fn read_csv(csv_path: &Path) -> Result<Vec<Assignment>> {
+    let is_stdin = csv_path == Path::new("-");
+    let reader: Box<dyn io::Read> = match is_stdin {
+        true => Box::new(io::stdin()),
+        false => Box::new(
+            File::open(csv_path)
+                .with_context(|| format!("could not read file: {}", csv_path.display()))?,
+        ),
+    };
+    csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader)
+        .deserialize()
+        .enumerate()
+        // + 2 for the header and counting from 1
+        .map(|(row, assignment)| {
+            let name = match is_stdin {
+                true => "stdin".to_string(),
+                false => csv_path.display().to_string(),
+            };
+            assignment.wrap_err_with(|| format!("{} line {}", name, row + 2))
+        })
+        .collect()
+}
+
+/// Sets every assignment, nothing is changed unless all of them are valid.
+/// action is what the audit log records, ie. "set answer".
+pub fn apply(
+    banks: &mut Banks,
+    mode: Mode,
+    assignments: &[Assignment],
+    action: &str,
+) -> Result<()> {
+    let mut changed = vec![];
+    for assignment in assignments {
+        let index = index_of(banks, assignment)?;
+        let mut question = banks.question(index).clone();
+        mode.set_value(&mut question, &assignment.value)
+            .wrap_err_with(|| format!("question {}", assignment.question))?;
+        changed.push((index, question));
+    }
+    for (index, question) in changed {
+        banks.update(index, action, |old| {
+            *old = question;
+            true
+        })?;
+    }
+    Ok(())
+}
+
+/// Applies precomputed responses (ie. from a model) before a session. Rows left
+/// blank are disputed and stay for the rater, and questions that already have a
+/// response keep it. Returns how many were applied.
+pub fn prefill(banks: &mut Banks, mode: Mode, assignments: &[Assignment]) -> Result<usize> {
+    let mut todo = vec![];
+    for assignment in assignments {
+        let index = index_of(banks, assignment)?;
+        if !assignment.value.is_empty() && !mode.is_done(banks.question(index)) {
+            todo.push(assignment.clone());
+        }
+    }
+    apply(banks, mode, &todo, &format!("prefill {}", mode.name()))?;
+    Ok(todo.len())
+}
+
+fn index_of(banks: &Banks, assignment: &Assignment) -> Result<usize> {
+    if assignment.question == 0 || assignment.question > banks.len() {
+        bail!(
+            "there is no question {}, the questions go from 1 to {}",
+            assignment.question,
+            banks.len()
+        );
+    }
+    Ok(assignment.question - 1)
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+use crate::bank::{Bank, Question};
+
+// Blinded sessions (--blinded) are run against a stripped copy of the bank, with
+// the answer key, explanations and what a model wrote knowing the key removed
+// so they can't bias the rater. Before the session starts every question is
+// checked for them being put back (eg. a rater merging in the original file),
+// and the session is refused if they were.
+// The result of the check is saved in the bank:
+//
+// "blinding": { "checked": "2026-01-05T14:03:11Z", "passed": false, "questions": [3, 7] }
+
+// fields of a question (or of its metadata) that give the answer away
+pub const REVEALING_FIELDS: [&str; 3] =
+    ["explanation", "answer_explanation", "option_explanations"];
+
+// written by a model that was given the answer key, see review.rs and drafts.rs
+const MODEL_FIELDS: [&str; 2] = ["llm_review", "draft_explanation"];
+
+/// Result of the check, saved in the bank
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Blinding {
+    pub checked: String,
+    pub passed: bool,
+    // numbers in the file of the questions with an answer or explanation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub questions: Vec<usize>,
+}
+
+/// Checks a stripped bank for answer keys and explanations
+pub fn check(bank: &Bank, time: &str) -> Blinding {
+    let questions: Vec<usize> = bank
+        .questions
+        .iter()
+        .enumerate()
+        .filter(|(_, question)| reveals_answer(question))
+        .map(|(i, _)| i + 1)
+        .collect();
+    Blinding {
+        checked: time.to_string(),
+        passed: questions.is_empty(),
+        questions,
+    }
+}
+
+// everything strip takes out, put back
+fn reveals_answer(question: &Question) -> bool {
+    let metadata_extra = question
+        .metadata
+        .iter()
+        .flat_map(|metadata| &metadata.extra);
+    let model_fields = question
+        .extra
+        .iter()
+        .filter(|(key, _)| MODEL_FIELDS.contains(&key.as_str()));
+    !question.answer.is_empty()
+        || question
+            .extra
+            .iter()
+            .chain(metadata_extra)
+            .filter(|(key, _)| REVEALING_FIELDS.contains(&key.as_str()))
+            .chain(model_fields)
+            .any(|(_, value)| !value.is_null())
+}
+
+/// A copy of the question without the answer key, explanations and responses,
+/// for handing out
+pub fn strip(question: &Question) -> Question {
+    let mut stripped = question.clone();
+    stripped.answer.clear();
+    stripped.clear_responses();
+    stripped.extra.retain(|key, _| {
+        !REVEALING_FIELDS.contains(&key.as_str()) && !MODEL_FIELDS.contains(&key.as_str())
+    });
+    if let Some(metadata) = &mut stripped.metadata {
+        metadata
+            .extra
+            .retain(|key, _| !REVEALING_FIELDS.contains(&key.as_str()));
+    }
+    stripped
+}
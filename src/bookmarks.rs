@@ -0,0 +1,39 @@
+use std::collections::BTreeSet;
+
+use ratatui::{prelude::*, widgets::ListItem};
+
+use crate::{bank::Banks, i18n::t, popup, theme::Palette};
+
+// Bookmarks are the rater's own way back to questions, nothing is written in
+// the bank so they never show up in exports. `b` toggles one on the current
+// question, `B` opens the jump list: pick a bookmark by its number, or with the
+// arrows and Enter.
+
+/// The bookmarked questions, by their index in the session
+pub type Bookmarks = BTreeSet<usize>;
+
+/// The jump list, over the middle of area
+pub fn render_jump_list(
+    frame: &mut Frame,
+    area: Rect,
+    banks: &Banks,
+    bookmarks: &Bookmarks,
+    selected: usize,
+    palette: &Palette,
+) {
+    let items: Vec<ListItem> = bookmarks
+        .iter()
+        .enumerate()
+        .map(|(n, &index)| {
+            // the first line of the stem, enough to recognise the question
+            let stem = banks.question(index).question.trim();
+            let stem = stem.lines().next().unwrap_or_default();
+            ListItem::new(Line::from(vec![
+                format!("{}. ", n + 1).fg(palette.action_key).bold(),
+                t!("bookmarks.question", number = index + 1).into(),
+                stem.to_string().into(),
+            ]))
+        })
+        .collect();
+    popup::list(frame, area, t!("bookmarks.title"), items, selected);
+}
@@ -0,0 +1,804 @@
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Parser, Subcommand, ValueHint};
+use color_eyre::{
+    eyre::{bail, eyre, WrapErr},
+    Result,
+};
+
+use crate::{
+    bank::Format, batch::Assignment, export::ExportFormat, import::Column, llm, mode::Mode, remote,
+    sample::Stratum, theme::Theme,
+};
+
+/// Command line arguments. `question_cli answer questions.json` and
+/// `question_cli classify questions.json` work as they always have, and
+/// `question_cli questions.json` opens the bank in the config's default_mode
+/// (or --mode).
+#[derive(Parser)]
+#[command(
+    version,
+    about,
+    override_usage = "question_cli [OPTIONS] <COMMAND>\n       question_cli [OPTIONS] <JSON_PATH>..."
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Question banks to open in the default_mode from the config file
+    #[arg(value_name = "JSON_PATH", value_hint = ValueHint::FilePath)]
+    pub json_paths: Vec<PathBuf>,
+
+    /// Mode to open JSON_PATH in, instead of the default_mode
+    #[arg(long, value_enum, requires = "json_paths")]
+    pub mode: Option<Mode>,
+
+    #[command(flatten)]
+    pub options: Options,
+}
+
+// flags shared by every subcommand, they can go before or after it
+#[derive(Args)]
+pub struct Options {
+    /// Language of the interface (en, fr), defaults to the system language
+    #[arg(long, global = true)]
+    pub lang: Option<String>,
+
+    /// Config file to use instead of ~/.config/question_cli/config.toml
+    #[arg(long, global = true, value_hint = ValueHint::FilePath)]
+    pub config: Option<PathBuf>,
+
+    /// Colour scheme
+    #[arg(long, global = true, value_enum)]
+    pub theme: Option<Theme>,
+
+    /// Save automatically every N seconds when there are changes (0 turns it off)
+    #[arg(long, global = true, value_name = "SECONDS")]
+    pub autosave: Option<u64>,
+
+    /// Rater ID recorded in the saved file
+    #[arg(long, global = true)]
+    pub rater: Option<String>,
+
+    /// Use this profile's config file, keymap, logs and session files, for
+    /// several people sharing one computer account
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Answer the questions, your answers are saved as "human_answer"
+    Answer(Session),
+    /// Classify the questions as higher order or not, saved as "is_higher_order"
+    Classify(Session),
+    /// Rate the quality of each question from 1 to 5, saved as "rating"
+    Rate(Session),
+    /// Pick the final answer where the rater disagreed with the key, saved as "adjudicated_answer"
+    Adjudicate(Session),
+    /// Answer with the correct answer shown right away and a running score
+    Quiz(Session),
+    /// Like quiz, and the explanation and reference are shown once you've answered
+    Learn(Session),
+    /// Classify the questions on several axes set in the config, each saved in its own field
+    Dimensions(Session),
+    /// Assign the questions to topics of the curriculum taxonomy, saved as "topic"
+    Topic(Session),
+    /// Respond to the questions of a plugin's item type (their "type", eg.
+    /// numeric), saved as "plugin_response"
+    Plugin(Session),
+    /// Triage the model's critiques of the questions, saved as "review_triage".
+    /// With --llm the model critiques them first, see the [llm] config.
+    Review {
+        #[command(flatten)]
+        session: Session,
+
+        /// Ask the model of the config to critique the questions, saved in
+        /// their "llm_review", instead of opening them
+        #[arg(long)]
+        llm: bool,
+
+        /// Model to ask instead of the config's
+        #[arg(long, requires = "llm")]
+        model: Option<String>,
+
+        #[command(flatten)]
+        backend: Backend,
+    },
+    /// Approve or reject the distractors proposed for questions with fewer than
+    /// 4 options. With --llm the model proposes them first, see the [llm] config.
+    Distractors {
+        #[command(flatten)]
+        session: Session,
+
+        /// Ask the model of the config for distractors, saved pending in the
+        /// questions' "proposed_distractors", instead of opening them
+        #[arg(long)]
+        llm: bool,
+
+        /// Model to ask instead of the config's
+        #[arg(long, requires = "llm")]
+        model: Option<String>,
+
+        #[command(flatten)]
+        backend: Backend,
+    },
+    /// Edit and accept the explanations drafted for the questions without one.
+    /// With --llm the model drafts them first, see the [llm] config.
+    Author {
+        #[command(flatten)]
+        session: Session,
+
+        /// Ask the model of the config to draft the missing explanations, saved
+        /// as "draft_explanation", instead of opening them
+        #[arg(long)]
+        llm: bool,
+
+        /// Model to ask instead of the config's
+        #[arg(long, requires = "llm")]
+        model: Option<String>,
+
+        #[command(flatten)]
+        backend: Backend,
+    },
+    /// Compare two models' answers and reasoning side by side, marking which are
+    /// acceptable. With --llm each --model answers the questions first.
+    Compare {
+        #[command(flatten)]
+        session: Session,
+
+        /// Model to compare, twice: the left one then the right one. With --llm
+        /// each of them (the config's model by default) answers the questions.
+        #[arg(long = "model", value_name = "MODEL")]
+        models: Vec<String>,
+
+        /// Ask the models to answer the questions, saved in their
+        /// "model_answers", instead of opening them
+        #[arg(long)]
+        llm: bool,
+
+        #[command(flatten)]
+        backend: Backend,
+    },
+    /// Index the question stems with an embedding model (embedding_model in
+    /// the [llm] config), for search. Only new or changed stems are embedded.
+    Embed {
+        #[arg(value_name = "JSON_PATH", required = true, value_hint = ValueHint::FilePath)]
+        json_paths: Vec<PathBuf>,
+
+        /// Embedding model to use instead of the config's
+        #[arg(long)]
+        model: Option<String>,
+
+        /// Kind of server the model is on
+        #[arg(long, value_enum)]
+        provider: Option<llm::Provider>,
+
+        /// URL of the server
+        #[arg(long, value_name = "URL")]
+        endpoint: Option<String>,
+    },
+    /// List the questions closest in meaning to a text, eg. search
+    /// "anticoagulation in pregnancy" questions.json, once they're embedded
+    Search {
+        query: String,
+
+        #[arg(value_name = "JSON_PATH", required = true, value_hint = ValueHint::FilePath)]
+        json_paths: Vec<PathBuf>,
+
+        /// How many questions to list
+        #[arg(long, short = 'n', default_value_t = 10)]
+        count: usize,
+
+        /// Print the results as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Kind of server the embedding model is on
+        #[arg(long, value_enum)]
+        provider: Option<llm::Provider>,
+
+        /// URL of the server
+        #[arg(long, value_name = "URL")]
+        endpoint: Option<String>,
+    },
+    /// Group the questions by meaning, from their embeddings, to spot topics
+    /// over-represented in the blueprint
+    Cluster {
+        #[arg(value_name = "JSON_PATH", required = true, value_hint = ValueHint::FilePath)]
+        json_paths: Vec<PathBuf>,
+
+        /// Average cosine similarity the questions of a cluster need, higher
+        /// for tighter clusters
+        #[arg(long, default_value_t = 0.6)]
+        threshold: f32,
+
+        /// Print the clusters as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List the pairs of questions that are likely duplicates, by their words
+    /// or, with --embeddings, by meaning
+    Dedupe {
+        #[arg(value_name = "JSON_PATH", required = true, value_hint = ValueHint::FilePath)]
+        json_paths: Vec<PathBuf>,
+
+        /// Compare the embeddings of the stems (question_cli embed first)
+        /// instead of their words, to catch rewordings of the same question
+        #[arg(long)]
+        embeddings: bool,
+
+        /// How similar two stems must be, from 0 to 1 (0.8 by words, 0.9 by
+        /// embeddings)
+        #[arg(long)]
+        threshold: Option<f32>,
+
+        /// Print the pairs as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Set responses without opening the interface, eg. question_cli set answer questions.json --set 12=3
+    Set {
+        #[arg(value_enum)]
+        mode: Mode,
+
+        #[command(flatten)]
+        files: Files,
+
+        /// Question number (as shown in the interface) and its value: an option
+        /// number or text, true/false when classifying or a rating from 1 to 5
+        #[arg(long = "set", value_name = "QUESTION=VALUE")]
+        assignments: Vec<Assignment>,
+
+        /// CSV file with "question" and "value" columns, - for stdin
+        #[arg(long, value_name = "CSV", value_hint = ValueHint::FilePath)]
+        from: Option<PathBuf>,
+    },
+    /// Answer in a web browser instead of the terminal, eg. question_cli serve questions.json --port 8080
+    Serve {
+        /// answer, classify or rate
+        #[arg(long, value_enum, default_value_t = Mode::Answer)]
+        mode: Mode,
+
+        #[command(flatten)]
+        files: Files,
+
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+
+        /// Address to listen on, 0.0.0.0 to let other computers on the network connect
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+    },
+    /// Serve sessions over SSH, run by sshd for the account raters connect to
+    /// (see the README): each rater is known by their key and works on their
+    /// own copy of the bank
+    Ssh {
+        /// answer, classify, rate...
+        #[arg(long, value_enum, default_value_t = Mode::Answer)]
+        mode: Mode,
+
+        /// The raters' public keys, one per line: the rater_id, then the key as
+        /// in authorized_keys, eg. R03 ssh-ed25519 AAAA... r03@laptop
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        raters: PathBuf,
+
+        #[command(flatten)]
+        session: Session,
+    },
+    /// Host a live session for teaching, participants join with `question_cli join`
+    /// and their responses are collected as they're given
+    Host {
+        /// answer, classify or rate
+        #[arg(long, value_enum, default_value_t = Mode::Answer)]
+        mode: Mode,
+
+        #[arg(value_hint = ValueHint::FilePath)]
+        json_path: PathBuf,
+
+        #[arg(long, default_value_t = 9001)]
+        port: u16,
+
+        /// Address to listen on, 0.0.0.0 to let other computers on the network connect
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+    },
+    /// Join a live session, eg. question_cli join ws://192.168.1.20:9001 --rater R03
+    Join {
+        /// ws:// address given by the host
+        url: String,
+    },
+    /// Print how many questions have been answered and classified, and with
+    /// raters' copies of a bank the reliability of its scores
+    Stats {
+        /// PATH to the .json file, - to read it from stdin, or one answered
+        /// copy of the bank per rater
+        #[arg(value_name = "JSON_PATH", required = true, value_hint = ValueHint::FilePath)]
+        json_paths: Vec<PathBuf>,
+        /// Print the numbers as JSON
+        #[arg(long)]
+        json: bool,
+        /// Also draw the score distribution and each question's item
+        /// characteristic curve
+        #[arg(long, conflicts_with = "json")]
+        plot: bool,
+    },
+    /// Rewrite a bank as a plain list of questions or as an object with bank settings
+    Convert {
+        /// Bank to convert, - to read it from stdin
+        #[arg(value_hint = ValueHint::FilePath)]
+        input: PathBuf,
+        /// Where to write the converted bank, - for stdout
+        #[arg(value_hint = ValueHint::FilePath)]
+        output: PathBuf,
+        #[arg(long, value_enum)]
+        to: Format,
+    },
+    /// Make a bank from a spreadsheet, one question per row (see the README for the columns)
+    Import {
+        /// .xlsx (or .xls, .ods) file to import
+        #[arg(
+            required_unless_present = "google_sheet",
+            conflicts_with = "google_sheet",
+            value_hint = ValueHint::FilePath
+        )]
+        input: Option<PathBuf>,
+
+        /// Worksheet of the input file, the first one by default
+        #[arg(long, conflicts_with = "google_sheet")]
+        sheet: Option<String>,
+
+        /// ID of a Google Sheet shared with your service account (the long part of its link)
+        #[arg(long, value_name = "ID")]
+        google_sheet: Option<String>,
+
+        /// Cells of the Google Sheet to read, eg. "Sheet1!A:H", the first sheet by default
+        #[arg(long, conflicts_with = "input")]
+        range: Option<String>,
+
+        /// Column for a field when the headers aren't the usual ones, eg.
+        /// --column question=Stem --column option="Choice 1" --column option="Choice 2"
+        #[arg(long = "column", value_name = "FIELD=HEADER")]
+        columns: Vec<Column>,
+
+        /// The bank to write
+        #[arg(long, short, value_hint = ValueHint::FilePath)]
+        output: PathBuf,
+    },
+    /// Draw a random subset of a bank, eg. for a pilot: n questions, or n of
+    /// each tag or topic with --per
+    Sample {
+        #[arg(value_hint = ValueHint::FilePath)]
+        json_path: PathBuf,
+
+        /// How many questions to draw (of each tag or topic with --per)
+        #[arg(short)]
+        n: usize,
+
+        /// Draw n questions of each tag or topic, to keep the bank's blueprint
+        #[arg(long, value_enum)]
+        per: Option<Stratum>,
+
+        /// Seed of the draw, the same seed draws the same questions
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// The subset to write
+        #[arg(long, short, value_hint = ValueHint::FilePath)]
+        output: PathBuf,
+    },
+    /// Look for common item-writing flaws ("all of the above", the answer being
+    /// the longest option...) and print a checklist for reviewers
+    Lint {
+        #[arg(value_name = "JSON_PATH", required = true, value_hint = ValueHint::FilePath)]
+        json_paths: Vec<PathBuf>,
+
+        /// Print the checklist of every question, not just the flagged ones
+        #[arg(long)]
+        all: bool,
+    },
+    /// Measure how much reading each stem takes (words, Flesch-Kincaid grade)
+    /// and flag the outliers
+    Readability {
+        #[arg(value_name = "JSON_PATH", required = true, value_hint = ValueHint::FilePath)]
+        json_paths: Vec<PathBuf>,
+
+        /// Print the numbers as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Report the likely typos in the stems and options, with the closest known word
+    Spellcheck {
+        #[arg(value_name = "JSON_PATH", required = true, value_hint = ValueHint::FilePath)]
+        json_paths: Vec<PathBuf>,
+
+        /// Word list to check against, one word per line, instead of the
+        /// config's dictionary (/usr/share/dict/words by default)
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        dictionary: Option<PathBuf>,
+
+        /// File of words to accept too (terms of your field, names...), on top
+        /// of the config's spelling_allowlist
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        allow: Vec<PathBuf>,
+    },
+    /// Write the bank as a paper exam in Markdown, eg. question_cli print questions.json --answer-sheet > exam.md
+    Print {
+        /// Banks to print, several are numbered as one exam
+        #[arg(value_name = "JSON_PATH", required = true, value_hint = ValueHint::FilePath)]
+        json_paths: Vec<PathBuf>,
+
+        /// Heading of the exam, the file name by default
+        #[arg(long)]
+        title: Option<String>,
+
+        /// Add an answer sheet with a row of boxes per question
+        #[arg(long)]
+        answer_sheet: bool,
+
+        /// Print only the answer key for graders: each question's number (and
+        /// id), correct option and classification
+        #[arg(long, conflicts_with = "answer_sheet")]
+        key: bool,
+
+        /// Print only the questions on this exam form (their "forms"), and
+        /// those on every form
+        #[arg(long)]
+        form: Option<String>,
+
+        /// Write to this file instead of stdout
+        #[arg(long, short, value_hint = ValueHint::FilePath)]
+        output: Option<PathBuf>,
+    },
+    /// Export the responses in raters' copies of a bank, eg. for REDCap
+    Export {
+        #[arg(long, value_enum)]
+        to: ExportFormat,
+
+        /// One answered copy of the bank per rater (the banks, for embeddings)
+        #[arg(value_name = "JSON_PATH", required = true, value_hint = ValueHint::FilePath)]
+        json_paths: Vec<PathBuf>,
+
+        /// Write to this file instead of stdout
+        #[arg(long, short, value_hint = ValueHint::FilePath)]
+        output: Option<PathBuf>,
+    },
+    /// Score raters' copies of a bank against the answer key, questions counting
+    /// for their "weight"
+    Grade {
+        /// One answered copy of the bank per rater
+        #[arg(value_name = "JSON_PATH", required = true, value_hint = ValueHint::FilePath)]
+        json_paths: Vec<PathBuf>,
+
+        /// Print the scores as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Percent of the points needed to pass, instead of the config's pass_mark
+        #[arg(long)]
+        pass_mark: Option<f64>,
+    },
+    /// Estimate the questions' difficulties and raters' abilities with the Rasch
+    /// model, from raters' copies of a bank
+    Rasch {
+        /// One answered copy of the bank per rater
+        #[arg(value_name = "JSON_PATH", required = true, value_hint = ValueHint::FilePath)]
+        json_paths: Vec<PathBuf>,
+
+        /// Calibrate each "group" of copies on its own, to compare the difficulties
+        #[arg(long)]
+        by_group: bool,
+
+        /// Print the estimates as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Convert scores on one form to the scale of another, through the anchor
+    /// questions they share (the same "id")
+    Equate {
+        /// Answered copies of the form whose scores are converted
+        #[arg(long, num_args = 1.., required = true, value_hint = ValueHint::FilePath)]
+        form_x: Vec<PathBuf>,
+
+        /// Answered copies of the form with the scale to convert to
+        #[arg(long, num_args = 1.., required = true, value_hint = ValueHint::FilePath)]
+        form_y: Vec<PathBuf>,
+
+        /// Print the equating as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check that saved files weren't changed after they were signed (see signing_key)
+    Verify {
+        #[arg(value_name = "JSON_PATH", required = true, value_hint = ValueHint::FilePath)]
+        json_paths: Vec<PathBuf>,
+    },
+    /// Write sha256 checksums of banks before sending them out, each rater's copy
+    /// is checked against them when opened
+    Manifest {
+        #[arg(value_name = "JSON_PATH", required = true, value_hint = ValueHint::FilePath)]
+        json_paths: Vec<PathBuf>,
+    },
+    /// Make a packet per rater (questions.R01.json for R01), each going through
+    /// the questions in a counterbalanced order
+    Counterbalance {
+        #[arg(value_hint = ValueHint::FilePath)]
+        json_path: PathBuf,
+
+        /// File of rater IDs, one per line
+        #[arg(long, value_hint = ValueHint::FilePath, required_unless_present = "raters")]
+        roster: Option<PathBuf>,
+
+        /// Rater IDs, eg. --raters R01,R02,R03
+        #[arg(
+            long,
+            value_name = "RATER",
+            value_delimiter = ',',
+            conflicts_with = "roster"
+        )]
+        raters: Vec<String>,
+    },
+    /// Show when each bank was worked on, for how long and how far along it got,
+    /// from the event logs (event_log = true or --event-log)
+    History,
+    /// Print a completion script for your shell, eg. question_cli completions bash > /etc/bash_completion.d/question_cli
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+}
+
+// an interactive answering/classifying session
+#[derive(Args)]
+pub struct Session {
+    #[command(flatten)]
+    pub files: Files,
+
+    /// Question number to start at, instead of where you left off
+    #[arg(long, value_name = "N")]
+    pub start: Option<usize>,
+
+    /// CSV file of precomputed responses ("question" and "value" columns) to
+    /// apply first. Blank values are left for you to review, the session starts
+    /// at the first question still missing a response.
+    #[arg(long, value_name = "CSV", value_hint = ValueHint::FilePath)]
+    pub from: Option<PathBuf>,
+
+    /// Log navigation and responses with timestamps for research (see
+    /// event_log in the config file)
+    #[arg(long)]
+    pub event_log: bool,
+
+    /// Run as an exam: quitting before the end needs the proctor_code from the
+    /// config file, the event log is on and the start and end times are saved
+    #[arg(long)]
+    pub proctored: bool,
+
+    /// The bank was stripped of its answer key and explanations: refuse to start
+    /// if they were put back, and save the result of the check in the bank
+    #[arg(long)]
+    pub blinded: bool,
+
+    /// Work on your own copy of each bank (questions.R03.json for rater R03),
+    /// made the first time. For raters sharing one account, eg. over SSH.
+    #[arg(long)]
+    pub per_rater: bool,
+
+    /// Work on a named session's own copy of each bank (questions.attempt2.json
+    /// for attempt2), made without the responses the first time. For taking a
+    /// quiz again and comparing attempts.
+    #[arg(long, value_name = "NAME")]
+    pub session: Option<String>,
+
+    /// Only go through the questions still missing a response in this mode
+    #[arg(long)]
+    pub only_unanswered: bool,
+
+    /// Only go through the questions with one of these "tags", eg. --tags cardio,renal
+    #[arg(long, value_name = "TAG", value_delimiter = ',')]
+    pub tags: Vec<String>,
+
+    /// Only go through the questions flagged for review ("flagged": true)
+    #[arg(long)]
+    pub flagged: bool,
+
+    /// Only go through the questions on this exam form (their "forms"), and
+    /// those on every form
+    #[arg(long)]
+    pub form: Option<String>,
+
+    /// Show the key and N of each question's distractors, picked at random for
+    /// each rater and saved in the question's "shown_options"
+    #[arg(long, value_name = "N")]
+    pub distractors: Option<usize>,
+
+    /// Once every question is done, POST the saved file(s) to this URL with the
+    /// submit_token from the config file as a bearer token
+    #[arg(long, value_name = "URL")]
+    pub submit_url: Option<String>,
+}
+
+// where --llm asks, instead of the [llm] config
+#[derive(Args)]
+pub struct Backend {
+    /// Kind of server the model is on: OpenAI's API (or one like it) or a
+    /// local Ollama
+    #[arg(long, value_enum, requires = "llm")]
+    pub provider: Option<llm::Provider>,
+
+    /// URL of the server, eg. http://localhost:11434
+    #[arg(long, value_name = "URL", requires = "llm")]
+    pub endpoint: Option<String>,
+
+    /// Prompt template to ask with instead of the built-in prompt: a TOML
+    /// file with the system message and the prompt, see the README
+    #[arg(long, value_name = "TOML", requires = "llm", value_hint = ValueHint::FilePath)]
+    pub prompt: Option<PathBuf>,
+}
+
+impl Backend {
+    /// The [llm] config with the flags and model given over it
+    pub fn settings(&self, config: &llm::Settings, model: Option<&String>) -> llm::Settings {
+        config.with(
+            self.provider,
+            self.endpoint.as_ref(),
+            model.or(config.model.as_ref()),
+        )
+    }
+}
+
+// the banks a session or `set` works on
+#[derive(Args)]
+pub struct Files {
+    /// PATH to the .json file, or an https:// link to one. Several files or a quoted
+    /// glob ("part*.json") are gone through as one session, each question is saved
+    /// back to its own file.
+    #[arg(value_name = "JSON_PATH", required = true, value_hint = ValueHint::FilePath)]
+    pub json_paths: Vec<PathBuf>,
+
+    /// Also write the changes made as an RFC 6902 JSON Patch to this file, for
+    /// applying the responses to another copy of the bank
+    #[arg(long, value_name = "PATCH_PATH", value_hint = ValueHint::FilePath)]
+    pub patch_out: Option<PathBuf>,
+}
+
+impl Session {
+    /// A session on json_paths with nothing else set
+    pub fn of(json_paths: Vec<PathBuf>) -> Session {
+        Session {
+            files: Files {
+                json_paths,
+                patch_out: None,
+            },
+            start: None,
+            from: None,
+            event_log: false,
+            proctored: false,
+            blinded: false,
+            per_rater: false,
+            session: None,
+            only_unanswered: false,
+            tags: vec![],
+            flagged: false,
+            form: None,
+            distractors: None,
+            submit_url: None,
+        }
+    }
+}
+
+// written next to a bank by notes.rs, state.rs, manifest.rs, embeddings.rs
+// and live.rs, they aren't banks
+const SIDECARS: [&str; 5] = [
+    ".notes.json",
+    ".session.json",
+    ".manifest.json",
+    ".embeddings.json",
+    ".live.json",
+];
+
+// a glob leaves out the files next to a bank and its per-rater or session
+// copies (questions.alice.json next to questions.json, see shared.rs)
+fn is_sidecar(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    if SIDECARS.iter().any(|suffix| name.ends_with(suffix)) {
+        return true;
+    }
+    let Some(at) = name.find(".json") else {
+        return false;
+    };
+    match name[..at].rsplit_once('.') {
+        Some((stem, _label)) => path
+            .with_file_name(format!("{}{}", stem, &name[at..]))
+            .exists(),
+        None => false,
+    }
+}
+
+impl Files {
+    /// The files to open in an interactive session, see resolve_piped
+    pub fn resolve(&self) -> Result<Vec<PathBuf>> {
+        if self
+            .json_paths
+            .iter()
+            .any(|json_path| json_path == Path::new("-"))
+        {
+            bail!("- (stdin) can't be used in interactive sessions");
+        }
+        self.resolve_piped()
+    }
+
+    /// The files to open, with globs the shell didn't expand (ie. on Windows)
+    /// expanded and links replaced by their downloaded copy. Without the
+    /// interface (set, review --llm...) - reads the bank from stdin and the
+    /// result is written to stdout.
+    pub fn resolve_piped(&self) -> Result<Vec<PathBuf>> {
+        let mut json_paths = vec![];
+        for json_path in &self.json_paths {
+            if json_path == Path::new("-") {
+                json_paths.push(json_path.clone());
+                continue;
+            }
+            if remote::is_url(json_path) {
+                json_paths.push(remote::fetch(&json_path.to_string_lossy())?);
+                continue;
+            }
+            let pattern = json_path.to_string_lossy();
+            if !pattern.contains(['*', '?', '[']) {
+                json_paths.push(json_path.clone());
+                continue;
+            }
+            let mut matches = glob::glob(&pattern)
+                .wrap_err_with(|| format!("invalid glob: {}", pattern))?
+                .collect::<Result<Vec<_>, _>>()?;
+            matches.retain(|path| !is_sidecar(path));
+            if matches.is_empty() {
+                return Err(eyre!("no files match {}", pattern));
+            }
+            json_paths.extend(matches);
+        }
+        Ok(json_paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn globs_leave_out_sidecars_and_copies() {
+        let dir = std::env::temp_dir().join(format!("question_cli_glob_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let names = [
+            "cardio.json",
+            "cardio.notes.json",
+            "cardio.alice.notes.json",
+            "cardio.alice.session.json",
+            "cardio.manifest.json",
+            "cardio.embeddings.json",
+            "cardio.live.json",
+            "cardio.alice.json",
+            "cardio.attempt2.json",
+            "renal.json",
+            // not a copy, there's no neuro.json
+            "neuro.2024.json",
+        ];
+        for name in names {
+            fs::write(dir.join(name), "[]").unwrap();
+        }
+        let files = Files {
+            json_paths: vec![dir.join("*.json")],
+            patch_out: None,
+        };
+        let names: Vec<String> = files
+            .resolve()
+            .unwrap()
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, ["cardio.json", "neuro.2024.json", "renal.json"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,197 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use serde::Serialize;
+
+use crate::{bank::Banks, embeddings, embeddings::Embeddings, i18n::t};
+
+// `question_cli cluster questions.json` groups the questions by meaning, from
+// the embeddings of `question_cli embed` (see embeddings.rs), to spot topics
+// the bank covers more than its blueprint says. Clusters are merged by average
+// linkage: the two closest are merged while the average similarity between
+// their questions is at least --threshold (0.6 by default; how similar is
+// similar depends on the model). The biggest clusters are listed first, each
+// with its topics when questions have one and its most typical question first.
+
+/// A group of similar questions
+#[derive(Debug, Serialize)]
+pub struct Cluster {
+    pub questions: Vec<usize>, // from 1, the most typical first
+    pub share: f64,            // of the questions embedded
+    pub topics: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Clusters {
+    pub model: String,
+    pub threshold: f32,
+    pub clusters: Vec<Cluster>,
+    pub unclustered: usize,
+    pub stale: usize, // changed since embedded, left out
+}
+
+pub fn of(banks: &Banks, embeddings: &Embeddings, threshold: f32) -> Clusters {
+    let indices: Vec<usize> = (0..embeddings.vectors.len())
+        .filter(|&i| embeddings.vectors[i].is_some())
+        .collect();
+    let vectors: Vec<&[f32]> = indices
+        .iter()
+        .filter_map(|&i| embeddings.vectors[i].as_deref())
+        .collect();
+    let n = vectors.len();
+    let mut similarity: Vec<Vec<f32>> = (0..n)
+        .map(|a| {
+            (0..n)
+                .map(|b| embeddings::cosine(vectors[a], vectors[b]))
+                .collect()
+        })
+        .collect();
+
+    // members of each cluster, merged ones emptied, and each cluster's closest
+    // other one, so a merge doesn't go through every pair again
+    let mut members: Vec<Vec<usize>> = (0..n).map(|a| vec![a]).collect();
+    let closest = |a: usize, similarity: &[Vec<f32>], members: &[Vec<usize>]| {
+        (0..n)
+            .filter(|&b| b != a && !members[b].is_empty())
+            .map(|b| (b, similarity[a][b]))
+            .max_by(|x, y| x.1.total_cmp(&y.1))
+    };
+    let mut best: Vec<Option<(usize, f32)>> =
+        (0..n).map(|a| closest(a, &similarity, &members)).collect();
+    while let Some((a, b)) = best
+        .iter()
+        .enumerate()
+        .filter(|(a, _)| !members[*a].is_empty())
+        .filter_map(|(a, best)| best.map(|(b, value)| (a, b, value)))
+        .max_by(|x, y| x.2.total_cmp(&y.2))
+        .filter(|&(_, _, value)| value >= threshold)
+        .map(|(a, b, _)| (a, b))
+    {
+        // b goes into a, the average similarity to a weighted by their sizes
+        let (size_a, size_b) = (members[a].len() as f32, members[b].len() as f32);
+        let merged: Vec<f32> = (0..n)
+            .map(|c| (similarity[a][c] * size_a + similarity[b][c] * size_b) / (size_a + size_b))
+            .collect();
+        for (c, value) in merged.into_iter().enumerate() {
+            similarity[a][c] = value;
+            similarity[c][a] = value;
+        }
+        let moved = std::mem::take(&mut members[b]);
+        members[a].extend(moved);
+        best[b] = None;
+        best[a] = closest(a, &similarity, &members);
+        for c in 0..n {
+            if c == a || members[c].is_empty() {
+                continue;
+            }
+            match best[c] {
+                Some((other, _)) if other == a || other == b => {
+                    best[c] = closest(c, &similarity, &members);
+                }
+                Some((_, value)) if similarity[c][a] > value => {
+                    best[c] = Some((a, similarity[c][a]))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut clusters: Vec<Cluster> = members
+        .into_iter()
+        .filter(|members| members.len() > 1)
+        .map(|mut members| {
+            // the question closest to the others on average
+            let typicality = |a: &usize| -> f32 {
+                members
+                    .iter()
+                    .map(|&b| embeddings::cosine(vectors[*a], vectors[b]))
+                    .sum()
+            };
+            let scores: Vec<f32> = members.iter().map(typicality).collect();
+            let mut order: Vec<usize> = (0..members.len()).collect();
+            order.sort_by(|&x, &y| scores[y].total_cmp(&scores[x]));
+            members = order.into_iter().map(|i| members[i]).collect();
+            let mut topics = BTreeMap::new();
+            for &member in &members {
+                if let Some(topic) = banks.question(indices[member]).topic() {
+                    *topics.entry(topic.to_string()).or_default() += 1;
+                }
+            }
+            Cluster {
+                share: members.len() as f64 / n as f64,
+                questions: members.iter().map(|&member| indices[member] + 1).collect(),
+                topics,
+            }
+        })
+        .collect();
+    clusters.sort_by_key(|cluster| std::cmp::Reverse(cluster.questions.len()));
+    let clustered: usize = clusters.iter().map(|cluster| cluster.questions.len()).sum();
+    Clusters {
+        model: embeddings.model.clone(),
+        threshold,
+        clusters,
+        unclustered: n - clustered,
+        stale: embeddings.stale(),
+    }
+}
+
+impl Clusters {
+    pub fn print(&self, banks: &Banks, out: &mut impl Write) -> io::Result<()> {
+        for (i, cluster) in self.clusters.iter().enumerate() {
+            writeln!(
+                out,
+                "{}",
+                t!(
+                    "clusters.cluster",
+                    number = i + 1,
+                    count = cluster.questions.len(),
+                    percent = format!("{:.0}", cluster.share * 100.0)
+                )
+            )?;
+            if !cluster.topics.is_empty() {
+                let mut topics: Vec<(&String, &usize)> = cluster.topics.iter().collect();
+                topics.sort_by(|a, b| b.1.cmp(a.1));
+                let topics: Vec<String> = topics
+                    .iter()
+                    .map(|(topic, count)| format!("{} {}", topic, count))
+                    .collect();
+                writeln!(
+                    out,
+                    "  {}",
+                    t!("clusters.topics", topics = topics.join(", "))
+                )?;
+            }
+            for &number in &cluster.questions {
+                let question = banks.question(number - 1);
+                let stem = question.question.trim().lines().next().unwrap_or_default();
+                let stem: String = match stem.chars().count() > 70 {
+                    true => stem.chars().take(69).chain(['…']).collect(),
+                    false => stem.to_string(),
+                };
+                let number = match question.id() {
+                    Some(id) => format!("{} ({})", number, id),
+                    None => number.to_string(),
+                };
+                writeln!(out, "  {:<12} {}", number, stem)?;
+            }
+            writeln!(out)?;
+        }
+        writeln!(
+            out,
+            "{}",
+            t!(
+                "clusters.summary",
+                clusters = self.clusters.len(),
+                unclustered = self.unclustered,
+                threshold = self.threshold,
+                model = self.model.as_str()
+            )
+        )?;
+        if self.stale > 0 {
+            writeln!(out, "{}", t!("embeddings.stale", count = self.stale))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,247 @@
+use std::sync::OnceLock;
+
+use color_eyre::Result;
+use ratatui::{
+    style::{Style, Stylize},
+    text::Line,
+};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    bank::{Banks, Question},
+    i18n::t,
+    llm::{self, Client},
+    markdown,
+    prompts::Template,
+    theme::Palette,
+};
+
+// Comparing two models head to head, for model evaluation studies.
+// `question_cli compare --llm --model gpt-4o --model llama3 questions.json`
+// has each model answer each question with its reasoning, saved by model:
+//
+// "model_answers": {
+//   "gpt-4o": {"answer": "Amlodipine", "reasoning": "..."},
+//   "llama3": {"answer": "Lisinopril", "reasoning": "..."}
+// }
+//
+// `question_cli compare --model gpt-4o --model llama3 questions.json` then
+// shows the two side by side (the first two that answered without --model),
+// and the reviewer marks which are acceptable: 1 the left one, 2 the right
+// one, 3 both, 4 neither, saved by model:
+//
+// "model_acceptable": {"gpt-4o": true, "llama3": false}
+//
+// Each run asks about every question, the replies cached (see llm.rs) so only
+// new or edited questions go to the models. An answer that changes loses its
+// judgment.
+
+const INSTRUCTIONS: &str = "You answer multiple choice questions for medical exams. Give \
+the number of the best option on the first line, as \"Answer: 2\", then explain your \
+reasoning briefly.";
+
+pub const JUDGMENTS: [&str; 4] = ["left", "right", "both", "neither"];
+
+static MODELS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Sets the models to compare, from --model
+pub fn init(models: &[String]) {
+    let _ = MODELS.set(models.to_vec());
+}
+
+/// The two models compared on the question: the ones given, or the first two
+/// that answered it
+pub fn pair(question: &Question) -> Option<(String, String)> {
+    let models: Vec<String> = match MODELS.get() {
+        Some(models) if !models.is_empty() => models.clone(),
+        _ => answers(question)?.keys().cloned().collect(),
+    };
+    match models.as_slice() {
+        [left, right, ..] => Some((left.clone(), right.clone())),
+        _ => None,
+    }
+}
+
+fn answers(question: &Question) -> Option<&Map<String, Value>> {
+    question.extra.get("model_answers")?.as_object()
+}
+
+/// Whether the reviewer found the model's answer acceptable
+pub fn acceptable(question: &Question, model: &str) -> Option<bool> {
+    question
+        .extra
+        .get("model_acceptable")?
+        .get(model)?
+        .as_bool()
+}
+
+/// Marks the pair's answers acceptable or not, judgment being one of JUDGMENTS
+pub fn judge(question: &mut Question, judgment: &str) -> bool {
+    let Some((left, right)) = pair(question) else {
+        return false;
+    };
+    let (left_ok, right_ok) = match judgment {
+        "left" => (true, false),
+        "right" => (false, true),
+        "both" => (true, true),
+        "neither" => (false, false),
+        _ => return false,
+    };
+    let judged = question
+        .extra
+        .entry("model_acceptable".to_string())
+        .or_insert_with(|| json!({}));
+    if !judged.is_object() {
+        *judged = json!({});
+    }
+    judged[&left] = Value::from(left_ok);
+    judged[&right] = Value::from(right_ok);
+    true
+}
+
+/// Takes back the judgment of the pair's answers
+pub fn unjudge(question: &mut Question) -> bool {
+    let Some((left, right)) = pair(question) else {
+        return false;
+    };
+    let Some(judged) = question
+        .extra
+        .get_mut("model_acceptable")
+        .and_then(Value::as_object_mut)
+    else {
+        return false;
+    };
+    let changed = judged.remove(&left).is_some() | judged.remove(&right).is_some();
+    if judged.is_empty() {
+        question.extra.remove("model_acceptable");
+    }
+    changed
+}
+
+// "Answer: 2" on the first line, whatever the case or markup, then the reasoning
+fn parse(reply: &str, question: &Question) -> (Option<String>, String) {
+    let (first, rest) = reply.split_once('\n').unwrap_or((reply, ""));
+    let number: String = first
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(char::is_ascii_digit)
+        .collect();
+    let answer = number
+        .parse::<usize>()
+        .ok()
+        .and_then(|number| question.options.get(number.checked_sub(1)?))
+        .cloned();
+    match answer {
+        Some(_) => (answer, rest.trim().to_string()),
+        None => (None, reply.trim().to_string()),
+    }
+}
+
+/// Has each model answer the questions, saving after each new or changed
+/// answer, with the template's prompt if given. Returns how many there were.
+pub fn run(banks: &mut Banks, clients: &[Client], template: Option<&Template>) -> Result<usize> {
+    let prompts: Vec<(usize, String)> = (0..banks.len())
+        .map(|index| {
+            let question = banks.question(index);
+            let prompt = match template {
+                Some(template) => template.render(question, 0),
+                None => llm::item(question),
+            };
+            (index, prompt)
+        })
+        .collect();
+    let system = template.and_then(|template| template.system.as_deref());
+    let mut added = 0;
+    for client in clients {
+        let mut done = 0;
+        client.complete_all(system.unwrap_or(INSTRUCTIONS), &prompts, |index, reply| {
+            done += 1;
+            eprintln!(
+                "{}",
+                t!(
+                    "compare.progress",
+                    model = client.model,
+                    number = index + 1,
+                    done = done,
+                    total = prompts.len()
+                )
+            );
+            let question = banks.question(index);
+            let (answer, reasoning) = parse(&reply.text, question);
+            let entry = json!({"answer": answer, "reasoning": reasoning});
+            if answers(question).and_then(|answers| answers.get(&client.model)) == Some(&entry) {
+                return Ok(());
+            }
+            banks.update(index, "compare", |question| {
+                let answers = question
+                    .extra
+                    .entry("model_answers".to_string())
+                    .or_insert_with(|| json!({}));
+                if !answers.is_object() {
+                    *answers = json!({});
+                }
+                answers[&client.model] = entry;
+                if let Some(judged) = question
+                    .extra
+                    .get_mut("model_acceptable")
+                    .and_then(Value::as_object_mut)
+                {
+                    judged.remove(&client.model);
+                }
+                llm::note_usage(question, "compare", client, reply.usage);
+                true
+            })?;
+            banks.save()?;
+            added += 1;
+            Ok(())
+        })?;
+    }
+    Ok(added)
+}
+
+/// One model's answer and reasoning, for its half of the right panel. side is
+/// 0 for the left one.
+pub fn panel(question: &Question, side: usize, palette: &Palette) -> Vec<Line<'static>> {
+    let Some(pair) = pair(question) else {
+        return match side {
+            0 => vec![Line::from(t!("compare.none").italic())],
+            _ => vec![],
+        };
+    };
+    let model = match side {
+        0 => pair.0,
+        _ => pair.1,
+    };
+    let label = t!("compare.model", key = side + 1, model = model.as_str());
+    let mut lines = vec![Line::from(label.bold()), Line::from("")];
+    let Some(entry) = answers(question).and_then(|answers| answers.get(&model)) else {
+        lines.push(Line::from(t!("compare.unanswered").italic()));
+        return lines;
+    };
+    let answer = entry["answer"].as_str();
+    let style = match answer {
+        Some(answer) if answer == question.answer => Style::default().fg(palette.selected),
+        Some(_) if !question.answer.is_empty() => Style::default().fg(palette.missing),
+        _ => Style::default(),
+    };
+    let answer = match answer.and_then(|answer| question.options.iter().position(|o| o == answer)) {
+        Some(i) => format!("{} - {}", i + 1, question.options[i]),
+        None => t!("compare.no_answer").to_string(),
+    };
+    lines.push(Line::styled(answer, style.bold()));
+    lines.push(Line::from(""));
+    let reasoning = entry["reasoning"].as_str().unwrap_or_default();
+    lines.extend(markdown::to_lines(reasoning, Style::default()));
+    if let Some(acceptable) = acceptable(question, &model) {
+        lines.push(Line::from(""));
+        lines.push(Line::from(
+            match acceptable {
+                true => t!("compare.acceptable"),
+                false => t!("compare.unacceptable"),
+            }
+            .fg(palette.info)
+            .bold(),
+        ));
+    }
+    lines
+}
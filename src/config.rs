@@ -0,0 +1,169 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{
+    eyre::{bail, WrapErr},
+    Result,
+};
+use serde::Deserialize;
+
+use crate::{
+    bank::KeyOrder, dimensions::Dimension, hooks, keymap::Keymap, llm, mode::Mode, plugins,
+    theme::Theme,
+};
+
+/// Defaults loaded from config.toml, any CLI flag given takes precedence.
+/// Example:
+///
+/// theme = "light"
+/// default_mode = "classify"
+/// autosave_interval = 60 # seconds, 0 turns it off
+/// rater_id = "R03"
+/// lang = "fr"
+/// key_order = "preserve" # keep the key order of the .json files when saving
+/// audit_log = true # questions.json changes are logged to questions.audit.jsonl
+/// event_log = true # navigation and answers are logged with timestamps, see events.rs
+/// track_time = true # active, idle and per question time is saved in the bank, see timing.rs
+/// auto_advance = true # answering moves on to the next question still missing
+/// confirm_changes = true # changing a response needs its key pressed twice
+/// record_eliminations = true # options struck out are saved in the bank's "eliminated"
+/// answer_history = true # every response and change is kept in the question's "response_history"
+/// reaction_time = true # milliseconds from showing a question to its first response, as "reaction_ms"
+/// pass_mark = 60 # percent of the points to pass, for quizzes and grade
+/// proctor_code = "4417" # needed to quit a --proctored session early
+/// signing_key = "shared secret" # saved files are signed, see signing.rs
+/// age_identity = "/home/me/.config/question_cli/key.txt" # for .age banks, see crypt.rs
+/// age_recipients = ["age1..."]
+/// submit_token = "..." # sent with --submit-url, or $QUESTION_CLI_SUBMIT_TOKEN
+/// google_service_account = "/home/me/key.json" # for import --google-sheet, see google.rs
+/// taxonomy = ["Cardiology/Arrhythmia", "Renal/AKI"] # for topic mode, see taxonomy.rs
+/// dictionary = "/usr/share/hunspell/en_US.dic" # for spellcheck, see spelling.rs
+/// spelling_allowlist = ["/home/me/terms.txt"]
+///
+/// [keymap]
+/// next = "l"
+/// prev = "h"
+///
+/// [[dimensions]] # see dimensions.rs
+/// field = "relevance"
+/// values = ["low", "medium", "high"]
+///
+/// [[plugins]] # item types of your own, see plugins.rs
+/// path = "plugins/dose.wasm"
+///
+/// [[hooks]] # commands run on events of a session, see hooks.rs
+/// on = "save"
+/// script = "git commit -qm responses questions.json"
+///
+/// [llm] # for review, distractors, author and compare --llm, see llm.rs
+/// model = "gpt-4o"
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub theme: Theme,
+    pub keymap: Keymap,
+    pub autosave_interval: u64,
+    pub default_mode: Option<Mode>,
+    pub rater_id: Option<String>,
+    pub lang: Option<String>,
+    pub key_order: KeyOrder,
+    pub audit_log: bool,
+    pub event_log: bool,
+    pub track_time: bool,
+    pub auto_advance: bool,
+    pub confirm_changes: bool,
+    pub record_eliminations: bool,
+    pub answer_history: bool,
+    pub reaction_time: bool,
+    pub pass_mark: Option<f64>,
+    pub proctor_code: Option<String>,
+    pub signing_key: Option<String>,
+    pub age_identity: Option<PathBuf>,
+    pub age_recipients: Vec<String>,
+    pub submit_token: Option<String>,
+    pub google_service_account: Option<PathBuf>,
+    pub dimensions: Vec<Dimension>,
+    pub taxonomy: Vec<String>, // topic mode, see taxonomy.rs
+    pub plugins: Vec<plugins::Source>,
+    pub hooks: Vec<hooks::Hook>,
+    pub dictionary: Option<PathBuf>,
+    pub spelling_allowlist: Vec<PathBuf>,
+    pub llm: llm::Settings,
+    #[serde(skip)]
+    pub profile: Option<String>, // --profile, not set in the file
+}
+
+/// $XDG_CONFIG_HOME/question_cli/config.toml, which is ~/.config/question_cli/config.toml
+/// on most machines (%APPDATA%\question_cli\config.toml on Windows). A
+/// --profile has its own, ~/.config/question_cli/profiles/<name>.toml.
+pub fn default_path(profile: Option<&str>) -> Option<PathBuf> {
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+    let dir = config_home.join("question_cli");
+    Some(match profile {
+        Some(profile) => dir.join("profiles").join(format!("{}.toml", profile)),
+        None => dir.join("config.toml"),
+    })
+}
+
+/// $XDG_DATA_HOME/question_cli, ~/.local/share/question_cli on most machines,
+/// for the session logs. A --profile keeps its own in profiles/<name>.
+pub fn data_dir(profile: Option<&str>) -> Option<PathBuf> {
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+    let dir = data_home.join("question_cli");
+    Some(match profile {
+        Some(profile) => dir.join("profiles").join(profile),
+        None => dir,
+    })
+}
+
+/// Loads the config file at path, or the default one. A missing default
+/// config is fine, a missing explicitly given one is an error. A profile
+/// without a config file starts from the defaults.
+pub fn load(path: Option<&Path>, profile: Option<&str>) -> Result<Config> {
+    if let Some(profile) = profile {
+        if profile.is_empty()
+            || !profile
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        {
+            bail!(
+                "profile '{}' can't be used in a file name, use letters, numbers, - and _",
+                profile
+            );
+        }
+    }
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => match default_path(profile) {
+            Some(path) if path.exists() => path,
+            _ => {
+                return Ok(Config {
+                    profile: profile.map(str::to_string),
+                    ..Config::default()
+                })
+            }
+        },
+    };
+    let data = fs::read_to_string(&path)
+        .with_context(|| format!("could not read config file: {}", path.display()))?;
+    let config: Config = toml::from_str(&data)
+        .with_context(|| format!("invalid config file: {}", path.display()))?;
+    config
+        .keymap
+        .check()
+        .with_context(|| format!("invalid [keymap] in {}", path.display()))?;
+    Ok(Config {
+        profile: profile.map(str::to_string),
+        ..config
+    })
+}
@@ -0,0 +1,127 @@
+use std::{fs, path::Path};
+
+use color_eyre::{
+    eyre::{bail, WrapErr},
+    Result,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bank::{self, Banks},
+    shared,
+};
+
+// To counter order effects (fatigue, learning as you go), `question_cli
+// counterbalance questions.json --roster raters.txt` makes a packet per rater,
+// questions.R01.json for R01, each going through the questions in the order of
+// a row of a balanced Latin square (a Williams design): every question comes
+// at every position, and after every other question, equally often across
+// raters. The questions stay in the order of the file so the packets can be
+// compared and exported as usual, the order is in the packet's metadata and
+// sessions follow it:
+//
+// "counterbalance": { "rater": "R01", "row": 1, "order": [1, 2, 12, 3, 11, ...] }
+//
+// Raters then open their packet with --per-rater, or directly.
+
+/// The order a rater's packet is gone through in, saved in the bank
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Counterbalance {
+    pub rater: String,
+    pub row: usize,        // of the Latin square, from 1
+    pub order: Vec<usize>, // question numbers in the file, from 1
+}
+
+/// Row of a balanced Latin square of size n, from 0: 0, 1, n-1, 2, n-2...
+/// shifted by the row. With an odd n the square is only balanced for
+/// carry-over with its mirror rows, which the rows from n to 2n are.
+pub fn row(n: usize, row: usize) -> Vec<usize> {
+    if n == 0 {
+        return vec![];
+    }
+    let shift = row % n;
+    let order = (0..n).map(|j| {
+        let step = match j % 2 {
+            0 => (n - j / 2) % n,
+            _ => j.div_ceil(2),
+        };
+        (step + shift) % n
+    });
+    match n % 2 == 1 && (row / n) % 2 == 1 {
+        true => order.rev().collect(),
+        false => order.collect(),
+    }
+}
+
+/// The rater IDs of a roster file, one per line, # for comments
+pub fn read_roster(path: &Path) -> Result<Vec<String>> {
+    let data =
+        fs::read_to_string(path).wrap_err_with(|| format!("could not read {}", path.display()))?;
+    Ok(data
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Writes a packet per rater, without responses, returns their paths
+pub fn write_packets(json_path: &Path, raters: &[String]) -> Result<Vec<String>> {
+    if raters.is_empty() {
+        bail!("no raters to counterbalance for, give a --roster or --raters");
+    }
+    let mut written = vec![];
+    for (i, rater) in raters.iter().enumerate() {
+        let packet_path = shared::copy_path(json_path, rater, "rater_id")?;
+        if packet_path.exists() {
+            bail!(
+                "{} already exists, not overwriting it",
+                packet_path.display()
+            );
+        }
+        let mut bank = bank::load_json(json_path)?;
+        for question in &mut bank.questions {
+            question.clear_responses();
+        }
+        bank.time_spent = None;
+        bank.exam = None;
+        bank.rater_id = Some(rater.clone());
+        bank.counterbalance = Some(Counterbalance {
+            rater: rater.clone(),
+            row: i + 1,
+            order: row(bank.questions.len(), i).iter().map(|q| q + 1).collect(),
+        });
+        bank::save_json(&packet_path, &bank)
+            .wrap_err_with(|| format!("could not create {}", packet_path.display()))?;
+        written.push(packet_path.display().to_string());
+    }
+    Ok(written)
+}
+
+/// Goes through the banks in their packets' order, those without one in the
+/// order of the file
+pub fn apply(banks: &mut Banks) {
+    let mut view = vec![];
+    let mut offset = 0;
+    let mut ordered = false;
+    for (_, bank) in banks.files() {
+        let count = bank.questions.len();
+        let order = bank.counterbalance.as_ref().map(|c| &c.order);
+        // an order that isn't every question once is ignored
+        match order.filter(|order| {
+            let mut sorted = order.to_vec();
+            sorted.sort();
+            sorted == (1..=count).collect::<Vec<_>>()
+        }) {
+            Some(order) => {
+                view.extend(order.iter().map(|number| offset + number - 1));
+                ordered = true;
+            }
+            None => view.extend(offset..offset + count),
+        }
+        offset += count;
+    }
+    if ordered {
+        banks.set_view(view);
+    }
+}
@@ -0,0 +1,127 @@
+use std::{
+    iter,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use age::{
+    secrecy::{ExposeSecret, SecretString},
+    IdentityFile,
+};
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+
+// Banks named *.age (eg. exam.json.age) are encrypted with age
+// (https://age-encryption.org), for confidential questions that get emailed
+// around. They're decrypted on load and encrypted again on save, the questions
+// are never written to disk in the clear.
+//
+// By default they use a passphrase, asked for once per run (or taken from
+// $QUESTION_CLI_PASSPHRASE). With age_identity in the config file they use age
+// keys instead: files are decrypted with the identity file and encrypted to its
+// own key plus any age_recipients, eg. the other raters' public keys.
+
+/// How .age files are opened and saved, set from the config file
+#[derive(Debug)]
+enum Keys {
+    Passphrase,
+    Identity {
+        path: PathBuf,
+        recipients: Vec<String>,
+    },
+}
+
+static KEYS: OnceLock<Keys> = OnceLock::new();
+static PASSPHRASE: OnceLock<SecretString> = OnceLock::new();
+
+pub fn init(identity: Option<&Path>, recipients: &[String]) {
+    let keys = match identity {
+        Some(path) => Keys::Identity {
+            path: path.to_path_buf(),
+            recipients: recipients.to_vec(),
+        },
+        None => Keys::Passphrase,
+    };
+    let _ = KEYS.set(keys);
+}
+
+pub fn is_encrypted(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "age")
+}
+
+pub fn decrypt(path: &Path, data: &[u8]) -> Result<String> {
+    let decryptor = age::Decryptor::new(data).wrap_err("not an age encrypted file")?;
+    let mut reader = match keys() {
+        Keys::Passphrase => {
+            let identity = age::scrypt::Identity::new(passphrase(path)?);
+            decryptor.decrypt(iter::once(&identity as &dyn age::Identity))
+        }
+        Keys::Identity { path, .. } => {
+            let identities = identity_file(path)?
+                .into_identities()
+                .wrap_err("could not read the age_identity")?;
+            decryptor.decrypt(identities.iter().map(|identity| identity.as_ref()))
+        }
+    }
+    .map_err(|err| eyre!("could not decrypt: {}", err))?;
+    let plaintext = std::io::read_to_string(&mut reader).wrap_err("could not decrypt")?;
+    Ok(plaintext)
+}
+
+pub fn encrypt(path: &Path, plaintext: &str) -> Result<Vec<u8>> {
+    let ciphertext = match keys() {
+        Keys::Passphrase => {
+            let recipient = age::scrypt::Recipient::new(passphrase(path)?);
+            age::encrypt(&recipient, plaintext.as_bytes())
+        }
+        Keys::Identity {
+            path: identity,
+            recipients,
+        } => {
+            let mut all = identity_file(identity)?
+                .to_recipients()
+                .wrap_err("could not read the age_identity")?;
+            for recipient in recipients {
+                let recipient: age::x25519::Recipient = recipient
+                    .parse()
+                    .map_err(|err| eyre!("invalid age recipient {}: {}", recipient, err))?;
+                all.push(Box::new(recipient));
+            }
+            let encryptor = age::Encryptor::with_recipients(
+                all.iter().map(|recipient| recipient.as_ref() as _),
+            )
+            .wrap_err("no age recipients")?;
+            let mut ciphertext = vec![];
+            let mut writer = encryptor.wrap_output(&mut ciphertext)?;
+            std::io::Write::write_all(&mut writer, plaintext.as_bytes())?;
+            writer.finish()?;
+            Ok(ciphertext)
+        }
+    };
+    ciphertext.map_err(|err| eyre!("could not encrypt: {}", err))
+}
+
+fn keys() -> &'static Keys {
+    KEYS.get_or_init(|| Keys::Passphrase)
+}
+
+fn identity_file(path: &Path) -> Result<IdentityFile<age::NoCallbacks>> {
+    IdentityFile::from_file(path.to_string_lossy().to_string())
+        .wrap_err_with(|| format!("could not read the age_identity {}", path.display()))
+}
+
+// asked once, the same passphrase is used for every file of the run
+fn passphrase(path: &Path) -> Result<SecretString> {
+    if let Some(passphrase) = PASSPHRASE.get() {
+        return Ok(passphrase.expose_secret().into());
+    }
+    let passphrase = match std::env::var("QUESTION_CLI_PASSPHRASE") {
+        Ok(passphrase) => passphrase,
+        Err(_) => rpassword::prompt_password(format!("Passphrase for {}: ", path.display()))
+            .wrap_err("could not read the passphrase")?,
+    };
+    let _ = PASSPHRASE.set(passphrase.as_str().into());
+    Ok(passphrase.into())
+}
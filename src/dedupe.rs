@@ -0,0 +1,169 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::{bank::Banks, embeddings, embeddings::Embeddings, i18n::t, lint, spelling};
+
+// `question_cli dedupe questions.json` lists the pairs of questions whose stems
+// are likely the same question, across all the banks given, most similar
+// first. By default stems are compared word by word (case and punctuation
+// aside): their similarity is 1 minus the words to add, remove or change over
+// the longer stem's words, which catches copies with small edits. With
+// --embeddings it's the cosine similarity of their embeddings (question_cli
+// embed first), which also catches rewordings of the same question: "Which
+// anticoagulant is safe in pregnancy?" and "A pregnant woman needs
+// anticoagulation, which drug?" share few words but mean the same.
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Method {
+    Words,
+    Embeddings,
+}
+
+impl Method {
+    /// How similar two stems must be by default
+    pub fn threshold(&self) -> f32 {
+        match self {
+            Method::Words => 0.8,
+            Method::Embeddings => 0.9,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Pair {
+    pub questions: [usize; 2], // from 1
+    pub similarity: f32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Duplicates {
+    pub method: Method,
+    pub model: Option<String>, // the embedding model
+    pub threshold: f32,
+    pub pairs: Vec<Pair>,
+    pub stale: usize, // changed since embedded, left out
+}
+
+/// The pairs of stems at least threshold similar by their words
+pub fn by_words(banks: &Banks, threshold: f32) -> Duplicates {
+    let stems: Vec<Vec<String>> = (0..banks.len())
+        .map(|i| {
+            lint::normalize(&banks.question(i).question)
+                .split(' ')
+                .filter(|word| !word.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .collect();
+    let similarity = |a: &[String], b: &[String]| -> Option<f32> {
+        let longest = a.len().max(b.len());
+        // the length difference alone is that many edits
+        if longest == 0 || (a.len().abs_diff(b.len()) as f32) > (1.0 - threshold) * longest as f32 {
+            return None;
+        }
+        Some(1.0 - spelling::distance(a, b) as f32 / longest as f32)
+    };
+    let pairs = pairs(stems.len(), threshold, |a, b| {
+        similarity(&stems[a], &stems[b])
+    });
+    Duplicates {
+        method: Method::Words,
+        model: None,
+        threshold,
+        pairs,
+        stale: 0,
+    }
+}
+
+/// The pairs of stems at least threshold similar by their embeddings
+pub fn by_embeddings(embeddings: &Embeddings, threshold: f32) -> Duplicates {
+    let vectors = &embeddings.vectors;
+    let pairs = pairs(vectors.len(), threshold, |a, b| {
+        Some(embeddings::cosine(
+            vectors[a].as_ref()?,
+            vectors[b].as_ref()?,
+        ))
+    });
+    Duplicates {
+        method: Method::Embeddings,
+        model: Some(embeddings.model.clone()),
+        threshold,
+        pairs,
+        stale: embeddings.stale(),
+    }
+}
+
+// every pair of questions similar enough, most similar first
+fn pairs(
+    count: usize,
+    threshold: f32,
+    similarity: impl Fn(usize, usize) -> Option<f32>,
+) -> Vec<Pair> {
+    let mut pairs = vec![];
+    for a in 0..count {
+        for b in a + 1..count {
+            match similarity(a, b) {
+                Some(similarity) if similarity >= threshold => pairs.push(Pair {
+                    questions: [a + 1, b + 1],
+                    similarity,
+                }),
+                _ => {}
+            }
+        }
+    }
+    pairs.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    pairs
+}
+
+impl Duplicates {
+    pub fn print(&self, banks: &Banks, out: &mut impl Write) -> io::Result<()> {
+        for pair in &self.pairs {
+            let numbers = pair
+                .questions
+                .map(|number| match banks.question(number - 1).id() {
+                    Some(id) => format!("{} ({})", number, id),
+                    None => number.to_string(),
+                });
+            writeln!(
+                out,
+                "{}",
+                t!(
+                    "dedupe.pair",
+                    first = numbers[0].as_str(),
+                    second = numbers[1].as_str(),
+                    similarity = format!("{:.3}", pair.similarity)
+                )
+            )?;
+            for number in pair.questions {
+                let stem = banks.question(number - 1).question.trim();
+                let stem = stem.lines().next().unwrap_or_default();
+                let stem: String = match stem.chars().count() > 76 {
+                    true => stem.chars().take(75).chain(['…']).collect(),
+                    false => stem.to_string(),
+                };
+                writeln!(out, "  {}", stem)?;
+            }
+            writeln!(out)?;
+        }
+        let summary = match &self.model {
+            Some(model) => t!(
+                "dedupe.summary_embeddings",
+                count = self.pairs.len(),
+                threshold = self.threshold,
+                model = model.as_str()
+            ),
+            None => t!(
+                "dedupe.summary",
+                count = self.pairs.len(),
+                threshold = self.threshold
+            ),
+        };
+        writeln!(out, "{}", summary)?;
+        if self.stale > 0 {
+            writeln!(out, "{}", t!("embeddings.stale", count = self.stale))?;
+        }
+        Ok(())
+    }
+}
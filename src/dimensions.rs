@@ -0,0 +1,92 @@
+use std::sync::OnceLock;
+
+use color_eyre::{eyre::bail, Result};
+use ratatui::prelude::*;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{bank::Question, i18n::t, theme::Palette};
+
+// Classifying on several axes in one pass (`question_cli dimensions`), each
+// axis saved in a field of its own. They're set in the config file:
+//
+// [[dimensions]]
+// field = "cognitive_level"
+// values = ["recall", "application", "analysis"]
+//
+// [[dimensions]]
+// field = "relevance"
+// values = ["low", "medium", "high"]
+//
+// Tab goes to the next axis, the number keys pick a value on it.
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Dimension {
+    pub field: String,
+    pub values: Vec<String>,
+}
+
+static DIMENSIONS: OnceLock<Vec<Dimension>> = OnceLock::new();
+
+/// Checks and sets the axes from the config
+pub fn init(dimensions: &[Dimension]) -> Result<()> {
+    for dimension in dimensions {
+        if dimension.values.is_empty() || dimension.values.len() > 9 {
+            bail!(
+                "dimension {} needs from 1 to 9 values, one per number key",
+                dimension.field
+            );
+        }
+    }
+    let _ = DIMENSIONS.set(dimensions.to_vec());
+    Ok(())
+}
+
+pub fn all() -> &'static [Dimension] {
+    DIMENSIONS.get().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// The value picked on an axis, if any
+pub fn value<'a>(question: &'a Question, dimension: &Dimension) -> Option<&'a str> {
+    question.extra.get(&dimension.field).and_then(Value::as_str)
+}
+
+/// The first axis after current without a value, from the start again past the end
+pub fn next_missing(question: &Question, current: usize) -> Option<usize> {
+    let count = all().len();
+    (current + 1..count)
+        .chain(0..=current.min(count.saturating_sub(1)))
+        .find(|&index| value(question, &all()[index]).is_none())
+}
+
+/// The axes for the right panel, the values of the current one numbered
+pub fn panel(question: &Question, current: usize, palette: &Palette) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(t!("dimensions.title").bold()), Line::from("")];
+    for (index, dimension) in all().iter().enumerate() {
+        let picked = value(question, dimension);
+        let name = format!("{}: ", dimension.field);
+        if index != current {
+            lines.push(Line::from(vec![
+                name.dim(),
+                match picked {
+                    Some(picked) => picked.to_string().fg(palette.selected),
+                    None => t!("dimensions.missing").fg(palette.missing),
+                },
+            ]));
+            continue;
+        }
+        lines.push(Line::from(format!("▶ {}", name).bold()));
+        for (i, option) in dimension.values.iter().enumerate() {
+            let style = match Some(option.as_str()) == picked {
+                true => Style::default().fg(palette.selected).bold().underlined(),
+                false => Style::default().fg(palette.option),
+            };
+            lines.push(Line::from(vec![
+                format!("  {} - ", i + 1).fg(palette.action_key),
+                Span::styled(option.clone(), style),
+            ]));
+        }
+    }
+    lines
+}
@@ -0,0 +1,46 @@
+use color_eyre::Result;
+use rand::{rngs::StdRng, seq::IteratorRandom, SeedableRng};
+use sha2::{Digest, Sha256};
+
+use crate::bank::Banks;
+
+// For distractor pool experiments, `--distractors 3` shows each rater the key
+// and 3 of the question's distractors, picked at random. The pick is seeded by
+// the rater and the question, so a rater gets the same subset every time and
+// raters get different ones. The options shown are saved in the question, by
+// their number in the full list, and kept from then on:
+//
+// "shown_options": [1, 3, 4, 6]
+//
+// The number keys pick among the options shown, answers are saved as the
+// option's text like any other.
+
+/// Picks the options to show for the questions with more than count
+/// distractors that don't have theirs yet. True if any were picked.
+pub fn subset(banks: &mut Banks, count: usize, rater: &str) -> Result<bool> {
+    let mut picked = false;
+    for index in 0..banks.len() {
+        let question = banks.question(index);
+        let distractors: Vec<usize> = (0..question.options.len())
+            .filter(|&i| question.options[i] != question.answer)
+            .collect();
+        if question.extra.contains_key("shown_options") || distractors.len() <= count {
+            continue;
+        }
+        let (_, id) = banks.question_id(index);
+        let mut rng = StdRng::seed_from_u64(seed(rater, &id));
+        let mut shown = distractors.into_iter().choose_multiple(&mut rng, count);
+        shown.extend(question.options.iter().position(|o| *o == question.answer));
+        shown.sort();
+        picked |= banks.update(index, "subset", |question| {
+            question.set_shown_options(&shown);
+            true
+        })?;
+    }
+    Ok(picked)
+}
+
+fn seed(rater: &str, id: &str) -> u64 {
+    let hash = Sha256::digest(format!("{}/{}", rater, id));
+    u64::from_le_bytes(hash[..8].try_into().unwrap_or_default())
+}
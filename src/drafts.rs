@@ -0,0 +1,114 @@
+use color_eyre::Result;
+use ratatui::{
+    style::{Style, Stylize},
+    text::Line,
+};
+use serde_json::Value;
+
+use crate::{
+    bank::{Banks, Question},
+    i18n::t,
+    llm::{self, Client},
+    markdown, mode,
+    prompts::Template,
+};
+
+// `question_cli author --llm questions.json` has the model of the config's
+// [llm] table (see llm.rs) draft an explanation for each question without one,
+// saved as "draft_explanation" and never shown to raters. In
+// `question_cli author questions.json` the author edits each draft and accepts
+// it, which makes it the question's "explanation", or discards it.
+
+const INSTRUCTIONS: &str = "You write explanations for multiple choice questions for \
+medical exams, shown to students after they answer. In one or two short paragraphs, \
+explain why the answer is correct, then briefly why each other option is wrong. Don't \
+restate the question. Use Markdown sparingly.";
+
+pub fn draft(question: &Question) -> Option<&str> {
+    question
+        .extra
+        .get("draft_explanation")
+        .and_then(Value::as_str)
+}
+
+pub fn set_draft(question: &mut Question, text: &str) -> bool {
+    if draft(question) == Some(text) {
+        return false;
+    }
+    question
+        .extra
+        .insert("draft_explanation".to_string(), Value::from(text));
+    true
+}
+
+/// Makes the draft the question's explanation, or drops it
+pub fn decide(question: &mut Question, accept: bool) -> bool {
+    let Some(draft) = question.extra.remove("draft_explanation") else {
+        return false;
+    };
+    if accept {
+        question.extra.insert("explanation".to_string(), draft);
+    }
+    true
+}
+
+/// Has the model draft explanations for the questions with an answer key but
+/// no explanation or draft, saving after each one, with the template's prompt
+/// if given. Returns how many it drafted.
+pub fn run(banks: &mut Banks, client: &Client, template: Option<&Template>) -> Result<usize> {
+    let prompts: Vec<(usize, String)> = (0..banks.len())
+        .filter(|&index| {
+            let question = banks.question(index);
+            !question.answer.is_empty()
+                && question.explanation().is_none()
+                && draft(question).is_none()
+        })
+        .map(|index| {
+            let question = banks.question(index);
+            let prompt = match template {
+                Some(template) => template.render(question, 0),
+                None => format!("{}\nAnswer: {}\n", llm::item(question), question.answer),
+            };
+            (index, prompt)
+        })
+        .collect();
+    let system = template.and_then(|template| template.system.as_deref());
+    let mut done = 0;
+    client.complete_all(system.unwrap_or(INSTRUCTIONS), &prompts, |index, reply| {
+        done += 1;
+        eprintln!(
+            "{}",
+            t!(
+                "drafts.progress",
+                number = index + 1,
+                done = done,
+                total = prompts.len()
+            )
+        );
+        banks.update(index, "draft explanation", |question| {
+            llm::note_usage(question, "author", client, reply.usage);
+            set_draft(question, &reply.text)
+        })?;
+        banks.save()
+    })?;
+    Ok(done)
+}
+
+/// The draft for the right panel, with a cursor while it's being edited, or
+/// the explanation once it's been accepted
+pub fn panel(question: &Question, editing: Option<&str>) -> Vec<Line<'static>> {
+    if let (None, None, Some(_)) = (editing, draft(question), question.explanation()) {
+        return mode::explanation(question);
+    }
+    let mut lines = vec![Line::from(t!("drafts.title").bold()), Line::from("")];
+    match (editing, draft(question)) {
+        (Some(text), _) => lines.extend(
+            format!("{}▏", text)
+                .split('\n')
+                .map(|line| Line::from(line.to_string())),
+        ),
+        (None, Some(text)) => lines.extend(markdown::to_lines(text, Style::default())),
+        (None, None) => lines.push(Line::from(t!("drafts.none").italic())),
+    }
+    lines
+}
@@ -0,0 +1,308 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{
+    eyre::{bail, eyre, WrapErr},
+    Result,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    bank::{Banks, Question},
+    i18n::t,
+    llm::Client,
+};
+
+// `question_cli embed questions.json` indexes the stems with an embedding
+// model (embedding_model in the config's [llm] table, see llm.rs) in
+// questions.embeddings.json next to the bank:
+//
+// {"model": "text-embedding-3-small", "vectors": {"<sha256 of the stem>": [0.0123, ...]}}
+//
+// Stems are looked up by their checksum, so running it again after editing the
+// bank only embeds the new or changed ones. `question_cli search "anticoagulation
+// in pregnancy" questions.json` then lists the questions closest in meaning to
+// the text (by cosine similarity), which finds the paraphrases keyword search
+// misses. `question_cli export --to embeddings questions.json -o vectors.parquet`
+// writes them by question id for analyses elsewhere, as JSON or, in builds
+// with the parquet feature, Parquet.
+
+const BATCH: usize = 64; // stems sent at once
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Index {
+    pub model: String,
+    pub vectors: BTreeMap<String, Vec<f32>>,
+}
+
+/// questions.json => questions.embeddings.json
+pub fn index_path(json_path: &Path) -> PathBuf {
+    json_path.with_extension("embeddings.json")
+}
+
+fn load(json_path: &Path) -> Result<Option<Index>> {
+    let index_path = index_path(json_path);
+    if !index_path.exists() {
+        return Ok(None);
+    }
+    let data = fs::read_to_string(&index_path)
+        .wrap_err_with(|| format!("could not read {}", index_path.display()))?;
+    let index = serde_json::from_str(&data)
+        .wrap_err_with(|| format!("invalid embeddings in {}", index_path.display()))?;
+    Ok(Some(index))
+}
+
+fn write(json_path: &Path, index: &Index) -> Result<()> {
+    let index_path = index_path(json_path);
+    fs::write(&index_path, serde_json::to_string(index)? + "\n")
+        .wrap_err_with(|| format!("could not write {}", index_path.display()))
+}
+
+fn checksum(question: &Question) -> String {
+    hex::encode(Sha256::digest(question.question.trim()))
+}
+
+/// Embeds the stems of each bank that aren't in its index yet (all of them
+/// with another model), saving the index as it goes. Returns how many.
+pub fn embed(banks: &Banks, client: &Client) -> Result<usize> {
+    let mut embedded = 0;
+    for (json_path, bank) in banks.files() {
+        let mut index = load(json_path)?
+            .filter(|index| index.model == client.model)
+            .unwrap_or_else(|| Index {
+                model: client.model.clone(),
+                ..Default::default()
+            });
+        let mut todo: Vec<(String, String)> = vec![];
+        for question in &bank.questions {
+            let sum = checksum(question);
+            if question.question.trim().is_empty()
+                || index.vectors.contains_key(&sum)
+                || todo.iter().any(|(other, _)| *other == sum)
+            {
+                continue;
+            }
+            todo.push((sum, question.question.trim().to_string()));
+        }
+        for batch in todo.chunks(BATCH) {
+            eprintln!(
+                "{}",
+                t!(
+                    "embeddings.progress",
+                    file = json_path.display(),
+                    done = embedded + batch.len(),
+                    total = embedded + todo.len()
+                )
+            );
+            let stems: Vec<String> = batch.iter().map(|(_, stem)| stem.clone()).collect();
+            let vectors = client.embed(&stems)?;
+            for ((sum, _), vector) in batch.iter().zip(vectors) {
+                index.vectors.insert(sum.clone(), vector);
+            }
+            write(json_path, &index)?;
+        }
+        embedded += todo.len();
+        // the stems since edited or deleted
+        let current: HashSet<String> = bank.questions.iter().map(checksum).collect();
+        index.vectors.retain(|sum, _| current.contains(sum));
+        write(json_path, &index)?;
+    }
+    Ok(embedded)
+}
+
+/// The embeddings of the questions, by question in the order of the session
+pub struct Embeddings {
+    pub model: String,
+    pub vectors: Vec<Option<Vec<f32>>>, // None for stems changed since embed
+}
+
+impl Embeddings {
+    /// Those of every bank, which must have been embedded with the same model
+    pub fn load(banks: &Banks) -> Result<Embeddings> {
+        let mut indexes: HashMap<&Path, Index> = HashMap::new();
+        let mut model: Option<String> = None;
+        for json_path in banks.paths() {
+            let index = load(json_path)?.ok_or_else(|| {
+                eyre!(
+                    "{} has no embeddings, run question_cli embed first",
+                    json_path.display()
+                )
+            })?;
+            match &model {
+                Some(model) if *model != index.model => bail!(
+                    "{} was embedded with {} and not {}, embed the banks with the same model",
+                    json_path.display(),
+                    index.model,
+                    model
+                ),
+                _ => model = Some(index.model.clone()),
+            }
+            indexes.insert(json_path, index);
+        }
+        let vectors = (0..banks.len())
+            .map(|i| {
+                let (json_path, _) = banks.source(i);
+                indexes[json_path]
+                    .vectors
+                    .get(&checksum(banks.question(i)))
+                    .cloned()
+            })
+            .collect();
+        Ok(Embeddings {
+            model: model.unwrap_or_default(),
+            vectors,
+        })
+    }
+
+    /// How many questions changed since they were embedded
+    pub fn stale(&self) -> usize {
+        self.vectors
+            .iter()
+            .filter(|vector| vector.is_none())
+            .count()
+    }
+}
+
+/// Cosine similarity, 1 for the same meaning
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 {
+        return 0.0;
+    }
+    dot / norms
+}
+
+/// The questions most similar to the query, with their similarity, best first
+pub fn search(
+    embeddings: &Embeddings,
+    client: &Client,
+    query: &str,
+    count: usize,
+) -> Result<Vec<(usize, f32)>> {
+    if embeddings.model != client.model {
+        bail!(
+            "the banks were embedded with {}, search with the same model",
+            embeddings.model
+        );
+    }
+    if embeddings.stale() > 0 {
+        eprintln!("{}", t!("embeddings.stale", count = embeddings.stale()));
+    }
+    let query = client.embed(&[query.to_string()])?.remove(0);
+    let mut hits: Vec<(usize, f32)> = embeddings
+        .vectors
+        .iter()
+        .enumerate()
+        .filter_map(|(i, vector)| Some((i, cosine(&query, vector.as_ref()?))))
+        .collect();
+    hits.sort_by(|a, b| b.1.total_cmp(&a.1));
+    hits.truncate(count);
+    Ok(hits)
+}
+
+/// A question's embedding for export, by its id (its number in the bank
+/// without one)
+#[derive(Serialize)]
+pub struct Row<'a> {
+    pub id: String,
+    pub bank: String, // the file name
+    pub embedding: &'a [f32],
+}
+
+/// The embedded questions, in order, leaving out the ones changed since
+pub fn rows<'a>(banks: &Banks, embeddings: &'a Embeddings) -> Vec<Row<'a>> {
+    (0..banks.len())
+        .filter_map(|i| {
+            let embedding = embeddings.vectors[i].as_deref()?;
+            let (json_path, id) = banks.question_id(i);
+            let bank = json_path.file_name().unwrap_or_default();
+            Some(Row {
+                id,
+                bank: bank.to_string_lossy().to_string(),
+                embedding,
+            })
+        })
+        .collect()
+}
+
+/// {"model": "text-embedding-3-small", "questions": [{"id": "Q1", "bank":
+/// "questions.json", "embedding": [0.0123, ...]}, ...]}
+pub fn export(banks: &Banks, embeddings: &Embeddings, mut out: impl Write) -> Result<()> {
+    let export = json!({"model": embeddings.model, "questions": rows(banks, embeddings)});
+    writeln!(out, "{}", serde_json::to_string(&export)?)?;
+    Ok(())
+}
+
+/// A Parquet file with id, bank and embedding (a list of floats) columns, and
+/// the model in its metadata
+#[cfg(feature = "parquet")]
+pub fn export_parquet(banks: &Banks, embeddings: &Embeddings, path: &Path) -> Result<()> {
+    use std::sync::Arc;
+
+    use arrow_array::{
+        builder::{Float32Builder, ListBuilder},
+        ArrayRef, RecordBatch, StringArray,
+    };
+    use parquet::{
+        arrow::ArrowWriter,
+        file::{metadata::KeyValue, properties::WriterProperties},
+    };
+
+    let rows = rows(banks, embeddings);
+    let ids = StringArray::from_iter_values(rows.iter().map(|row| row.id.as_str()));
+    let bank_names = StringArray::from_iter_values(rows.iter().map(|row| row.bank.as_str()));
+    let mut vectors = ListBuilder::new(Float32Builder::new());
+    for row in &rows {
+        vectors.values().append_slice(row.embedding);
+        vectors.append(true);
+    }
+    let batch = RecordBatch::try_from_iter([
+        ("id", Arc::new(ids) as ArrayRef),
+        ("bank", Arc::new(bank_names) as ArrayRef),
+        ("embedding", Arc::new(vectors.finish()) as ArrayRef),
+    ])?;
+    let properties = WriterProperties::builder()
+        .set_key_value_metadata(Some(vec![KeyValue::new(
+            "model".to_string(),
+            embeddings.model.clone(),
+        )]))
+        .build();
+    let file =
+        fs::File::create(path).wrap_err_with(|| format!("could not create {}", path.display()))?;
+    let mut writer = ArrowWriter::try_new(file, batch.schema(), Some(properties))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(not(feature = "parquet"))]
+pub fn export_parquet(_banks: &Banks, _embeddings: &Embeddings, _path: &Path) -> Result<()> {
+    bail!("this build can't write Parquet, build question_cli with --features parquet or export to JSON")
+}
+
+/// The search results, a line each: the similarity, the question's number (and
+/// id) and the start of its stem
+pub fn print(banks: &Banks, hits: &[(usize, f32)], out: &mut impl Write) -> io::Result<()> {
+    for &(i, similarity) in hits {
+        let question = banks.question(i);
+        let number = match question.id() {
+            Some(id) => format!("{} ({})", i + 1, id),
+            None => (i + 1).to_string(),
+        };
+        let stem = question.question.trim().lines().next().unwrap_or_default();
+        let stem: String = match stem.chars().count() > 70 {
+            true => stem.chars().take(69).chain(['…']).collect(),
+            false => stem.to_string(),
+        };
+        writeln!(out, "{:.3}  {:<12} {}", similarity, number, stem)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use std::{
+    collections::BTreeSet,
+    io::{self, Write},
+};
+
+use color_eyre::{eyre::bail, Result};
+use serde::Serialize;
+
+use crate::{export::Rater, i18n::t};
+
+// `question_cli equate --form-x X*.json --form-y Y*.json` puts scores on form X
+// on the scale of form Y, when each was sat by a different group and they share
+// anchor items: the questions with the same "id" in both. Tucker linear
+// equating, with the anchor internal (counted in both forms' scores). Scores
+// are the number of questions answered right, questions without an answer key
+// don't count.
+
+/// How the forms were linked, and form X's scores on form Y's scale
+#[derive(Debug, Serialize)]
+pub struct Equating {
+    pub anchors: Vec<String>, // ids
+    pub raters_x: usize,
+    pub raters_y: usize,
+    pub slope: f64,
+    pub intercept: f64,
+    pub table: Vec<(usize, f64)>, // (score on X, equivalent on Y)
+}
+
+// means, variances and covariance of (total, anchor) scores in a group
+struct Moments {
+    total: f64,
+    anchor: f64,
+    var_total: f64,
+    var_anchor: f64,
+    covariance: f64,
+}
+
+fn moments(scores: &[(f64, f64)]) -> Moments {
+    let n = scores.len() as f64;
+    let total = scores.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let anchor = scores.iter().map(|(_, v)| v).sum::<f64>() / n;
+    let var = |f: &dyn Fn(&(f64, f64)) -> f64| scores.iter().map(f).sum::<f64>() / n;
+    Moments {
+        total,
+        anchor,
+        var_total: var(&|(x, _)| (x - total).powi(2)),
+        var_anchor: var(&|(_, v)| (v - anchor).powi(2)),
+        covariance: var(&|(x, v)| (x - total) * (v - anchor)),
+    }
+}
+
+// the ids of a form's scored questions
+fn ids(raters: &[Rater]) -> BTreeSet<String> {
+    let questions = raters[0].bank.questions.iter();
+    questions
+        .filter(|q| !q.answer.is_empty())
+        .filter_map(|q| q.id())
+        .collect()
+}
+
+// (total, anchor) number right for every rater
+fn scores(raters: &[Rater], anchors: &BTreeSet<String>) -> Vec<(f64, f64)> {
+    raters
+        .iter()
+        .map(|rater| {
+            let right = rater
+                .bank
+                .questions
+                .iter()
+                .filter(|q| q.is_correct() == Some(true));
+            let anchor = right
+                .clone()
+                .filter(|q| q.id().is_some_and(|id| anchors.contains(&id)))
+                .count();
+            (right.count() as f64, anchor as f64)
+        })
+        .collect()
+}
+
+pub fn equate(form_x: &[Rater], form_y: &[Rater]) -> Result<Equating> {
+    if form_x.is_empty() || form_y.is_empty() {
+        bail!("equating needs answered copies of both forms");
+    }
+    let anchors: BTreeSet<String> = ids(form_x).intersection(&ids(form_y)).cloned().collect();
+    if anchors.is_empty() {
+        bail!("the forms have no questions with the same \"id\" to link them");
+    }
+    let p = moments(&scores(form_x, &anchors));
+    let q = moments(&scores(form_y, &anchors));
+    if p.var_anchor == 0.0 || q.var_anchor == 0.0 {
+        bail!("everyone in a group has the same anchor score, the forms can't be linked");
+    }
+
+    // the synthetic population, both groups weighted by their size
+    let w1 = form_x.len() as f64 / (form_x.len() + form_y.len()) as f64;
+    let w2 = 1.0 - w1;
+    let gamma1 = p.covariance / p.var_anchor;
+    let gamma2 = q.covariance / q.var_anchor;
+    let anchor_gap = p.anchor - q.anchor;
+    let mean_x = p.total - w2 * gamma1 * anchor_gap;
+    let mean_y = q.total + w1 * gamma2 * anchor_gap;
+    let var_x = p.var_total - w2 * gamma1.powi(2) * (p.var_anchor - q.var_anchor)
+        + w1 * w2 * gamma1.powi(2) * anchor_gap.powi(2);
+    let var_y = q.var_total
+        + w1 * gamma2.powi(2) * (p.var_anchor - q.var_anchor)
+        + w1 * w2 * gamma2.powi(2) * anchor_gap.powi(2);
+    if var_x <= 0.0 || var_y <= 0.0 {
+        bail!("the scores don't vary enough to equate the forms");
+    }
+    let slope = (var_y / var_x).sqrt();
+    let intercept = mean_y - slope * mean_x;
+
+    let questions = form_x[0].bank.questions.iter();
+    let maximum = questions.filter(|q| !q.answer.is_empty()).count();
+    Ok(Equating {
+        anchors: anchors.into_iter().collect(),
+        raters_x: form_x.len(),
+        raters_y: form_y.len(),
+        slope,
+        intercept,
+        table: (0..=maximum)
+            .map(|score| (score, slope * score as f64 + intercept))
+            .collect(),
+    })
+}
+
+impl Equating {
+    pub fn print(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(
+            out,
+            "{}",
+            t!(
+                "equating.linked",
+                anchors = self.anchors.len(),
+                x = self.raters_x,
+                y = self.raters_y
+            )
+        )?;
+        writeln!(
+            out,
+            "{}",
+            t!(
+                "equating.line",
+                slope = format!("{:.3}", self.slope),
+                intercept = format!("{:+.3}", self.intercept)
+            )
+        )?;
+        writeln!(out)?;
+        let (x, y) = (t!("equating.score_x"), t!("equating.score_y"));
+        let width = x.chars().count();
+        writeln!(out, "{}  {}", x, y)?;
+        for (score, equated) in &self.table {
+            writeln!(out, "{:>width$}  {:.2}", score, equated, width = width)?;
+        }
+        Ok(())
+    }
+}
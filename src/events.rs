@@ -0,0 +1,63 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use chrono::{SecondsFormat, Utc};
+use color_eyre::{eyre::WrapErr, Result};
+use serde_json::{json, Value};
+
+// Opt in research log (event_log = true or --event-log) of what a rater does
+// during a session, to study eg. answer changing. Every session gets its own
+// file in the data dir (~/.local/share/question_cli/logs/), one JSON object per
+// line with the time in milliseconds:
+//
+// {"time":"2026-01-05T14:03:11.482Z","elapsed_ms":5120,"event":"navigate","from":3,"to":4}
+//
+// Events: start (files, mode, rater_id, answered, total), navigate (from, to),
+// response (question, field, old, new), save, end (answered, total).
+// Question numbers count from 1 across the session, as in the interface.
+
+#[derive(Debug)]
+pub struct EventLog {
+    file: File,
+    path: PathBuf,
+    started: Instant,
+}
+
+/// Where the session logs go
+pub fn log_dir(profile: Option<&str>) -> Option<PathBuf> {
+    crate::config::data_dir(profile).map(|dir| dir.join("logs"))
+}
+
+impl EventLog {
+    pub fn create(dir: &Path) -> Result<EventLog> {
+        fs::create_dir_all(dir)
+            .wrap_err_with(|| format!("could not create the log directory {}", dir.display()))?;
+        let name = Utc::now().format("%Y%m%dT%H%M%S%.3fZ.jsonl").to_string();
+        let path = dir.join(name);
+        let file = File::create(&path)
+            .wrap_err_with(|| format!("could not create the event log {}", path.display()))?;
+        Ok(EventLog {
+            file,
+            path,
+            started: Instant::now(),
+        })
+    }
+
+    /// Appends an event, fields is a JSON object with its details
+    pub fn log(&mut self, event: &str, fields: Value) -> Result<()> {
+        let mut entry = json!({
+            "time": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            "elapsed_ms": self.started.elapsed().as_millis() as u64,
+            "event": event,
+        });
+        if let (Some(entry), Value::Object(fields)) = (entry.as_object_mut(), fields) {
+            entry.extend(fields);
+        }
+        writeln!(self.file, "{}", entry)
+            .wrap_err_with(|| format!("could not write to the event log {}", self.path.display()))
+    }
+}
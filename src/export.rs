@@ -0,0 +1,320 @@
+use std::{io::Write, path::PathBuf};
+
+use clap::ValueEnum;
+use color_eyre::{eyre::bail, Result};
+use serde_json::Value;
+
+use crate::{
+    bank::{self, Bank, Classification, Question},
+    grade,
+};
+
+// `question_cli export --to <format> R01.json R02.json ...` turns the answered
+// copies of a bank, one per rater, into files for analysis elsewhere. Raters
+// are told apart by the rater_id saved in their copy, or the file name.
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ExportFormat {
+    /// CSV for REDCap's data import tool: a record per rater, with fields
+    /// q1_answer (option number), q1_higher_order (1/0, 2 unsure), q1_rating...
+    Redcap,
+    /// Tidy CSV for R or pandas, a row per rater and question: rater, question,
+    /// question_id, response, correct, time, classification, rating
+    Long,
+    /// CSV for borderline regression standard setting, a row per rater (or
+    /// candidate): rater, q1... (the points scored on each question), total,
+    /// global_rating (the copy's own "global_rating")
+    Borderline,
+    /// CSV for SPSS or R, a row per rater and a column per question and
+    /// response: q1_answer (option number), q1_correct (1/0), q1_higher_order...
+    /// with a codebook of the columns and their codes in <output>.codebook.csv
+    Wide,
+    /// The embedding of each question of the banks (question_cli embed first)
+    /// by its id, as JSON or, when -o ends in .parquet, Parquet
+    Embeddings,
+}
+
+/// An answered copy of the bank
+pub struct Rater {
+    pub id: String,
+    pub bank: Bank,
+}
+
+/// Loads every rater's copy, they have to be copies of the same bank
+pub fn load_raters(json_paths: &[PathBuf]) -> Result<Vec<Rater>> {
+    let mut raters: Vec<Rater> = vec![];
+    for json_path in json_paths {
+        let bank = bank::load_json(json_path)?;
+        let id = bank.rater_id.clone().unwrap_or_else(|| {
+            let name = json_path.file_name().unwrap_or_default().to_string_lossy();
+            name.split('.').next().unwrap_or_default().to_string()
+        });
+        if let Some(first) = raters.first() {
+            if first.bank.questions.len() != bank.questions.len() {
+                bail!(
+                    "{} has {} questions and {}'s copy has {}, they aren't copies of the same bank",
+                    json_path.display(),
+                    bank.questions.len(),
+                    first.id,
+                    first.bank.questions.len()
+                );
+            }
+        }
+        if raters.iter().any(|rater| rater.id == id) {
+            bail!("two files are from rater {}", id);
+        }
+        raters.push(Rater { id, bank });
+    }
+    Ok(raters)
+}
+
+pub fn export(format: ExportFormat, raters: &[Rater], out: impl Write) -> Result<()> {
+    match format {
+        ExportFormat::Redcap => redcap(raters, out),
+        ExportFormat::Long => long(raters, out),
+        ExportFormat::Borderline => borderline(raters, out),
+        ExportFormat::Wide => matrix(raters, "rater", &WIDE_FIELDS, out),
+        ExportFormat::Embeddings => unreachable!("exported from the banks, see embeddings.rs"),
+    }
+}
+
+// the responses raters can give, as REDCap field suffixes and coded values
+type Field = (&'static str, fn(&Question) -> Option<String>);
+
+const REDCAP_FIELDS: [Field; 5] = [
+    ("answer", |q| option_number(q, q.human_answer.as_deref())),
+    ("higher_order", |q| {
+        q.is_higher_order.map(|value| match value {
+            Classification::HigherOrder => "1".to_string(),
+            Classification::LowerOrder => "0".to_string(),
+            Classification::Unsure => "2".to_string(),
+        })
+    }),
+    ("confidence", |q| {
+        q.confidence.map(|confidence| confidence.to_string())
+    }),
+    ("rating", |q| q.rating.map(|rating| rating.to_string())),
+    ("adjudicated", |q| {
+        option_number(q, q.adjudicated_answer.as_deref())
+    }),
+];
+
+// the same with whether the answer is right after it, 1 or 0
+const WIDE_FIELDS: [Field; 6] = [
+    REDCAP_FIELDS[0],
+    ("correct", |q| {
+        q.is_correct().map(|right| u8::from(right).to_string())
+    }),
+    REDCAP_FIELDS[1],
+    REDCAP_FIELDS[2],
+    REDCAP_FIELDS[3],
+    REDCAP_FIELDS[4],
+];
+
+// REDCap radio buttons are coded, so options go out as their number from 1
+fn option_number(question: &Question, option: Option<&str>) -> Option<String> {
+    let position = question
+        .options
+        .iter()
+        .position(|o| Some(o.as_str()) == option)?;
+    Some((position + 1).to_string())
+}
+
+// only the fields some rater filled in, so a classify only study doesn't get
+// empty answer fields
+fn used<'a>(raters: &[Rater], fields: &'a [Field]) -> Vec<&'a Field> {
+    fields
+        .iter()
+        .filter(|(_, value)| {
+            raters
+                .iter()
+                .flat_map(|rater| &rater.bank.questions)
+                .any(|question| value(question).is_some())
+        })
+        .collect()
+}
+
+fn redcap(raters: &[Rater], out: impl Write) -> Result<()> {
+    matrix(raters, "record_id", &REDCAP_FIELDS, out)
+}
+
+// a row per rater, a column per question and field
+fn matrix(raters: &[Rater], id_column: &str, fields: &[Field], out: impl Write) -> Result<()> {
+    let fields = used(raters, fields);
+    let count = raters.first().map_or(0, |rater| rater.bank.questions.len());
+
+    let mut writer = csv::Writer::from_writer(out);
+    let mut header = vec![id_column.to_string()];
+    for number in 1..=count {
+        header.extend(
+            fields
+                .iter()
+                .map(|(suffix, _)| format!("q{}_{}", number, suffix)),
+        );
+    }
+    writer.write_record(&header)?;
+    for rater in raters {
+        let mut record = vec![rater.id.clone()];
+        for question in &rater.bank.questions {
+            record.extend(
+                fields
+                    .iter()
+                    .map(|(_, value)| value(question).unwrap_or_default()),
+            );
+        }
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn long(raters: &[Rater], out: impl Write) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(out);
+    writer.write_record([
+        "rater",
+        "question",
+        "question_id",
+        "response",
+        "correct",
+        "time",
+        "classification",
+        "confidence",
+        "rating",
+        "skipped",
+        "reaction_ms",
+    ])?;
+    for rater in raters {
+        let dwell = rater
+            .bank
+            .time_spent
+            .as_ref()
+            .map(|time_spent| time_spent.dwell_seconds.as_slice())
+            .unwrap_or_default();
+        for (i, question) in rater.bank.questions.iter().enumerate() {
+            // the bank's own ids when it has them, so rows can be joined to it
+            let question_id = match question.extra.get("id") {
+                Some(Value::String(id)) => id.clone(),
+                Some(id) => id.to_string(),
+                None => (i + 1).to_string(),
+            };
+            let correct = match (&question.human_answer, question.answer.as_str()) {
+                (Some(response), answer) if !answer.is_empty() => (*response == answer).to_string(),
+                _ => String::new(),
+            };
+            let time = dwell
+                .get(i)
+                .map(|seconds| format!("{:.1}", seconds))
+                .unwrap_or_default();
+            writer.write_record([
+                rater.id.clone(),
+                (i + 1).to_string(),
+                question_id,
+                question.human_answer.clone().unwrap_or_default(),
+                correct,
+                time,
+                question
+                    .is_higher_order
+                    .map(|value| value.to_string())
+                    .unwrap_or_default(),
+                question
+                    .confidence
+                    .map(|confidence| confidence.to_string())
+                    .unwrap_or_default(),
+                question
+                    .rating
+                    .map(|rating| rating.to_string())
+                    .unwrap_or_default(),
+                question.skipped.join(";"),
+                question
+                    .extra
+                    .get("reaction_ms")
+                    .map(Value::to_string)
+                    .unwrap_or_default(),
+            ])?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn borderline(raters: &[Rater], out: impl Write) -> Result<()> {
+    let count = raters.first().map_or(0, |rater| rater.bank.questions.len());
+    let mut writer = csv::Writer::from_writer(out);
+    let mut header = vec!["rater".to_string()];
+    header.extend((1..=count).map(|number| format!("q{}", number)));
+    header.extend(["total".to_string(), "global_rating".to_string()]);
+    writer.write_record(&header)?;
+    for rater in raters {
+        // a question's weight when it's right, blank without an answer key
+        let scores: Vec<Option<f64>> = rater
+            .bank
+            .questions
+            .iter()
+            .map(|q| match q.human_answer.as_ref() == Some(&q.answer) {
+                _ if q.answer.is_empty() => None,
+                true => Some(q.weight()),
+                false => Some(0.0),
+            })
+            .collect();
+        let total = scores.iter().flatten().fold(0.0, |sum, score| sum + score);
+        let mut record = vec![rater.id.clone()];
+        record.extend(
+            scores
+                .iter()
+                .map(|score| score.map(grade::points).unwrap_or_default()),
+        );
+        record.push(grade::points(total));
+        // a number (1 fail to 5 excellent...) or a label, as the examiner gave it
+        record.push(match rater.bank.extra.get("global_rating") {
+            Some(Value::String(rating)) => rating.clone(),
+            Some(rating) => rating.to_string(),
+            None => String::new(),
+        });
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// What the columns of the wide export are and how they're coded, a row per
+/// column: variable, label (the question's stem), values
+pub fn codebook(raters: &[Rater], out: impl Write) -> Result<()> {
+    let fields = used(raters, &WIDE_FIELDS);
+    let mut writer = csv::Writer::from_writer(out);
+    writer.write_record(["variable", "label", "values"])?;
+    writer.write_record(["rater", "Rater ID, or the file name", ""])?;
+    let Some(first) = raters.first() else {
+        writer.flush()?;
+        return Ok(());
+    };
+    for (i, question) in first.bank.questions.iter().enumerate() {
+        let stem = question.question.trim().lines().next().unwrap_or_default();
+        let stem: String = match stem.chars().count() > 80 {
+            true => stem.chars().take(79).chain(['…']).collect(),
+            false => stem.to_string(),
+        };
+        for (suffix, _) in &fields {
+            let values = match *suffix {
+                "answer" | "adjudicated" => question
+                    .options
+                    .iter()
+                    .enumerate()
+                    .map(|(n, option)| format!("{} = {}", n + 1, option))
+                    .collect::<Vec<_>>()
+                    .join("; "),
+                "correct" => "1 = right; 0 = wrong".to_string(),
+                "higher_order" => "1 = higher order; 0 = lower order; 2 = unsure".to_string(),
+                "confidence" => "50 to 100 (%)".to_string(),
+                "rating" => "1 to 5".to_string(),
+                _ => String::new(),
+            };
+            writer.write_record([
+                format!("q{}_{}", i + 1, suffix),
+                format!("Q{} {}: {}", i + 1, suffix.replace('_', " "), stem),
+                values,
+            ])?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::{bank::Question, export::Rater, i18n::t};
+
+// `question_cli grade R01.json R02.json ...` scores each rater's answered copy
+// against the answer key. Questions count for their "weight" (1 without one),
+// unanswered ones count as wrong and those without an answer key aren't
+// scored at all. With a pass mark (pass_mark in the config or --pass-mark, a
+// percent of the points) each rater passes or fails, and how far they are from
+// the cut score shows the borderline ones.
+
+/// A rater's score, printed by `question_cli grade`
+#[derive(Debug, Serialize)]
+pub struct Grade {
+    pub rater: String,
+    pub points: f64, // weights of the questions answered right
+    pub total: f64,  // weights of all the scored questions
+    pub correct: usize,
+    pub answered: usize,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<Verdict>,
+}
+
+/// Against the pass mark
+#[derive(Debug, Serialize)]
+pub struct Verdict {
+    pub passed: bool,
+    pub cut_score: f64, // in points
+    pub margin: f64,    // points above the cut score, negative below it
+}
+
+impl Verdict {
+    pub fn of(points: f64, total: f64, pass_mark: f64) -> Verdict {
+        let cut_score = total * pass_mark / 100.0;
+        // rounded like the points shown, so exactly on the cut score passes
+        // (+ 0.0 turns a -0 into 0)
+        let margin = ((points - cut_score) * 100.0).round() / 100.0 + 0.0;
+        Verdict {
+            passed: margin >= 0.0,
+            cut_score,
+            margin,
+        }
+    }
+
+    /// "pass, 1.5 points above the cut score of 12"
+    pub fn describe(&self) -> String {
+        let cut_score = points(self.cut_score);
+        match self.passed {
+            true => t!(
+                "grade.pass",
+                margin = points(self.margin),
+                cut_score = cut_score
+            ),
+            false => t!(
+                "grade.fail",
+                margin = points(-self.margin),
+                cut_score = cut_score
+            ),
+        }
+    }
+}
+
+impl Grade {
+    pub fn of(rater: &Rater, pass_mark: Option<f64>) -> Grade {
+        let scored: Vec<&Question> = rater
+            .bank
+            .questions
+            .iter()
+            .filter(|q| !q.answer.is_empty())
+            .collect();
+        let correct: Vec<&Question> = scored
+            .iter()
+            .copied()
+            .filter(|q| q.human_answer.as_ref() == Some(&q.answer))
+            .collect();
+        let points = weights(correct.iter().copied());
+        let total = weights(scored.iter().copied());
+        Grade {
+            rater: rater.id.clone(),
+            points,
+            total,
+            correct: correct.len(),
+            answered: scored.iter().filter(|q| q.human_answer.is_some()).count(),
+            verdict: pass_mark.map(|pass_mark| Verdict::of(points, total, pass_mark)),
+        }
+    }
+
+    pub fn percent(&self) -> f64 {
+        match self.total > 0.0 {
+            true => self.points * 100.0 / self.total,
+            false => 0.0,
+        }
+    }
+
+    pub fn print(&self, out: &mut impl Write) -> io::Result<()> {
+        let score = t!(
+            "grade.rater",
+            rater = self.rater,
+            points = points(self.points),
+            total = points(self.total),
+            percent = format!("{:.0}", self.percent()),
+            correct = self.correct,
+            answered = self.answered
+        );
+        match &self.verdict {
+            Some(verdict) => writeln!(out, "{}, {}", score, verdict.describe()),
+            None => writeln!(out, "{}", score),
+        }
+    }
+}
+
+/// The questions' weights added up
+pub fn weights<'a>(questions: impl Iterator<Item = &'a Question>) -> f64 {
+    // not sum(), that's -0 for no questions
+    questions
+        .map(Question::weight)
+        .fold(0.0, |sum, weight| sum + weight)
+}
+
+/// A number of points without the float noise: 2, 1.5, 0.33
+pub fn points(value: f64) -> String {
+    ((value * 100.0).round() / 100.0).to_string()
+}
@@ -0,0 +1,197 @@
+use std::{
+    env,
+    io::{stdout, Write},
+    path::{Path, PathBuf},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use icy_sixel::{DiffusionMethod, MethodForLargest, MethodForRep, PixelFormat, Quality};
+use image::{imageops::FilterType, DynamicImage};
+use ratatui::{
+    crossterm::{
+        cursor::{MoveTo, RestorePosition, SavePosition},
+        queue,
+        terminal::window_size,
+    },
+    layout::Rect,
+};
+
+// kitty only accepts the payload in chunks of at most 4096 bytes
+const KITTY_CHUNK_SIZE: usize = 4096;
+// used when the terminal doesn't report its size in pixels
+const DEFAULT_CELL_SIZE: (u32, u32) = (8, 16);
+
+/// Terminal graphics protocols we know how to speak
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Protocol {
+    Kitty,
+    Iterm2,
+    Sixel,
+    #[default]
+    Unsupported,
+}
+
+impl Protocol {
+    /// Guess the protocol from the environment. QUESTION_CLI_GRAPHICS (kitty, iterm, sixel
+    /// or none) overrides the guess for terminals we don't recognise.
+    pub fn detect() -> Protocol {
+        let var = |name: &str| env::var(name).unwrap_or_default().to_lowercase();
+        match var("QUESTION_CLI_GRAPHICS").as_str() {
+            "kitty" => return Protocol::Kitty,
+            "iterm" | "iterm2" => return Protocol::Iterm2,
+            "sixel" => return Protocol::Sixel,
+            "none" => return Protocol::Unsupported,
+            _ => {}
+        }
+        let (term, term_program) = (var("TERM"), var("TERM_PROGRAM"));
+        if env::var_os("KITTY_WINDOW_ID").is_some()
+            || term.contains("kitty")
+            || term.contains("ghostty")
+        {
+            Protocol::Kitty
+        } else if ["iterm.app", "wezterm", "vscode"].contains(&term_program.as_str()) {
+            Protocol::Iterm2
+        } else if term.contains("sixel") || ["foot", "mlterm", "contour"].contains(&term.as_str()) {
+            Protocol::Sixel
+        } else {
+            Protocol::Unsupported
+        }
+    }
+}
+
+/// Draws question images on top of the ratatui buffer. Images are written straight
+/// to the terminal after each frame, so this remembers what is on screen to avoid
+/// re-sending the same image on every key press.
+#[derive(Debug, Default)]
+pub struct ImageView {
+    protocol: Protocol,
+    shown: Option<(PathBuf, Rect)>,
+}
+
+impl ImageView {
+    pub fn new() -> ImageView {
+        ImageView {
+            protocol: Protocol::detect(),
+            shown: None,
+        }
+    }
+
+    pub fn is_supported(&self) -> bool {
+        self.protocol != Protocol::Unsupported
+    }
+
+    /// ratatui only redraws cells that changed, so when a different image (or none)
+    /// is about to be shown the terminal has to be cleared to wipe the old pixels.
+    pub fn needs_clear(&self, next: Option<&Path>) -> bool {
+        match &self.shown {
+            Some((path, _)) => Some(path.as_path()) != next,
+            None => false,
+        }
+    }
+
+    /// Shows the image at path inside area (in cells), or removes it if either is None.
+    pub fn draw(&mut self, path: Option<&Path>, area: Option<Rect>) -> Result<()> {
+        let (path, area) = match (path, area) {
+            (Some(path), Some(area)) if self.is_supported() && area.area() > 0 => (path, area),
+            _ => {
+                if self.shown.take().is_some() && self.protocol == Protocol::Kitty {
+                    write_at(None, "\x1b_Ga=d,d=A,q=2\x1b\\")?; // delete all kitty images
+                }
+                return Ok(());
+            }
+        };
+        if self
+            .shown
+            .as_ref()
+            .is_some_and(|shown| shown == &(path.to_path_buf(), area))
+        {
+            return Ok(());
+        }
+        self.shown = Some((path.to_path_buf(), area));
+
+        let bytes = std::fs::read(path)
+            .wrap_err_with(|| format!("could not read image: {}", path.display()))?;
+        let sequence = match self.protocol {
+            Protocol::Kitty => {
+                let image = fit(&bytes, area)?.to_rgba8();
+                let encoded = STANDARD.encode(image.as_raw());
+                let mut sequence = String::from("\x1b_Ga=d,d=A,q=2\x1b\\"); // drop the previous image
+                let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+                for (i, chunk) in chunks.iter().enumerate() {
+                    let more = u8::from(i + 1 < chunks.len());
+                    let chunk = std::str::from_utf8(chunk).expect("base64 is ascii");
+                    if i == 0 {
+                        sequence.push_str(&format!(
+                            "\x1b_Ga=T,f=32,s={},v={},C=1,q=2,m={};{}\x1b\\",
+                            image.width(),
+                            image.height(),
+                            more,
+                            chunk
+                        ));
+                    } else {
+                        sequence.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+                    }
+                }
+                sequence
+            }
+            // iTerm2 decodes and scales the original file itself
+            Protocol::Iterm2 => format!(
+                "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1:{}\x07",
+                bytes.len(),
+                area.width,
+                area.height,
+                STANDARD.encode(&bytes)
+            ),
+            Protocol::Sixel => {
+                let image = fit(&bytes, area)?.to_rgb8();
+                icy_sixel::sixel_string(
+                    image.as_raw(),
+                    image.width() as i32,
+                    image.height() as i32,
+                    PixelFormat::RGB888,
+                    DiffusionMethod::Stucki,
+                    MethodForLargest::Auto,
+                    MethodForRep::Auto,
+                    Quality::HIGH,
+                )
+                .map_err(|err| eyre!("sixel encoding failed: {err}"))?
+            }
+            Protocol::Unsupported => unreachable!("checked above"),
+        };
+        write_at(Some(area), &sequence)
+    }
+}
+
+// decodes the image and scales it to fit in area, keeping the aspect ratio
+fn fit(bytes: &[u8], area: Rect) -> Result<DynamicImage> {
+    let image = image::load_from_memory(bytes).wrap_err("could not decode image")?;
+    let (cell_width, cell_height) = match window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => (
+            (size.width / size.columns) as u32,
+            (size.height / size.rows) as u32,
+        ),
+        _ => DEFAULT_CELL_SIZE,
+    };
+    Ok(image.resize(
+        area.width as u32 * cell_width,
+        area.height as u32 * cell_height,
+        FilterType::Triangle,
+    ))
+}
+
+// writes an escape sequence with the cursor at the top left of area
+fn write_at(area: Option<Rect>, sequence: &str) -> Result<()> {
+    let mut stdout = stdout();
+    queue!(stdout, SavePosition)?;
+    if let Some(area) = area {
+        queue!(stdout, MoveTo(area.x, area.y))?;
+    }
+    stdout.write_all(sequence.as_bytes())?;
+    queue!(stdout, RestorePosition)?;
+    stdout.flush()?;
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use ratatui::{
+    crossterm::event::KeyCode,
+    prelude::*,
+    widgets::{Block, Borders, Clear, Padding, Paragraph},
+};
+
+use crate::{bank::Banks, i18n::t, mode::Mode, popup, theme::Palette};
+
+// The whole session at a glance (`p`): a cell per question, ten to a row,
+// coloured by where it stands. The arrows move the highlight, Space jumps it to
+// the next question still missing and Enter goes to the highlighted one.
+
+const COLUMNS: usize = 10;
+
+/// What a key press does to the open grid, like popup::choose
+pub fn choose(code: KeyCode, selected: usize, banks: &Banks, mode: Mode) -> popup::Choice {
+    let last = banks.len() - 1;
+    match code {
+        KeyCode::Left => popup::Choice::Highlight(selected.saturating_sub(1)),
+        KeyCode::Right => popup::Choice::Highlight((selected + 1).min(last)),
+        KeyCode::Up => popup::Choice::Highlight(selected.checked_sub(COLUMNS).unwrap_or(selected)),
+        KeyCode::Down => popup::Choice::Highlight(match selected + COLUMNS {
+            below if below <= last => below,
+            _ => selected,
+        }),
+        KeyCode::Home => popup::Choice::Highlight(0),
+        KeyCode::End => popup::Choice::Highlight(last),
+        // the next gap after the highlight
+        KeyCode::Char(' ') => {
+            popup::Choice::Highlight(mode.next_missing(banks, selected).unwrap_or(selected))
+        }
+        KeyCode::Enter => popup::Choice::Pick(selected),
+        _ => popup::Choice::Close,
+    }
+}
+
+/// The grid over the middle of area, the current question underlined
+pub fn render(
+    frame: &mut Frame,
+    area: Rect,
+    banks: &Banks,
+    mode: Mode,
+    current: usize,
+    selected: usize,
+    palette: &Palette,
+) {
+    let color = |index: usize| {
+        let question = banks.question(index);
+        match (mode.is_done(question), question.is_flagged()) {
+            (_, true) => palette.info,
+            _ if mode.is_skipped(question) => palette.option,
+            (true, false) => palette.selected,
+            (false, false) => palette.missing,
+        }
+    };
+    let rows = banks.len().div_ceil(COLUMNS);
+    let legend = Line::from(vec![
+        "■ ".fg(palette.selected),
+        t!("grid.done").into(),
+        "  ■ ".fg(palette.info),
+        t!("grid.flagged").into(),
+        "  ■ ".fg(palette.option),
+        t!("grid.skipped").into(),
+        "  ■ ".fg(palette.missing),
+        t!("grid.missing").into(),
+    ]);
+    let controls = Line::from(t!("grid.controls"));
+    // "   1 " then two columns per cell, or as wide as the legend and controls
+    let width = (5 + 2 * COLUMNS).max(legend.width()).max(controls.width()) as u16 + 4;
+    let block = Block::new()
+        .borders(Borders::ALL)
+        .title(format!(" {} ", t!("grid.title")))
+        .title_bottom(controls.centered())
+        .padding(Padding::horizontal(1));
+    let area = popup::centered(area, width, rows as u16 + 4);
+    let inner = block.inner(area);
+    // rows beyond the screen scroll to keep the highlight in view
+    let shown = (inner.height as usize).saturating_sub(2).max(1);
+    let first = (selected / COLUMNS).saturating_sub(shown - 1);
+    let mut lines: Vec<Line> = (first..rows.min(first + shown))
+        .map(|row| {
+            let start = row * COLUMNS;
+            let mut spans = vec![format!("{:>4} ", start + 1).dim()];
+            for index in start..banks.len().min(start + COLUMNS) {
+                let mut cell = "■".fg(color(index));
+                if index == current {
+                    cell = cell.underlined();
+                }
+                if index == selected {
+                    cell = cell.reversed();
+                }
+                spans.extend([cell, " ".into()]);
+            }
+            Line::from(spans)
+        })
+        .collect();
+    lines.push(Line::default());
+    lines.push(legend);
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
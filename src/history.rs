@@ -0,0 +1,148 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use chrono::{DateTime, Local};
+use color_eyre::{eyre::WrapErr, Result};
+use serde_json::Value;
+
+use crate::i18n::t;
+
+// `question_cli history` goes through the event logs (see events.rs) and shows,
+// per bank, the sessions it was worked on and how far along it was after each:
+//
+// /home/me/banks/cardio.json
+//   2026-01-05 14:03  answer     25m 10s  ██████░░░░░░░░░░░░░░  12/40
+//   2026-01-06 09:12  answer     40m 02s  ████████████████████  40/40
+//   Sessions: 2, 1h 05m in total
+
+const BAR_WIDTH: usize = 20;
+
+/// One session as read back from its event log
+#[derive(Debug)]
+pub struct Session {
+    started: DateTime<Local>,
+    files: Vec<String>,
+    mode: String,
+    duration_ms: u64,
+    answered: usize, // at the end of the session, or at the start if it didn't end cleanly
+    total: usize,
+}
+
+/// The sessions logged in dir, oldest first. Logs without a start event (ie.
+/// cut short while being created) are left out.
+pub fn read_sessions(dir: &Path) -> Result<Vec<Session>> {
+    let mut log_paths = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+            .collect::<Vec<_>>(),
+        // no sessions logged yet
+        Err(err) if err.kind() == io::ErrorKind::NotFound => vec![],
+        Err(err) => return Err(err).wrap_err_with(|| format!("could not read {}", dir.display())),
+    };
+    // logs are named after the time they were started
+    log_paths.sort();
+    let mut sessions = vec![];
+    for log_path in log_paths {
+        let data = fs::read_to_string(&log_path)
+            .wrap_err_with(|| format!("could not read {}", log_path.display()))?;
+        // a session that crashed can leave half a line at the end
+        let events: Vec<Value> = data
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        if let Some(session) = Session::from_events(&events) {
+            sessions.push(session);
+        }
+    }
+    Ok(sessions)
+}
+
+impl Session {
+    fn from_events(events: &[Value]) -> Option<Session> {
+        let start = events.iter().find(|event| event["event"] == "start")?;
+        let end = events.iter().rev().find(|event| event["event"] == "end");
+        let count = |event: &Value, key| event[key].as_u64().unwrap_or_default() as usize;
+        Some(Session {
+            started: DateTime::parse_from_rfc3339(start["time"].as_str()?)
+                .ok()?
+                .with_timezone(&Local),
+            files: start["files"]
+                .as_array()?
+                .iter()
+                .filter_map(|file| Some(file.as_str()?.to_string()))
+                .collect(),
+            mode: start["mode"].as_str().unwrap_or_default().to_string(),
+            duration_ms: events
+                .iter()
+                .filter_map(|event| event["elapsed_ms"].as_u64())
+                .max()
+                .unwrap_or_default(),
+            answered: count(end.unwrap_or(start), "answered"),
+            total: count(start, "total"),
+        })
+    }
+}
+
+/// Prints the sessions grouped by bank
+pub fn print(sessions: &[Session], out: &mut impl Write) -> io::Result<()> {
+    if sessions.is_empty() {
+        return writeln!(out, "{}", t!("history.empty"));
+    }
+    let mut banks: BTreeMap<&str, Vec<&Session>> = BTreeMap::new();
+    for session in sessions {
+        for file in &session.files {
+            banks.entry(file).or_default().push(session);
+        }
+    }
+    for (i, (file, sessions)) in banks.iter().enumerate() {
+        if i > 0 {
+            writeln!(out)?;
+        }
+        writeln!(out, "{}", file)?;
+        for session in sessions {
+            writeln!(
+                out,
+                "  {}  {:<10} {:>8}  {}  {}/{}",
+                session.started.format("%Y-%m-%d %H:%M"),
+                session.mode,
+                duration(session.duration_ms),
+                bar(session.answered, session.total),
+                session.answered,
+                session.total
+            )?;
+        }
+        let total_ms = sessions.iter().map(|session| session.duration_ms).sum();
+        writeln!(
+            out,
+            "  {}",
+            t!(
+                "history.summary",
+                count = sessions.len(),
+                time = duration(total_ms)
+            )
+        )?;
+    }
+    Ok(())
+}
+
+// 1h 05m, 25m 10s
+fn duration(ms: u64) -> String {
+    let seconds = ms / 1000;
+    match seconds {
+        0..=3599 => format!("{}m {:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h {:02}m", seconds / 3600, seconds / 60 % 60),
+    }
+}
+
+fn bar(done: usize, total: usize) -> String {
+    let filled = match total {
+        0 => 0,
+        total => (done * BAR_WIDTH / total).min(BAR_WIDTH),
+    };
+    "█".repeat(filled) + &"░".repeat(BAR_WIDTH - filled)
+}
@@ -0,0 +1,200 @@
+use std::{
+    io::Write,
+    process::{Child, Command, Stdio},
+};
+
+use color_eyre::{eyre::bail, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::proctor;
+
+// Commands run on the events of a session, to plug question_cli into other
+// tools without it knowing about them: notify a Slack channel, push the
+// responses to a server, commit the bank... Set in the config file:
+//
+// [[hooks]]
+// on = "answered"
+// command = ["curl", "-s", "-d", "@-", "https://example.org/responses"]
+//
+// [[hooks]]
+// on = "save"
+// script = "git commit -qm 'responses' questions.json" # through sh -c (cmd /C on Windows)
+//
+// The events are those of the event log (see events.rs) with answered, a
+// question done, and pre_save. Each is given to the hook as JSON on stdin,
+// with the session's files, mode and rater_id:
+//
+// {"event":"answered","time":"2026-...","files":["/home/me/questions.json"],"mode":"answer","rater_id":"R01","question":4,"id":"Q4","field":"human_answer","value":"Heparin","skipped":false}
+//
+// and its name in $QUESTION_CLI_EVENT. Hooks run in the background, their
+// output is dropped and a failure shows in the status line, except pre_save
+// hooks: the save waits for them and doesn't happen if one fails, eg. to check
+// the bank first.
+
+pub const EVENTS: [&str; 9] = [
+    "start",
+    "navigate",
+    "response",
+    "answered",
+    "eliminate",
+    "reveal",
+    "pre_save",
+    "save",
+    "end",
+];
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Hook {
+    pub on: String,
+    pub command: Option<Vec<String>>, // run as is
+    pub script: Option<String>,       // run by the shell
+}
+
+#[derive(Debug, Default)]
+pub struct Hooks {
+    hooks: Vec<Hook>,
+    context: serde_json::Map<String, Value>, // added to every event
+    running: Vec<(String, Child)>,           // with what's shown if it fails
+}
+
+impl Hooks {
+    /// Checks the config's hooks, context being the session's files, mode...
+    pub fn new(hooks: &[Hook], context: Value) -> Result<Hooks> {
+        for hook in hooks {
+            if !EVENTS.contains(&hook.on.as_str()) {
+                bail!(
+                    "there's no {} event to hook, the events are {}",
+                    hook.on,
+                    EVENTS.join(", ")
+                );
+            }
+            match (&hook.command, &hook.script) {
+                (Some(command), None) if !command.is_empty() => {}
+                (None, Some(_)) => {}
+                _ => bail!(
+                    "the {} hook needs either a command (a list of its words) or a script",
+                    hook.on
+                ),
+            }
+        }
+        Ok(Hooks {
+            hooks: hooks.to_vec(),
+            context: match context {
+                Value::Object(context) => context,
+                _ => Default::default(),
+            },
+            running: vec![],
+        })
+    }
+
+    /// Starts the event's hooks. Returns what went wrong with the hooks that
+    /// failed since the last event, if any did.
+    pub fn run(&mut self, event: &str, fields: &Value) -> Option<String> {
+        let mut failed = self.finished();
+        let payload = self.payload(event, fields);
+        for hook in self.hooks.iter().filter(|hook| hook.on == event) {
+            match start(hook, event, &payload) {
+                Ok(child) => self.running.push((name(hook), child)),
+                Err(err) => failed.push(format!("{}: {}", name(hook), err)),
+            }
+        }
+        (!failed.is_empty()).then(|| failed.join(", "))
+    }
+
+    /// Runs the pre_save hooks one after the other, an error if one failed
+    pub fn pre_save(&mut self) -> Result<()> {
+        let payload = self.payload("pre_save", &Value::Null);
+        for hook in self.hooks.iter().filter(|hook| hook.on == "pre_save") {
+            let status = start(hook, "pre_save", &payload).and_then(|mut child| child.wait());
+            match status {
+                Ok(status) if status.success() => {}
+                Ok(status) => bail!("{} {}", name(hook), status),
+                Err(err) => bail!("{}: {}", name(hook), err),
+            }
+        }
+        Ok(())
+    }
+
+    /// Waits for the hooks still running, when the session ends
+    pub fn finish(&mut self) {
+        for (_, child) in &mut self.running {
+            let _ = child.wait();
+        }
+        self.running.clear();
+    }
+
+    // the hooks that exited since, with what went wrong for those that failed
+    fn finished(&mut self) -> Vec<String> {
+        let mut failed = vec![];
+        self.running
+            .retain_mut(|(name, child)| match child.try_wait() {
+                Ok(None) => true,
+                Ok(Some(status)) if status.success() => false,
+                Ok(Some(status)) => {
+                    failed.push(format!("{} {}", name, status));
+                    false
+                }
+                Err(err) => {
+                    failed.push(format!("{}: {}", name, err));
+                    false
+                }
+            });
+        failed
+    }
+
+    fn payload(&self, event: &str, fields: &Value) -> String {
+        let mut payload = serde_json::Map::new();
+        payload.insert("event".to_string(), Value::from(event));
+        payload.insert("time".to_string(), Value::from(proctor::now()));
+        payload.extend(self.context.clone());
+        if let Value::Object(fields) = fields {
+            payload.extend(fields.clone());
+        }
+        Value::Object(payload).to_string()
+    }
+}
+
+// the program, for messages
+fn name(hook: &Hook) -> String {
+    match (&hook.command, &hook.script) {
+        (Some(command), _) => command[0].clone(),
+        (None, Some(script)) => script
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        (None, None) => String::new(),
+    }
+}
+
+fn start(hook: &Hook, event: &str, payload: &str) -> std::io::Result<Child> {
+    let mut command = match (&hook.command, &hook.script) {
+        (Some(words), _) => {
+            let mut command = Command::new(&words[0]);
+            command.args(&words[1..]);
+            command
+        }
+        (None, script) => {
+            let (shell, flag) = match cfg!(windows) {
+                true => ("cmd", "/C"),
+                false => ("sh", "-c"),
+            };
+            let mut command = Command::new(shell);
+            command.args([flag, script.as_deref().unwrap_or_default()]);
+            command
+        }
+    };
+    let mut child = command
+        .env("QUESTION_CLI_EVENT", event)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    // a hook that doesn't read it is fine
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(payload.as_bytes());
+    }
+    Ok(child)
+}
@@ -0,0 +1,107 @@
+use std::{collections::HashMap, env, sync::OnceLock};
+
+use color_eyre::{eyre::eyre, Result};
+
+/// Locale files compiled into the binary, English first as it is the fallback.
+const LOCALES: [(&str, &str); 2] = [
+    ("en", include_str!("../locales/en.toml")),
+    ("fr", include_str!("../locales/fr.toml")),
+];
+
+// (selected locale, english) flattened to "section.key" => text
+static STRINGS: OnceLock<(HashMap<String, String>, HashMap<String, String>)> = OnceLock::new();
+
+/// Looks up a UI string, ie. t!("status.missing_answer"). With arguments the
+/// {placeholders} are filled in and a String is returned instead of a &str:
+/// t!("status.saved", time = now).
+macro_rules! t {
+    ($key:expr) => {
+        $crate::i18n::get($key)
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::format($crate::i18n::get($key), &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+pub(crate) use t;
+
+/// Selects the UI language. Without an explicit lang the usual LC_ALL,
+/// LC_MESSAGES and LANG variables are checked, falling back to English.
+pub fn init(lang: Option<&str>) -> Result<()> {
+    let selected = match lang {
+        Some(lang) => find(lang).ok_or_else(|| {
+            let available: Vec<&str> = LOCALES.iter().map(|(name, _)| *name).collect();
+            eyre!(
+                "unknown language '{}', available: {}",
+                lang,
+                available.join(", ")
+            )
+        })?,
+        None => ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| find(&value))
+            .unwrap_or(LOCALES[0].1),
+    };
+    let _ = STRINGS.set((parse(selected)?, parse(LOCALES[0].1)?));
+    Ok(())
+}
+
+// "fr", "fr_CA.UTF-8", "fr-CA" all select fr
+fn find(lang: &str) -> Option<&'static str> {
+    let lang = lang.to_lowercase();
+    let code = lang.split(['_', '-', '.']).next().unwrap_or_default();
+    LOCALES
+        .iter()
+        .find(|(name, _)| *name == code)
+        .map(|(_, source)| *source)
+}
+
+fn parse(source: &str) -> Result<HashMap<String, String>> {
+    let table: toml::Table = toml::from_str(source)?;
+    let mut strings = HashMap::new();
+    flatten("", &table, &mut strings);
+    Ok(strings)
+}
+
+fn flatten(prefix: &str, table: &toml::Table, strings: &mut HashMap<String, String>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            toml::Value::Table(table) => flatten(&key, table, strings),
+            toml::Value::String(text) => {
+                strings.insert(key, text.clone());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The text for key in the selected language, falling back to English and then
+/// to the key itself so a missing translation is visible but never fatal.
+pub fn get(key: &str) -> &'static str {
+    let (selected, english) = STRINGS.get_or_init(|| {
+        let english = parse(LOCALES[0].1).expect("bundled en.toml is valid");
+        (english.clone(), english)
+    });
+    selected
+        .get(key)
+        .or_else(|| english.get(key))
+        .map(String::as_str)
+        .unwrap_or_else(|| {
+            // leaking is fine, this only happens for keys missing from every locale
+            Box::leak(key.to_string().into_boxed_str())
+        })
+}
+
+pub fn format(template: &str, args: &[(&str, String)]) -> String {
+    let mut text = template.to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
@@ -0,0 +1,190 @@
+use std::{path::Path, str::FromStr};
+
+use calamine::{open_workbook_auto, Reader};
+use color_eyre::{
+    eyre::{bail, eyre, WrapErr},
+    Result,
+};
+use serde_json::{json, Map, Value};
+
+use crate::bank::{Bank, Question};
+
+// Question authors often write banks as a spreadsheet, one question per row.
+// The first row names the columns:
+//
+// question | option a | option b | option c | answer | topic
+//
+// Options are the columns named "option ..." or a single letter (A, B, C...),
+// in order, empty cells are left out. The answer can be the option's text, its
+// letter or its number. topic, learning_objective and reference go in the
+// metadata, any other column (ids, authors...) is kept as is.
+//
+// Sheets with other headers are mapped with --column FIELD=HEADER, eg.
+// --column question=Stem --column option=Choice1 --column option=Choice2.
+// Mapped options replace the automatic ones, in the order given.
+
+const METADATA_COLUMNS: [&str; 3] = ["topic", "learning_objective", "reference"];
+
+/// A --column FIELD=HEADER mapping
+#[derive(Debug, Clone)]
+pub struct Column {
+    pub field: String,
+    pub header: String,
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Column, String> {
+        let (field, header) = text
+            .split_once('=')
+            .ok_or_else(|| format!("expected FIELD=HEADER, got '{}'", text))?;
+        Ok(Column {
+            field: field.trim().to_lowercase(),
+            header: header.trim().to_string(),
+        })
+    }
+}
+
+/// Reads the cells of a worksheet (the first one by default) of an .xlsx,
+/// .xls or .ods file
+pub fn read_workbook(path: &Path, sheet: Option<&str>) -> Result<Vec<Vec<String>>> {
+    let mut workbook =
+        open_workbook_auto(path).wrap_err_with(|| format!("could not open {}", path.display()))?;
+    let sheet = match sheet {
+        Some(sheet) => sheet.to_string(),
+        None => workbook
+            .sheet_names()
+            .first()
+            .cloned()
+            .ok_or_else(|| eyre!("{} has no sheets", path.display()))?,
+    };
+    let range = workbook
+        .worksheet_range(&sheet)
+        .wrap_err_with(|| format!("could not read the sheet '{}'", sheet))?;
+    Ok(range
+        .rows()
+        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+        .collect())
+}
+
+/// The bank in a table of cells, header row first
+pub fn from_rows(rows: &[Vec<String>], columns: &[Column]) -> Result<Bank> {
+    let Some((header, rows)) = rows.split_first() else {
+        bail!("the sheet is empty");
+    };
+    let find = |name: &str| {
+        header
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name.trim()))
+    };
+    let mut header: Vec<String> = header
+        .iter()
+        .map(|name| name.trim().to_lowercase().replace(' ', "_"))
+        .collect();
+    let mut mapped_options = vec![];
+    for mapping in columns {
+        let Some(i) = find(&mapping.header) else {
+            bail!("no \"{}\" column in the first row", mapping.header);
+        };
+        match mapping.field.as_str() {
+            "option" => mapped_options.push(i),
+            field => header[i] = field.to_string(),
+        }
+    }
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let Some(question_column) = column("question") else {
+        bail!("no \"question\" column in the first row, map one with --column question=HEADER");
+    };
+    let answer_column = column("answer");
+    let option_columns: Vec<usize> = match mapped_options.is_empty() {
+        false => mapped_options,
+        true => header
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| {
+                name.starts_with("option")
+                    || (name.len() == 1 && name.chars().all(|c| c.is_ascii_alphabetic()))
+            })
+            .map(|(i, _)| i)
+            .collect(),
+    };
+    if option_columns.is_empty() {
+        bail!(
+            "no option columns (\"option a\", \"option b\"... or \"A\", \"B\"...) in the first row"
+        );
+    }
+
+    let mut bank = Bank::default();
+    for (i, row) in rows.iter().enumerate() {
+        let cell = |column: usize| row.get(column).map(|cell| cell.trim()).unwrap_or_default();
+        if cell(question_column).is_empty() {
+            continue; // blank lines between sections
+        }
+        let row_number = i + 2; // as the spreadsheet numbers them
+        let options: Vec<String> = option_columns
+            .iter()
+            .map(|&column| cell(column).to_string())
+            .filter(|option| !option.is_empty())
+            .collect();
+        let answer = match answer_column.map(cell).unwrap_or_default() {
+            "" => String::new(),
+            answer => match find_option(&options, answer) {
+                Some(option) => option,
+                None => bail!(
+                    "row {}: the answer '{}' isn't one of the options",
+                    row_number,
+                    answer
+                ),
+            },
+        };
+        let mut question = Map::new();
+        let mut metadata = Map::new();
+        for (column, name) in header.iter().enumerate() {
+            let value = cell(column);
+            if value.is_empty()
+                || column == question_column
+                || Some(column) == answer_column
+                || option_columns.contains(&column)
+            {
+                continue;
+            }
+            match METADATA_COLUMNS.contains(&name.as_str()) {
+                true => metadata.insert(name.clone(), json!(value)),
+                false => question.insert(name.clone(), json!(value)),
+            };
+        }
+        question.insert("question".to_string(), json!(cell(question_column)));
+        question.insert("options".to_string(), json!(options));
+        question.insert("answer".to_string(), json!(answer));
+        question.insert("is_higher_order".to_string(), Value::Null);
+        question.insert("human_answer".to_string(), Value::Null);
+        if !metadata.is_empty() {
+            question.insert("metadata".to_string(), Value::Object(metadata));
+        }
+        let question: Question = serde_json::from_value(Value::Object(question))
+            .wrap_err_with(|| format!("row {}", row_number))?;
+        bank.questions.push(question);
+    }
+    Ok(bank)
+}
+
+// the answer as written by the author: the option itself, its letter or number
+fn find_option(options: &[String], answer: &str) -> Option<String> {
+    if let Some(option) = options.iter().find(|option| option.as_str() == answer) {
+        return Some(option.clone());
+    }
+    let index = match answer.parse::<usize>() {
+        Ok(number) => number.checked_sub(1)?,
+        Err(_) => {
+            let mut chars = answer.chars();
+            match (chars.next(), chars.next()) {
+                (Some(letter), None) if letter.is_ascii_alphabetic() => {
+                    (letter.to_ascii_lowercase() as u8 - b'a') as usize
+                }
+                _ => return None,
+            }
+        }
+    };
+    options.get(index).cloned()
+}
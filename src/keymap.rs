@@ -0,0 +1,228 @@
+use color_eyre::{
+    eyre::{bail, eyre, Report},
+    Result,
+};
+use ratatui::crossterm::event::KeyCode;
+use serde::Deserialize;
+
+/// A single key as written in the config file: a character ("s", "?") or the
+/// name of a special key ("Left", "Enter", "Space", "F1"...).
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Key(pub KeyCode);
+
+impl TryFrom<String> for Key {
+    type Error = Report;
+
+    fn try_from(name: String) -> Result<Key, Report> {
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(Key(KeyCode::Char(c)));
+        }
+        let code = match name.to_lowercase().as_str() {
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "enter" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "space" => KeyCode::Char(' '),
+            other => match other.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                Some(n) => KeyCode::F(n),
+                None => return Err(eyre!("unknown key '{}'", name)),
+            },
+        };
+        Ok(Key(code))
+    }
+}
+
+impl Key {
+    /// How the key is shown in the controls bar, ie. "<s>" or "<Left>"
+    pub fn label(&self) -> String {
+        format!("<{}>", self.name())
+    }
+
+    /// The name as written in the config, ie. "s" or "Left"
+    pub fn name(&self) -> String {
+        match self.0 {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::F(n) => format!("F{}", n),
+            code => format!("{:?}", code),
+        }
+    }
+}
+
+/// Keys for every remappable action, set in the [keymap] table of the config
+/// file. The answer keys (1, 2, 3...) are fixed.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Keymap {
+    pub prev: Key,
+    pub next: Key,
+    pub save: Key,
+    pub quit: Key,
+    #[serde(rename = "true")]
+    pub classify_true: Key,
+    #[serde(rename = "false")]
+    pub classify_false: Key,
+    pub unsure: Key,
+    pub metadata: Key,
+    pub reveal: Key,
+    pub notes: Key,
+    pub bookmark: Key,
+    pub bookmarks: Key, // the jump list
+    pub sort: Key,
+    pub sidebar: Key,
+    pub dimension: Key, // the next axis in dimensions mode
+    pub topic: Key,     // the topic finder in topic mode
+    pub edit: Key,      // the drafted explanation in author mode
+    pub tags: Key,      // the tag finder
+    pub grid: Key,      // the progress grid
+    pub auto_advance: Key,
+    pub clear: Key, // the current question's response
+    pub skip: Key,
+    pub eliminate: Key, // then the option's number
+    pub scroll_up: Key, // the question pane, when it doesn't fit
+    pub scroll_down: Key,
+    // when the file was changed by another program
+    pub reload: Key,
+    pub merge: Key,
+    pub keep: Key,
+}
+
+impl Keymap {
+    /// Refuses two actions on the same key, unless they're never both in use:
+    /// dimension, topic and edit are each for one mode, and reload, merge and
+    /// keep are only asked for when the file changed on disk. One of a mode's
+    /// own can take the sidebar's key, the sidebar is on Shift+Tab in that mode.
+    pub fn check(&self) -> Result<()> {
+        // (name, key, the mode it's only used in)
+        let actions = [
+            ("prev", self.prev, None),
+            ("next", self.next, None),
+            ("save", self.save, None),
+            ("quit", self.quit, None),
+            ("true", self.classify_true, None),
+            ("false", self.classify_false, None),
+            ("unsure", self.unsure, None),
+            ("metadata", self.metadata, None),
+            ("reveal", self.reveal, None),
+            ("notes", self.notes, None),
+            ("bookmark", self.bookmark, None),
+            ("bookmarks", self.bookmarks, None),
+            ("sort", self.sort, None),
+            ("sidebar", self.sidebar, None),
+            ("dimension", self.dimension, Some("dimensions")),
+            ("topic", self.topic, Some("topic")),
+            ("edit", self.edit, Some("author")),
+            ("tags", self.tags, None),
+            ("grid", self.grid, None),
+            ("auto_advance", self.auto_advance, None),
+            ("clear", self.clear, None),
+            ("skip", self.skip, None),
+            ("eliminate", self.eliminate, None),
+            ("scroll_up", self.scroll_up, None),
+            ("scroll_down", self.scroll_down, None),
+        ];
+        for (i, (name, key, mode)) in actions.iter().enumerate() {
+            for (other, other_key, other_mode) in &actions[i + 1..] {
+                let together = match (mode, other_mode) {
+                    (Some(mode), Some(other_mode)) => mode == other_mode,
+                    (None, None) => true,
+                    _ => *name != "sidebar" && *other != "sidebar",
+                };
+                if key == other_key && together {
+                    bail!("{} and {} are both on the {} key", name, other, key.name());
+                }
+            }
+        }
+        let prompt = [
+            ("reload", self.reload),
+            ("merge", self.merge),
+            ("keep", self.keep),
+        ];
+        for (i, (name, key)) in prompt.iter().enumerate() {
+            for (other, other_key) in &prompt[i + 1..] {
+                if key == other_key {
+                    bail!("{} and {} are both on the {} key", name, other, key.name());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        Keymap {
+            prev: Key(KeyCode::Left),
+            next: Key(KeyCode::Right),
+            save: Key(KeyCode::Char('s')),
+            quit: Key(KeyCode::Char('q')),
+            classify_true: Key(KeyCode::Char('t')),
+            classify_false: Key(KeyCode::Char('f')),
+            unsure: Key(KeyCode::Char('u')),
+            metadata: Key(KeyCode::Char('m')),
+            reveal: Key(KeyCode::Char('a')),
+            notes: Key(KeyCode::Char('n')),
+            bookmark: Key(KeyCode::Char('b')),
+            bookmarks: Key(KeyCode::Char('B')),
+            sort: Key(KeyCode::Char('o')),
+            sidebar: Key(KeyCode::Tab),
+            dimension: Key(KeyCode::Tab),
+            topic: Key(KeyCode::Enter),
+            edit: Key(KeyCode::Enter),
+            tags: Key(KeyCode::Char('T')),
+            grid: Key(KeyCode::Char('p')),
+            auto_advance: Key(KeyCode::Char('v')),
+            clear: Key(KeyCode::Backspace),
+            skip: Key(KeyCode::Char('x')),
+            eliminate: Key(KeyCode::Char('e')),
+            scroll_up: Key(KeyCode::PageUp),
+            scroll_down: Key(KeyCode::PageDown),
+            reload: Key(KeyCode::Char('r')),
+            merge: Key(KeyCode::Char('g')),
+            keep: Key(KeyCode::Char('k')),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_keys_are_all_different() {
+        Keymap::default().check().unwrap();
+    }
+
+    #[test]
+    fn keys_used_together_clash() {
+        let keymap = Keymap {
+            dimension: Key(KeyCode::Char('s')),
+            ..Keymap::default()
+        };
+        assert!(keymap.check().is_err());
+        // the sidebar gives up its key in the mode
+        let keymap = Keymap {
+            topic: Key(KeyCode::Tab),
+            ..Keymap::default()
+        };
+        keymap.check().unwrap();
+        // topic and edit are never in the same mode
+        let keymap = Keymap {
+            topic: Key(KeyCode::Char('d')),
+            ..Keymap::default()
+        };
+        keymap.check().unwrap();
+    }
+}
@@ -0,0 +1,212 @@
+use std::io::{self, Write};
+
+use crate::{
+    bank::{Banks, Question},
+    i18n::t,
+};
+
+// `question_cli lint questions.json` looks for common item-writing flaws and
+// prints a checklist for reviewers of each question with any. The checks are
+// simple text rules, they point at what to look at rather than decide: a
+// flagged question can be fine.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Flaw {
+    /// An "all of the above" or "none of the above" option
+    AllOfTheAbove,
+    /// The answer is the longest option, by itself
+    LongestIsKey,
+    /// The stem ends with "a" or "an" and only the answer (and some
+    /// distractors) agree with it
+    Cueing,
+    /// Options with the same text, ignoring case, spacing and punctuation
+    Duplicates,
+    /// Options whose text is in the stem already, word for word
+    InStem,
+    /// Options with "always", "never"... test-wise raters rule those out
+    AbsoluteTerms,
+}
+
+pub const CHECKS: [Flaw; 6] = [
+    Flaw::AllOfTheAbove,
+    Flaw::LongestIsKey,
+    Flaw::Cueing,
+    Flaw::Duplicates,
+    Flaw::InStem,
+    Flaw::AbsoluteTerms,
+];
+
+const ABOVE: [&str; 6] = [
+    "all of the above",
+    "none of the above",
+    "all of these",
+    "none of these",
+    "both of the above",
+    "neither of the above",
+];
+
+const ABSOLUTE: [&str; 7] = [
+    "always",
+    "never",
+    "only",
+    "completely",
+    "absolutely",
+    "entirely",
+    "invariably",
+];
+
+impl Flaw {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Flaw::AllOfTheAbove => t!("lint.all_of_the_above"),
+            Flaw::LongestIsKey => t!("lint.longest"),
+            Flaw::Cueing => t!("lint.cueing"),
+            Flaw::Duplicates => t!("lint.duplicates"),
+            Flaw::InStem => t!("lint.in_stem"),
+            Flaw::AbsoluteTerms => t!("lint.absolute"),
+        }
+    }
+
+    /// The options (indexes) it's found in, empty if the question doesn't
+    /// have the flaw
+    pub fn find(&self, question: &Question) -> Vec<usize> {
+        let options = &question.options;
+        let key = options.iter().position(|o| *o == question.answer);
+        match self {
+            Flaw::AllOfTheAbove => (0..options.len())
+                .filter(|&i| {
+                    let option = normalize(&options[i]);
+                    ABOVE.iter().any(|phrase| option.contains(phrase))
+                })
+                .collect(),
+            Flaw::LongestIsKey => {
+                let Some(key) = key.filter(|_| options.len() > 2) else {
+                    return vec![];
+                };
+                let length = |i: usize| options[i].trim().chars().count();
+                let longest = (0..options.len()).all(|i| i == key || length(i) < length(key));
+                match longest {
+                    true => vec![key],
+                    false => vec![],
+                }
+            }
+            Flaw::Cueing => {
+                let Some(key) = key else {
+                    return vec![];
+                };
+                let stem = question.question.trim_end_matches(|c: char| {
+                    c.is_whitespace() || matches!(c, '_' | '.' | ':' | '…')
+                });
+                let article = stem.rsplit(char::is_whitespace).next().unwrap_or_default();
+                let an = match article.to_lowercase().as_str() {
+                    "a" => false,
+                    "an" => true,
+                    _ => return vec![],
+                };
+                let agrees = |i: usize| starts_with_vowel(&options[i]) == an;
+                // the ones that don't read right after the stem
+                let cued: Vec<usize> = (0..options.len()).filter(|&i| !agrees(i)).collect();
+                match agrees(key) {
+                    true => cued,
+                    false => vec![],
+                }
+            }
+            Flaw::Duplicates => (0..options.len())
+                .filter(|&i| {
+                    let option = normalize(&options[i]);
+                    (0..options.len()).any(|j| j != i && normalize(&options[j]) == option)
+                })
+                .collect(),
+            Flaw::InStem => {
+                // padded so only whole words match
+                let stem = format!(" {} ", normalize(&question.question));
+                (0..options.len())
+                    .filter(|&i| {
+                        let option = normalize(&options[i]);
+                        // "yes", "no"... are bound to be in some stems
+                        option.chars().count() > 3 && stem.contains(&format!(" {} ", option))
+                    })
+                    .collect()
+            }
+            Flaw::AbsoluteTerms => (0..options.len())
+                .filter(|&i| {
+                    let option = options[i].to_lowercase();
+                    let mut words = option.split(|c: char| !c.is_alphabetic());
+                    words.any(|word| ABSOLUTE.contains(&word))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Lowercase words and numbers, single spaced: "Heparin (IV)." => "heparin iv"
+pub fn normalize(text: &str) -> String {
+    let text = text.to_lowercase();
+    let words = text.split(|c: char| !c.is_alphanumeric() && c != '\'');
+    let words: Vec<&str> = words.filter(|word| !word.is_empty()).collect();
+    words.join(" ")
+}
+
+// roughly, "an" before it: an apple, an hour... but a unit
+fn starts_with_vowel(option: &str) -> bool {
+    option
+        .trim_start()
+        .chars()
+        .next()
+        .is_some_and(|c| "aeiouAEIOU".contains(c))
+}
+
+/// Prints the checklist of the questions with a flaw (of all of them with
+/// every), then how many had one. True if any did.
+pub fn print(banks: &Banks, every: bool, out: &mut impl Write) -> io::Result<bool> {
+    let mut flagged = 0;
+    for index in 0..banks.len() {
+        let question = banks.question(index);
+        let found: Vec<(Flaw, Vec<usize>)> = CHECKS
+            .iter()
+            .map(|flaw| (*flaw, flaw.find(question)))
+            .collect();
+        let flawed = found.iter().any(|(_, options)| !options.is_empty());
+        if flawed {
+            flagged += 1;
+        }
+        if !flawed && !every {
+            continue;
+        }
+        let stem = question.question.trim().lines().next().unwrap_or_default();
+        let stem: String = match stem.chars().count() > 70 {
+            true => stem.chars().take(69).chain(['…']).collect(),
+            false => stem.to_string(),
+        };
+        let id = question
+            .id()
+            .map(|id| format!(" ({})", id))
+            .unwrap_or_default();
+        writeln!(
+            out,
+            "{}",
+            t!("lint.question", number = index + 1, id = id, stem = stem)
+        )?;
+        for (flaw, options) in found {
+            match options.is_empty() {
+                true => writeln!(out, "  [ ] {}", flaw.label())?,
+                false => {
+                    let numbers: Vec<String> =
+                        options.iter().map(|i| (i + 1).to_string()).collect();
+                    let options = match numbers.as_slice() {
+                        [number] => t!("lint.option", number = number),
+                        numbers => t!("lint.options", numbers = numbers.join(", ")),
+                    };
+                    writeln!(out, "  [x] {}: {}", flaw.label(), options)?
+                }
+            }
+        }
+        writeln!(out)?;
+    }
+    writeln!(
+        out,
+        "{}",
+        t!("lint.summary", flagged = flagged, total = banks.len())
+    )?;
+    Ok(flagged > 0)
+}
@@ -0,0 +1,299 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use chrono::{SecondsFormat, Utc};
+use color_eyre::{
+    eyre::{bail, eyre, WrapErr},
+    Result,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tungstenite::{error::ProtocolError, stream::MaybeTlsStream, WebSocket};
+
+use crate::{
+    bank::{self, Bank},
+    blinding,
+    config::Config,
+    i18n::t,
+    mode::Mode,
+    scoreboard, tui,
+};
+
+// Live sessions for audience response during teaching: the instructor hosts a
+// bank with `question_cli host cardio.json`, everyone else joins with
+// `question_cli join ws://instructor:9001 --rater R03` and goes through it in
+// the usual interface. Their responses stream back over a WebSocket as they're
+// given and are appended to cardio.live.csv next to the bank:
+//
+// time,rater,question,field,value
+// 2026-01-05T14:03:11Z,R03,3,human_answer,Amiodarone
+//
+// Participants get the bank without the answer key, explanations or images and
+// keep their own copy (cardio.live.json in the current directory), so joining
+// again resumes where they were.
+
+/// What goes over the socket, as JSON text messages
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    /// participant => host, first thing after connecting
+    Join { rater: String },
+    /// host => participant, the bank to go through
+    Bank {
+        name: String,
+        mode: Mode,
+        bank: Value,
+    },
+    /// participant => host, question counts from 1
+    Response {
+        question: usize,
+        field: String,
+        value: Value,
+    },
+}
+
+/// What the host knows of the session, shown on the scoreboard
+#[derive(Default)]
+pub struct Room {
+    pub participants: BTreeMap<String, Participant>,
+    pub message: String, // last thing that went wrong with a connection
+}
+
+#[derive(Default)]
+pub struct Participant {
+    pub connected: bool,
+    pub responses: BTreeMap<usize, String>, // question (from 1) => response
+}
+
+/// Hosts the bank until the instructor quits the scoreboard
+pub fn host(json_path: &Path, mode: Mode, address: &str, config: &Config) -> Result<()> {
+    if !matches!(mode, Mode::Answer | Mode::Classify | Mode::Rate) {
+        bail!("live sessions work in answer, classify and rate modes");
+    }
+    let bank = bank::load_json(json_path)?;
+    let mut shared = Bank::default();
+    shared.instructions = bank.instructions.clone();
+    shared.questions = bank
+        .questions
+        .iter()
+        .map(|question| {
+            let mut question = blinding::strip(question);
+            question.image_path = None; // relative to the instructor's bank
+            question
+        })
+        .collect();
+    let welcome = Message::Bank {
+        name: json_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string(),
+        mode,
+        bank: serde_json::to_value(&shared)?,
+    };
+    let welcome = serde_json::to_string(&welcome)?;
+
+    // hosting the same bank again picks up the responses given so far
+    let csv_path = json_path.with_extension("live.csv");
+    let mut room = Room::default();
+    let new_file = !csv_path.exists();
+    if !new_file {
+        let mut reader = csv::Reader::from_path(&csv_path)
+            .wrap_err_with(|| format!("could not read {}", csv_path.display()))?;
+        for record in reader.deserialize() {
+            let (_, rater, question, field, value): (String, String, usize, String, String) =
+                record.wrap_err_with(|| format!("in {}", csv_path.display()))?;
+            if field == mode.field() {
+                room.participants
+                    .entry(rater)
+                    .or_default()
+                    .responses
+                    .insert(question, value);
+            }
+        }
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&csv_path)
+        .wrap_err_with(|| format!("could not open {}", csv_path.display()))?;
+    let mut writer = csv::Writer::from_writer(file);
+    if new_file {
+        writer.write_record(["time", "rater", "question", "field", "value"])?;
+        writer.flush()?;
+    }
+    let writer = Arc::new(Mutex::new(writer));
+    let room = Arc::new(Mutex::new(room));
+
+    let listener =
+        TcpListener::bind(address).wrap_err_with(|| format!("could not listen on {}", address))?;
+    {
+        let room = room.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                let welcome = welcome.clone();
+                let writer = writer.clone();
+                let room = room.clone();
+                thread::spawn(move || {
+                    // a participant's connection failing doesn't stop the session
+                    if let Err(err) = participant(stream, &welcome, &writer, &room) {
+                        room.lock().unwrap().message = format!("{:#}", err);
+                    }
+                });
+            }
+        });
+    }
+
+    let url = format!("ws://{}", address);
+    let mut terminal = tui::init()?;
+    let result = scoreboard::run(&mut terminal, &bank, mode, &room, &url, config);
+    tui::restore()?;
+    eprintln!("{}", t!("live.saved", path = csv_path.display()));
+    result
+}
+
+// one thread per participant, until they quit
+fn participant(
+    stream: TcpStream,
+    welcome: &str,
+    writer: &Mutex<csv::Writer<File>>,
+    room: &Mutex<Room>,
+) -> Result<()> {
+    let mut socket = tungstenite::accept(stream).map_err(|err| eyre!("{}", err))?;
+    let rater = match receive(&mut socket)? {
+        Some(Message::Join { rater }) => rater,
+        _ => bail!("a participant didn't say who they are"),
+    };
+    room.lock()
+        .unwrap()
+        .participants
+        .entry(rater.clone())
+        .or_default()
+        .connected = true;
+    socket.send(welcome.into())?;
+    let result = responses(&mut socket, &rater, writer, room);
+    if let Some(participant) = room.lock().unwrap().participants.get_mut(&rater) {
+        participant.connected = false;
+    }
+    result
+}
+
+fn responses(
+    socket: &mut WebSocket<TcpStream>,
+    rater: &str,
+    writer: &Mutex<csv::Writer<File>>,
+    room: &Mutex<Room>,
+) -> Result<()> {
+    while let Some(message) = receive(socket)? {
+        if let Message::Response {
+            question,
+            field,
+            value,
+        } = message
+        {
+            let value = match value {
+                Value::String(value) => value,
+                Value::Null => String::new(),
+                value => value.to_string(),
+            };
+            let time = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+            let mut writer = writer.lock().unwrap();
+            writer.write_record([&time, rater, &question.to_string(), &field, &value])?;
+            writer.flush()?;
+            if let Some(participant) = room.lock().unwrap().participants.get_mut(rater) {
+                participant.responses.insert(question, value);
+            }
+        }
+    }
+    Ok(())
+}
+
+// the next message, None once the other side has gone
+fn receive<S: std::io::Read + std::io::Write>(
+    socket: &mut WebSocket<S>,
+) -> Result<Option<Message>> {
+    loop {
+        match socket.read() {
+            Ok(tungstenite::Message::Text(text)) => {
+                return Ok(Some(
+                    serde_json::from_str(&text).wrap_err("invalid live session message")?,
+                ))
+            }
+            Ok(tungstenite::Message::Close(_)) => return Ok(None),
+            Ok(_) => continue, // pings are answered by tungstenite
+            Err(
+                tungstenite::Error::ConnectionClosed
+                | tungstenite::Error::AlreadyClosed
+                | tungstenite::Error::Protocol(ProtocolError::ResetWithoutClosingHandshake),
+            ) => return Ok(None),
+            Err(tungstenite::Error::Io(err))
+                if err.kind() == std::io::ErrorKind::ConnectionReset =>
+            {
+                return Ok(None)
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+/// A participant's connection to the host
+pub struct Client {
+    socket: WebSocket<MaybeTlsStream<TcpStream>>,
+}
+
+impl std::fmt::Debug for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Client")
+    }
+}
+
+impl Client {
+    /// Joins the session at url, returns the local copy of the bank and the mode
+    pub fn join(url: &str, rater: &str) -> Result<(Client, PathBuf, Mode)> {
+        let (mut socket, _) =
+            tungstenite::connect(url).wrap_err_with(|| format!("could not connect to {}", url))?;
+        let join = Message::Join {
+            rater: rater.to_string(),
+        };
+        socket.send(serde_json::to_string(&join)?.into())?;
+        let (name, mode, bank) = match receive(&mut socket)? {
+            Some(Message::Bank { name, mode, bank }) => (name, mode, bank),
+            _ => bail!("{} didn't send a bank", url),
+        };
+        // cardio.json => cardio.live.json, kept when joining again
+        let name = Path::new(&name)
+            .file_name()
+            .unwrap_or("questions.json".as_ref());
+        let local_path = PathBuf::from(name).with_extension("live.json");
+        if !local_path.exists() {
+            fs::write(&local_path, bank::to_pretty(&bank)?)
+                .wrap_err_with(|| format!("could not save {}", local_path.display()))?;
+        }
+        Ok((Client { socket }, local_path, mode))
+    }
+
+    pub fn send(&mut self, question: usize, field: &str, value: Value) -> Result<()> {
+        let response = Message::Response {
+            question,
+            field: field.to_string(),
+            value,
+        };
+        self.socket.send(serde_json::to_string(&response)?.into())?;
+        Ok(())
+    }
+}
+
+// lets the host know the participant left, rather than vanished
+impl Drop for Client {
+    fn drop(&mut self) {
+        let _ = self.socket.close(None);
+        let _ = self.socket.flush();
+    }
+}
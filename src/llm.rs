@@ -0,0 +1,611 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::{self, BufRead, BufReader, IsTerminal},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use clap::ValueEnum;
+use color_eyre::{
+    eyre::{bail, eyre, WrapErr},
+    Result,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::{bank::Question, config, i18n::t, prompts::Prompts};
+
+// Language models for `question_cli review --llm`, `distractors --llm`... Any
+// server speaking OpenAI's chat completions API works: OpenAI by default, or
+// another endpoint (Azure, vLLM, LM Studio...). Set in the config file:
+//
+// [llm]
+// model = "gpt-4o"
+// endpoint = "https://api.openai.com/v1"
+// api_key = "sk-..." # or $OPENAI_API_KEY, for OpenAI's endpoint only
+// embedding_model = "text-embedding-3-small" # for embed, see embeddings.rs
+//
+// or a local Ollama server, for computers without internet access:
+//
+// [llm]
+// provider = "ollama"
+// model = "llama3"
+// endpoint = "http://localhost:11434" # the default
+//
+// Without a model, the one Ollama has installed is used (or the ones to pick
+// from are listed). Its replies are streamed, shown as they're written when
+// asking one question at a time. --provider, --endpoint and --model override
+// the config for a run.
+//
+// Replies are asked for at temperature 0, so runs can be repeated, and kept in
+// a cache (llm_cache/ in the data directory, see config.rs) by the model and a
+// hash of the prompt. Running a command again on an updated bank only asks
+// about the new or changed questions, the others are answered from the cache.
+// cache = false in [llm] asks every time.
+//
+// Long runs are made to survive the API: a request that fails on the network,
+// is rate limited (429) or meets a server error is tried again after 1, 2,
+// 4... seconds (or the Retry-After the server asks for), up to retries times.
+// requests_per_minute spaces the requests out to stay under the account's
+// limit, and concurrency asks about that many questions at once. A run that
+// fails anyway stops there, with every reply so far saved in the bank and the
+// cache, which is the run's progress: started again, it picks up where it
+// stopped.
+//
+// Runs end with the tokens they took, and their cost with the model's prices:
+//
+// [llm.prices]
+// "gpt-4o" = [2.50, 10.00] # per million prompt and completion tokens
+//
+// and each question keeps its own in "llm_usage", see note_usage.
+
+/// The kind of server models are asked on
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Provider {
+    // OpenAI's, or any server speaking its API
+    #[default]
+    Openai,
+    Ollama,
+}
+
+impl Provider {
+    fn default_endpoint(self) -> &'static str {
+        match self {
+            Provider::Openai => "https://api.openai.com/v1",
+            Provider::Ollama => "http://localhost:11434",
+        }
+    }
+}
+
+/// The [llm] table of the config file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub provider: Provider,
+    pub model: Option<String>,
+    pub endpoint: Option<String>,
+    pub api_key: Option<String>,
+    pub cache: bool,
+    pub concurrency: usize,
+    pub requests_per_minute: Option<u32>,
+    pub retries: u32,
+    pub prompts: Prompts,
+    pub prices: HashMap<String, [f64; 2]>, // per million prompt and completion tokens
+    pub embedding_model: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            provider: Provider::Openai,
+            model: None,
+            endpoint: None,
+            api_key: None,
+            cache: true,
+            concurrency: 1,
+            requests_per_minute: None,
+            retries: 5,
+            prompts: Prompts::default(),
+            prices: HashMap::new(),
+            embedding_model: None,
+        }
+    }
+}
+
+impl Settings {
+    /// These settings with another provider, endpoint or model
+    pub fn with(
+        &self,
+        provider: Option<Provider>,
+        endpoint: Option<&String>,
+        model: Option<&String>,
+    ) -> Settings {
+        let provider = provider.unwrap_or(self.provider);
+        Settings {
+            provider,
+            model: model.cloned(),
+            // the config's endpoint is another provider's
+            endpoint: match provider == self.provider {
+                true => endpoint.or(self.endpoint.as_ref()).cloned(),
+                false => endpoint.cloned(),
+            },
+            ..self.clone()
+        }
+    }
+}
+
+/// The tokens a reply took
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Usage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+impl Usage {
+    fn from_json(usage: &Value, prompt: &str, completion: &str) -> Usage {
+        Usage {
+            prompt_tokens: usage[prompt].as_u64().unwrap_or_default(),
+            completion_tokens: usage[completion].as_u64().unwrap_or_default(),
+        }
+    }
+}
+
+/// A model's reply, and the tokens it took when it was asked
+pub struct Reply {
+    pub text: String,
+    pub usage: Usage,
+}
+
+pub struct Client {
+    pub model: String,
+    provider: Provider,
+    endpoint: String,
+    api_key: Option<String>,
+    cache: Option<PathBuf>,
+    pub cached: AtomicUsize, // replies from the cache so far
+    concurrency: usize,
+    interval: Option<Duration>, // between requests, from requests_per_minute
+    next_request: Mutex<Instant>,
+    retries: u32,
+    echo: bool, // show streamed replies as they come
+    price: Option<[f64; 2]>,
+    spent: Mutex<(usize, Usage)>, // requests made and their tokens
+}
+
+impl Client {
+    /// A client for the model of settings, with the cache of the profile's data
+    /// directory
+    pub fn new(settings: &Settings, profile: Option<&str>) -> Result<Client> {
+        let endpoint = settings
+            .endpoint
+            .as_deref()
+            .unwrap_or(settings.provider.default_endpoint())
+            .trim_end_matches('/')
+            .to_string();
+        let model = match (&settings.model, settings.provider) {
+            (Some(model), _) => model.clone(),
+            (None, Provider::Ollama) => ollama_model(&endpoint)?,
+            (None, Provider::Openai) => {
+                bail!("no model to ask, set model in the [llm] table of the config file")
+            }
+        };
+        // $OPENAI_API_KEY only goes to OpenAI, not to Ollama or another server
+        let openai = settings.provider == Provider::Openai
+            && endpoint == Provider::Openai.default_endpoint();
+        let api_key = settings.api_key.clone().or_else(|| {
+            env::var("OPENAI_API_KEY")
+                .ok()
+                .filter(|key| openai && !key.is_empty())
+        });
+        let cache = match settings.cache {
+            true => config::data_dir(profile).map(|dir| dir.join("llm_cache")),
+            false => None,
+        };
+        let concurrency = settings.concurrency.max(1);
+        Ok(Client {
+            provider: settings.provider,
+            endpoint,
+            api_key,
+            cache,
+            cached: AtomicUsize::new(0),
+            concurrency,
+            interval: settings
+                .requests_per_minute
+                .filter(|&rate| rate > 0)
+                .map(|rate| Duration::from_secs(60) / rate),
+            next_request: Mutex::new(Instant::now()),
+            retries: settings.retries,
+            echo: concurrency == 1 && io::stderr().is_terminal(),
+            price: settings.prices.get(&model).copied(),
+            model,
+            spent: Mutex::new((0, Usage::default())),
+        })
+    }
+
+    /// The estimated cost of the tokens, with the model's [llm.prices]
+    pub fn cost(&self, usage: Usage) -> Option<f64> {
+        let [prompt, completion] = self.price?;
+        Some(
+            (usage.prompt_tokens as f64 * prompt + usage.completion_tokens as f64 * completion)
+                / 1e6,
+        )
+    }
+
+    /// The model's reply to prompt, following the system message's
+    /// instructions, from the cache if it's been asked before
+    pub fn complete(&self, system: &str, prompt: &str) -> Result<Reply> {
+        // the model, then the hash of everything it's sent
+        let path = self.cache.as_ref().map(|cache| {
+            let hash = Sha256::digest(format!("{}\0{}", system, prompt));
+            cache
+                .join(self.model.replace(['/', '\\', ':'], "_"))
+                .join(format!("{}.json", hex::encode(hash)))
+        });
+        let cached = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|data| serde_json::from_str::<Value>(&data).ok());
+        if let Some(entry) = cached.filter(|entry| entry["reply"].is_string()) {
+            self.cached.fetch_add(1, Ordering::Relaxed);
+            return Ok(Reply {
+                text: entry["reply"].as_str().unwrap_or_default().to_string(),
+                usage: Usage::from_json(&entry["usage"], "prompt_tokens", "completion_tokens"),
+            });
+        }
+        let reply = self.ask(system, prompt)?;
+        self.spend(reply.usage);
+        if let Some(path) = path {
+            let usage = json!({
+                "prompt_tokens": reply.usage.prompt_tokens,
+                "completion_tokens": reply.usage.completion_tokens,
+            });
+            let entry = json!({
+                "model": self.model,
+                "prompt": prompt,
+                "reply": reply.text,
+                "usage": usage,
+            });
+            // a reply that couldn't be kept is asked for again next time, no harm
+            let _ = fs::create_dir_all(path.parent().unwrap_or(&path))
+                .and_then(|_| fs::write(&path, entry.to_string()));
+        }
+        Ok(reply)
+    }
+
+    /// Asks the model each (question index, prompt), concurrency at a time,
+    /// handing the replies to done as they come in, in any order. Stops at the
+    /// first error.
+    pub fn complete_all(
+        &self,
+        system: &str,
+        prompts: &[(usize, String)],
+        mut done: impl FnMut(usize, Reply) -> Result<()>,
+    ) -> Result<()> {
+        let next = AtomicUsize::new(0);
+        let stop = AtomicBool::new(false);
+        let (sender, receiver) = mpsc::channel();
+        thread::scope(|scope| {
+            for _ in 0..self.concurrency.min(prompts.len()) {
+                let (sender, next, stop) = (sender.clone(), &next, &stop);
+                scope.spawn(move || {
+                    while !stop.load(Ordering::Relaxed) {
+                        let Some((index, prompt)) =
+                            prompts.get(next.fetch_add(1, Ordering::Relaxed))
+                        else {
+                            break;
+                        };
+                        let reply = self.complete(system, prompt);
+                        if sender.send((*index, reply)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(sender);
+            for (index, reply) in receiver {
+                let result = reply
+                    .wrap_err_with(|| format!("on question {}", index + 1))
+                    .and_then(|reply| done(index, reply));
+                if let Err(error) = result {
+                    // the others finish the request they're on, and that's it
+                    stop.store(true, Ordering::Relaxed);
+                    return Err(error.wrap_err("stopped, run it again to pick up from here"));
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// The embedding vectors of the texts, in order
+    pub fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let body = json!({"model": self.model, "input": texts});
+        let response = match self.provider {
+            Provider::Openai => self.post("embeddings", body)?,
+            Provider::Ollama => self.post("api/embed", body)?,
+        };
+        let response: Value = serde_json::from_str(&response.into_string()?)
+            .wrap_err_with(|| format!("unexpected reply from {}", self.endpoint))?;
+        let (vectors, usage) = match self.provider {
+            Provider::Openai => (
+                response["data"]
+                    .as_array()
+                    .map(|data| data.iter().map(|item| &item["embedding"]).collect()),
+                Usage::from_json(&response["usage"], "prompt_tokens", "completion_tokens"),
+            ),
+            Provider::Ollama => (
+                response["embeddings"]
+                    .as_array()
+                    .map(|vectors| vectors.iter().collect::<Vec<_>>()),
+                Usage::from_json(&response, "prompt_eval_count", "eval_count"),
+            ),
+        };
+        self.spend(usage);
+        let vectors: Option<Vec<Vec<f32>>> = vectors
+            .unwrap_or_default()
+            .into_iter()
+            .map(|vector| {
+                vector
+                    .as_array()?
+                    .iter()
+                    .map(|x| x.as_f64().map(|x| x as f32))
+                    .collect()
+            })
+            .collect();
+        match vectors {
+            Some(vectors) if vectors.len() == texts.len() => Ok(vectors),
+            _ => bail!("no embeddings from {} at {}", self.model, self.endpoint),
+        }
+    }
+
+    // counts a request made and its tokens
+    fn spend(&self, usage: Usage) {
+        let mut spent = self.spent.lock().unwrap_or_else(|e| e.into_inner());
+        spent.0 += 1;
+        spent.1.prompt_tokens += usage.prompt_tokens;
+        spent.1.completion_tokens += usage.completion_tokens;
+    }
+
+    fn ask(&self, system: &str, prompt: &str) -> Result<Reply> {
+        let messages = json!([
+            {"role": "system", "content": system},
+            {"role": "user", "content": prompt},
+        ]);
+        let response = match self.provider {
+            Provider::Openai => self.post(
+                "chat/completions",
+                json!({"model": self.model, "temperature": 0, "messages": messages}),
+            )?,
+            Provider::Ollama => self.post(
+                "api/chat",
+                json!({
+                    "model": self.model,
+                    "messages": messages,
+                    "stream": true,
+                    "options": {"temperature": 0},
+                }),
+            )?,
+        };
+        if self.provider == Provider::Ollama {
+            return self.read_stream(response);
+        }
+        let response = response.into_string()?;
+        let response: Value = serde_json::from_str(&response)
+            .wrap_err_with(|| format!("unexpected reply from {}", self.endpoint))?;
+        let text = response["choices"][0]["message"]["content"]
+            .as_str()
+            .ok_or_else(|| eyre!("no reply from {} in {}", self.model, response))?;
+        Ok(Reply {
+            text: text.trim().to_string(),
+            usage: Usage::from_json(&response["usage"], "prompt_tokens", "completion_tokens"),
+        })
+    }
+
+    // sends the request, again after a while when it fails on the way or on
+    // the server's side
+    fn post(&self, path: &str, body: Value) -> Result<ureq::Response> {
+        let url = format!("{}/{}", self.endpoint, path);
+        let body = body.to_string();
+        let mut attempt = 0;
+        let response = loop {
+            self.throttle();
+            let mut request = ureq::post(&url).set("Content-Type", "application/json");
+            if let Some(api_key) = &self.api_key {
+                request = request.set("Authorization", &format!("Bearer {}", api_key));
+            }
+            let (wait, failure) = match request.send_string(&body) {
+                Ok(response) => break response,
+                Err(ureq::Error::Status(code, response))
+                    if code == 408 || code == 429 || code >= 500 =>
+                {
+                    let wait = response
+                        .header("Retry-After")
+                        .and_then(|seconds| seconds.trim().parse().ok())
+                        .map(Duration::from_secs);
+                    (wait, format!("status code {}", code))
+                }
+                Err(ureq::Error::Transport(error)) => (None, error.to_string()),
+                Err(ureq::Error::Status(code, response)) => {
+                    // both say what's wrong in "error", OpenAI in its "message"
+                    let text = response.into_string().unwrap_or_default();
+                    let error = serde_json::from_str::<Value>(&text)
+                        .ok()
+                        .and_then(|reply| match &reply["error"] {
+                            Value::String(error) => Some(error.clone()),
+                            error => error["message"].as_str().map(str::to_string),
+                        })
+                        .unwrap_or(text);
+                    bail!(
+                        "could not ask {} at {}, status code {}: {}",
+                        self.model,
+                        self.endpoint,
+                        code,
+                        error.trim()
+                    );
+                }
+            };
+            if attempt >= self.retries {
+                bail!(
+                    "could not ask {} at {} in {} tries: {}",
+                    self.model,
+                    self.endpoint,
+                    attempt + 1,
+                    failure
+                );
+            }
+            thread::sleep(wait.unwrap_or(Duration::from_secs(1 << attempt.min(6))));
+            attempt += 1;
+        };
+        Ok(response)
+    }
+
+    // Ollama's reply, a JSON object per line with the next bit of text
+    fn read_stream(&self, response: ureq::Response) -> Result<Reply> {
+        let mut reply = String::new();
+        let mut usage = Usage::default();
+        for line in BufReader::new(response.into_reader()).lines() {
+            let line = line.wrap_err_with(|| format!("{} stopped replying", self.model))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let chunk: Value = serde_json::from_str(&line)
+                .wrap_err_with(|| format!("unexpected reply from {}", self.endpoint))?;
+            if let Some(error) = chunk["error"].as_str() {
+                bail!("{} at {}: {}", self.model, self.endpoint, error);
+            }
+            let text = chunk["message"]["content"].as_str().unwrap_or_default();
+            if self.echo {
+                eprint!("{}", text);
+            }
+            reply.push_str(text);
+            if chunk["done"].as_bool() == Some(true) {
+                // the counts come with the last one
+                usage = Usage::from_json(&chunk, "prompt_eval_count", "eval_count");
+                break;
+            }
+        }
+        if self.echo {
+            eprintln!();
+        }
+        Ok(Reply {
+            text: reply.trim().to_string(),
+            usage,
+        })
+    }
+
+    // waits for the next request's turn under requests_per_minute
+    fn throttle(&self) {
+        let Some(interval) = self.interval else {
+            return;
+        };
+        let now = Instant::now();
+        let start = {
+            let mut next = self.next_request.lock().unwrap_or_else(|e| e.into_inner());
+            let start = (*next).max(now);
+            *next = start + interval;
+            start
+        };
+        thread::sleep(start - now);
+    }
+}
+
+// the model installed in Ollama when there's just the one
+fn ollama_model(endpoint: &str) -> Result<String> {
+    let tags = ureq::get(&format!("{}/api/tags", endpoint))
+        .call()
+        .wrap_err_with(|| format!("could not reach Ollama at {}", endpoint))?
+        .into_string()?;
+    let tags: Value = serde_json::from_str(&tags)
+        .wrap_err_with(|| format!("unexpected reply from {}", endpoint))?;
+    let models: Vec<&str> = tags["models"]
+        .as_array()
+        .map_or(&[][..], Vec::as_slice)
+        .iter()
+        .filter_map(|model| model["name"].as_str())
+        .collect();
+    match models.as_slice() {
+        [model] => Ok(model.to_string()),
+        [] => bail!(
+            "no models in Ollama at {}, pull one first (ollama pull llama3)",
+            endpoint
+        ),
+        _ => bail!(
+            "pick a model with --model or model in [llm] of the config file: {}",
+            models.join(", ")
+        ),
+    }
+}
+
+/// Saves the tokens asking about the question took for the task, and their
+/// cost, in its "llm_usage" (one entry by task and model)
+pub fn note_usage(question: &mut Question, task: &str, client: &Client, usage: Usage) {
+    let mut entry = json!({
+        "task": task,
+        "model": client.model,
+        "prompt_tokens": usage.prompt_tokens,
+        "completion_tokens": usage.completion_tokens,
+    });
+    if let Some(cost) = client.cost(usage) {
+        entry["cost"] = json!(cost);
+    }
+    let usages = question
+        .extra
+        .entry("llm_usage".to_string())
+        .or_insert_with(|| json!([]));
+    if !usages.is_array() {
+        *usages = json!([]);
+    }
+    let Some(usages) = usages.as_array_mut() else {
+        return;
+    };
+    usages.retain(|usage| usage["task"] != task || usage["model"] != client.model.as_str());
+    usages.push(entry);
+}
+
+/// Says what a run took: the requests made, their tokens and cost, and the
+/// replies that came from the cache
+pub fn report(clients: &[Client]) {
+    for client in clients {
+        let (requests, usage) = *client.spent.lock().unwrap_or_else(|e| e.into_inner());
+        let mut summary = t!(
+            "llm.usage",
+            model = client.model,
+            requests = requests,
+            prompt = usage.prompt_tokens,
+            completion = usage.completion_tokens
+        );
+        if let Some(cost) = client.cost(usage) {
+            summary.push_str(&t!("llm.cost", cost = format!("{:.4}", cost)));
+        }
+        eprintln!("{}", summary);
+    }
+    let cached: usize = clients
+        .iter()
+        .map(|client| client.cached.load(Ordering::Relaxed))
+        .sum();
+    if cached > 0 {
+        eprintln!("{}", t!("llm.cached", count = cached));
+    }
+}
+
+/// The question as sent to a model: the stem, then the options numbered from 1
+pub fn item(question: &Question) -> String {
+    format!("{}\n\n{}", question.question.trim(), options(question))
+}
+
+/// The options numbered from 1, a line each
+pub fn options(question: &Question) -> String {
+    question
+        .options
+        .iter()
+        .enumerate()
+        .map(|(i, option)| format!("{}. {}\n", i + 1, option))
+        .collect()
+}
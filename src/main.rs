@@ -1,9 +1,12 @@
 #![warn(unused_extern_crates)]
 use chrono::prelude::*;
-use clap::Parser;
-use color_eyre::{eyre::WrapErr, Result};
+use clap::{CommandFactory, Parser, ValueEnum};
+use color_eyre::{
+    eyre::{bail, OptionExt, WrapErr},
+    Result,
+};
 use ratatui::{
-    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    crossterm::event::KeyCode,
     layout::Alignment,
     prelude::*,
     style::Stylize,
@@ -11,220 +14,348 @@ use ratatui::{
     widgets::{block::Title, Block, Borders, LineGauge, Paragraph},
     Frame,
 };
-use serde::{Deserialize, Serialize};
-use std::fs;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::io::Write;
+use std::path::PathBuf;
 use std::process;
+use std::time::Instant;
+
+use bank::Banks;
+use cli::{Cli, Command};
+use i18n::t;
+use mode::{Mode, QStatus};
+use update::Message;
 
+mod audit;
+mod bank;
+mod batch;
+mod blinding;
+mod bookmarks;
+mod cli;
+mod clusters;
+mod compare;
+mod config;
+mod counterbalance;
+mod crypt;
+mod dedupe;
+mod dimensions;
+mod distractors;
+mod drafts;
+mod embeddings;
+mod equating;
 mod errors;
+mod events;
+mod export;
+mod google;
+mod grade;
+mod graphics;
+mod grid;
+mod history;
+mod hooks;
+mod i18n;
+mod import;
+mod keymap;
+mod lint;
+mod live;
+mod llm;
+mod manifest;
+mod markdown;
+mod math;
+mod mode;
+mod notes;
+mod paper;
+mod plots;
+mod plugins;
+mod popup;
+mod proctor;
+mod prompts;
+mod proposals;
+mod rasch;
+mod readability;
+mod recent;
+mod reliability;
+mod remote;
+mod review;
+mod sample;
+mod scoreboard;
+mod shared;
+mod sidebar;
+mod signing;
+mod sort;
+mod spelling;
+mod state;
+mod stats;
+mod tags;
+mod taxonomy;
+mod text;
+mod theme;
+mod timing;
 mod tui;
-
-// Questions to be extracted from .json file
-#[derive(Serialize, Deserialize, Debug)]
-struct Question {
-    question: String,
-    options: Vec<String>,
-    answer: String,                // should be verbatim one of the options in options
-    is_higher_order: Option<bool>, // not always in .json file
-    human_answer: Option<String>,  // not always in .json file
-}
-
-type Questions = Vec<Question>;
-
-// Cli app can either classify or answer the questions from the .json
-#[derive(Debug, Default, PartialEq)]
-enum Mode {
-    Classify,
-    #[default]
-    Answer,
-}
-
-// Command line arguements required
-#[derive(Parser)]
-#[command(version, about)]
-struct Cli {
-    // Either "classify" or "answer"
-    mode: String,
-
-    // PATH to the .json file
-    json_path: std::path::PathBuf,
-}
+mod update;
+mod web;
 
 // For state control in App
 #[derive(Debug, Default)]
 pub struct App {
-    json_path: std::path::PathBuf,
-    questions: Questions,
+    banks: Banks,
     question_index: usize,
     mode: Mode,
     message: String,
     exit: bool,
     num_answered: usize,
-}
-
-// Question state options
-enum QStatus {
-    MissingClassification(Span<'static>),
-    MissingAnswer(Span<'static>),
-    Classification(Span<'static>),
-    Answer(Span<'static>),
-}
-
-impl QStatus {
-    // Method to extract the inner Span<'static>
-    fn get_span(&self) -> &Span<'static> {
-        match self {
-            QStatus::MissingClassification(span)
-            | QStatus::MissingAnswer(span)
-            | QStatus::Classification(span)
-            | QStatus::Answer(span) => span,
-        }
-    }
+    graphics: graphics::ImageView,
+    show_metadata: bool, // metadata section of the right panel is expanded
+    show_sidebar: bool,  // question index on the left
+    auto_advance: bool,  // answering moves on to the next question still missing
+    dimension: usize,    // the axis being classified on in dimensions mode
+    pending_change: Option<(usize, KeyCode)>, // confirm_changes, pressed once on that question
+    scroll: u16,         // rows of the question pane scrolled out of view
+    shown: Option<(usize, Instant)>, // the question on screen and when it was first drawn
+    max_scroll: u16,     // how far it can scroll, from the last frame drawn
+    page: u16,           // the pane's height, the same
+    eliminating: bool,   // the eliminate key was pressed, waiting for the option's number
+    eliminated: BTreeMap<usize, BTreeSet<usize>>, // options struck out, by question
+    revealed: bool,      // answer and explanation of the current question shown
+    blinded: bool,       // --blinded, nothing can be revealed
+    notes: notes::Notes,
+    note_editor: Option<String>, // the note being written for the current question
+    draft_editor: Option<String>, // the drafted explanation being edited in author mode
+    bookmarks: bookmarks::Bookmarks,
+    jump_list: Option<usize>, // open, with the selected bookmark
+    order: sort::Order,
+    sort_menu: Option<usize>, // open, with the selected order
+    grid: Option<usize>,      // open, with the highlighted question
+    topic_finder: Option<popup::Finder>,
+    tag_finder: Option<popup::Finder>,
+    config: config::Config,
+    dirty: bool,                           // unsaved changes, for autosave
+    conflict: Option<std::path::PathBuf>,  // file changed on disk, waiting for reload/merge/keep
+    patch_out: Option<std::path::PathBuf>, // --patch-out, rewritten on every save
+    events: Option<events::EventLog>,      // --event-log
+    hooks: hooks::Hooks,                   // [[hooks]] in the config
+    timer: Option<timing::Timer>,          // track_time
+    proctor_code: Option<String>,          // --proctored, needed to quit before the end
+    quit_prompt: Option<String>,           // the code typed so far
+    live: Option<live::Client>,            // joined a live session, responses are sent to the host
+    last_save: DateTime<Utc>,
 }
 
 impl App {
     fn new(
-        json_path: std::path::PathBuf,
-        questions: Questions,
+        banks: Banks,
         question_index: usize,
         mode: Mode,
         message: String,
-        exit: bool,
         num_answered: usize,
+        config: config::Config,
     ) -> App {
         App {
-            json_path,
-            questions,
+            banks,
             question_index,
             mode,
             message,
-            exit,
+            exit: false,
             num_answered,
+            graphics: graphics::ImageView::new(),
+            show_metadata: false,
+            show_sidebar: false,
+            auto_advance: config.auto_advance,
+            dimension: 0,
+            pending_change: None,
+            scroll: 0,
+            shown: None,
+            max_scroll: 0,
+            page: 0,
+            eliminating: false,
+            eliminated: BTreeMap::new(),
+            revealed: false,
+            blinded: false,
+            notes: notes::Notes::default(),
+            note_editor: None,
+            draft_editor: None,
+            bookmarks: bookmarks::Bookmarks::new(),
+            jump_list: None,
+            order: sort::Order::Original,
+            sort_menu: None,
+            grid: None,
+            topic_finder: None,
+            tag_finder: None,
+            config,
+            dirty: false,
+            conflict: None,
+            patch_out: None,
+            events: None,
+            hooks: hooks::Hooks::default(),
+            timer: None,
+            proctor_code: None,
+            quit_prompt: None,
+            live: None,
+            last_save: Utc::now(),
         }
     }
 
     /// runs the application's main loop until the user quits
     pub fn run(&mut self, terminal: &mut tui::Tui) -> Result<()> {
         while !self.exit {
-            terminal.draw(|frame| self.ui(frame))?;
-            self.handle_events()?;
+            if tui::resume()? {
+                terminal.clear()?;
+            }
+            let image_path = self.image_path();
+            if self.graphics.needs_clear(image_path.as_deref()) {
+                terminal.clear()?;
+            }
+            let (mut image_area, mut drawn) = (None, Message::Tick);
+            terminal.draw(|frame| (image_area, drawn) = self.ui(frame))?;
+            self.update(drawn)?;
+            // images are drawn over the finished frame, failing to show one shouldn't end the session
+            if let Err(err) = self.graphics.draw(image_path.as_deref(), image_area) {
+                self.update(Message::ImageFailed(err.to_string()))?;
+            }
+            self.update(update::read()?)?;
         }
         Ok(())
     }
 
-    // UI layout, Called by run().
-    fn ui(&self, frame: &mut Frame) {
+    // image of the current question
+    fn image_path(&self) -> Option<std::path::PathBuf> {
+        self.banks.image_path(self.question_index)
+    }
+
+    // UI layout, Called by run(). It only reads the state, what it finds out
+    // while drawing goes back through update.
+    // Returns the area reserved for the question's image, if it has one, and
+    // the Drawn message.
+    fn ui(&self, frame: &mut Frame) -> (Option<Rect>, Message) {
         // Get texts
 
-        let current_q = &self.questions[self.question_index];
+        let current_q = self.banks.question(self.question_index);
+        let palette = self.config.theme.palette();
+        let keymap = &self.config.keymap;
 
         let controls = {
             let mut i_vec = vec![
-                " Prev".into(),
-                "<Left>".blue().bold(),
-                " Next".into(),
-                "<Right>".blue().bold(),
-                " Save".into(),
-                "<s>".blue().bold(),
-                " Quit ".into(),
-                "<q> ".red().bold(),
+                t!("controls.prev").into(),
+                keymap.prev.label().fg(palette.key).bold(),
+                t!("controls.next").into(),
+                keymap.next.label().fg(palette.key).bold(),
+                t!("controls.save").into(),
+                keymap.save.label().fg(palette.key).bold(),
+                t!("controls.quit").into(),
+                format!("{} ", keymap.quit.label())
+                    .fg(palette.quit_key)
+                    .bold(),
             ];
 
             // specific controls based on mode
-            i_vec.splice(0..0, {
-                match self.mode {
-                    Mode::Classify => vec![
-                        " True".into(),
-                        "<t>".cyan().bold(),
-                        " False".into(),
-                        "<f>".cyan().bold(),
+            let options = current_q.shown_options().len();
+            i_vec.splice(0..0, self.mode.controls(keymap, options, &palette));
+            if self.can_reveal() {
+                i_vec.splice(
+                    i_vec.len() - 2..i_vec.len() - 2,
+                    [
+                        t!("controls.reveal").into(),
+                        keymap.reveal.label().fg(palette.key).bold(),
                     ],
-                    Mode::Answer => vec![" Enter answer ".into(), "<1, 2, 3, 4, 5>".cyan().bold()],
-                }
-            });
+                );
+            }
             Title::from(Line::from(i_vec))
         };
 
         let question_index_text = Title::from(Line::from(vec![
-            " Question ".into(),
-            (self.question_index + 1).to_string().cyan(),
-            " of ".into(),
-            self.questions.len().to_string().cyan(),
-            " ".into(),
+            t!("question.label").into(),
+            (self.question_index + 1).to_string().fg(palette.action_key),
+            t!("question.of").into(),
+            self.banks.len().to_string().fg(palette.action_key),
+            match self.bookmarks.contains(&self.question_index) {
+                true => " ★ ".fg(palette.action_key),
+                false => " ".into(),
+            },
         ]));
 
         // For paragraphs, to have separate lines you cannot use "\n". You must construct out of separate Line structs.
-        let mut q_text: Vec<Line<'_>> = vec![Line::from(current_q.question.clone())];
-        q_text.push(Line::from("")); // this is \n
-        let human_answer = current_q.human_answer.clone().unwrap_or("".to_string());
-        q_text.extend(
-            current_q
-                .options
-                .iter()
-                .enumerate()
-                .map(|(i, text)| {
-                    let letter_array = ["1", "2", "3", "4", "5", "6", "7"];
-                    if text == &human_answer && self.mode == Mode::Answer {
-                        Line::from(
-                            format!("{}\n", letter_array[i].to_string() + " - " + text)
-                                .green()
-                                .bold()
-                                .underlined(),
-                        )
-                    } else {
-                        Line::from(
-                            format!("{}\n", letter_array[i].to_string() + " - " + text).yellow(),
-                        )
-                    }
-                })
-                .collect::<Vec<Line>>(), // have to collect everything of any type apparently
-        );
+        // Stems and options are written in Markdown, so they go through the markdown module.
+        // The stem may contain tables, so it is kept as segments, followed by the options and
+        // the status in q_text.
+        let mut stem = markdown::to_segments(&current_q.question, Style::default());
+        stem.push(markdown::Segment::Text(vec![Line::from("")])); // this is \n
+        let eliminated = self.eliminated();
+        // with --distractors only some of the options are shown, numbered in order
+        for (n, i) in current_q.shown_options().into_iter().enumerate() {
+            let text = &current_q.options[i];
+            let mut style = self.mode.option_style(current_q, text, &palette);
+            if eliminated.contains(&i) {
+                style = style.dim().crossed_out();
+            }
+            // long options wrap under their text, not under the number
+            let number = Span::styled(format!("{} - ", n + 1), style);
+            stem.push(markdown::Segment::Hanging(
+                number,
+                markdown::to_lines(text, style),
+            ));
+        }
+        let mut q_text: Vec<Line<'_>> = vec![];
 
         // is the question answered or has it already been classified?
         // need to display a big MESSAGE to user if it still needs an action
         // will append the message to the question text box
-        let q_status = match self.mode {
-            Mode::Classify => {
-                if let Some(is_higher_order) = current_q.is_higher_order {
-                    QStatus::Classification(
-                        format!(
-                            "Current classification, is higher order: {}",
-                            is_higher_order
-                        )
-                        .blue(),
-                    )
-                } else {
-                    QStatus::MissingClassification(
-                        "MISSING CLASSIFICATION".to_string().red().bold(),
-                    )
-                }
-            }
-            Mode::Answer => {
-                if let Some(_answer) = &current_q.human_answer {
-                    QStatus::Answer("".blue())
-                } else {
-                    QStatus::MissingAnswer("MISSING ANSWER".to_string().red().bold())
-                }
-            }
-        };
+        let q_status = self.mode.status(&self.banks, self.question_index, &palette);
         q_text.push(Line::from(""));
         q_text.push(Line::from(q_status.get_span().clone()));
+        // the end of a quiz, against the pass mark
+        if let (Mode::Quiz | Mode::Learn, Some(pass_mark)) = (self.mode, self.config.pass_mark) {
+            let questions = self.banks.questions();
+            if questions.clone().all(|q| q.human_answer.is_some()) {
+                let correct = questions.clone().filter(|q| !q.is_disputed());
+                let verdict = grade::Verdict::of(
+                    grade::weights(correct),
+                    grade::weights(questions),
+                    pass_mark,
+                );
+                let color = match verdict.passed {
+                    true => palette.selected,
+                    false => palette.missing,
+                };
+                q_text.push(Line::from(verdict.describe().fg(color).bold()));
+            }
+        }
 
         // for the right box of the screen, depends on mode
-        let instructions = Text::from(match self.mode {
-            Mode::Classify => vec![
-                Line::from("Is this a higher order question? True <t> or False <f>?".bold()),
-                Line::from(""),
-                Line::from("Higher order question: involves application, analyzing, evaluating."),
-                Line::from(
-                    "Lower order question: involves basic understanding and rote memorization.",
-                ),
-            ],
-            Mode::Answer => vec![
-                Line::from("What is the correct answer?".bold()),
-                Line::from(""),
-                Line::from("Type 1, 2, 3, 4, or 5 to select an answer."),
-            ],
-        });
+        // the bank can ship its own instructions (Markdown) for each mode
+        let (_, bank) = self.banks.source(self.question_index);
+        // learn mode swaps them for the explanation once the question is answered
+        let instructions = match bank.instructions.get(self.mode.name()) {
+            _ if self.revealed => {
+                let answer = current_q
+                    .shown_options()
+                    .into_iter()
+                    .position(|i| current_q.options[i] == current_q.answer)
+                    .map(|i| format!("{} - {}", i + 1, current_q.answer))
+                    .unwrap_or(current_q.answer.clone());
+                let mut lines = vec![
+                    Line::from(t!("explanation.answer").bold()),
+                    Line::from(answer.fg(palette.selected)),
+                    Line::from(""),
+                ];
+                lines.extend(mode::explanation(current_q));
+                lines
+            }
+            _ if self.mode == Mode::Learn && current_q.human_answer.is_some() => {
+                mode::explanation(current_q)
+            }
+            _ if self.mode == Mode::Dimensions => {
+                dimensions::panel(current_q, self.dimension, &palette)
+            }
+            _ if self.mode == Mode::Review => review::panel(current_q),
+            _ if self.mode == Mode::Distractors => proposals::panel(current_q, &palette),
+            _ if self.mode == Mode::Author => {
+                drafts::panel(current_q, self.draft_editor.as_deref())
+            }
+            _ if self.mode == Mode::Plugin => plugins::panel(current_q, &palette),
+            Some(custom) => markdown::to_lines(custom, Style::default()),
+            None => self.mode.instructions(),
+        };
 
         // main layout setup
         let outer_layout = Layout::default()
@@ -235,11 +366,29 @@ impl App {
                 Constraint::Length(2),
             ])
             .split(frame.size());
-        // for question and instructions
+        // for the question index, question and instructions
+        let sidebar_width = match self.show_sidebar {
+            true => sidebar::WIDTH,
+            false => 0,
+        };
+        let [sidebar_area, main_area] =
+            Layout::horizontal([Constraint::Length(sidebar_width), Constraint::Min(0)])
+                .areas(outer_layout[1]);
         let inner_layout = Layout::default()
             .direction(Direction::Horizontal)
             .constraints(vec![Constraint::Percentage(60), Constraint::Percentage(40)])
-            .split(outer_layout[1]);
+            .split(main_area);
+        if self.show_sidebar {
+            sidebar::render(
+                frame,
+                sidebar_area,
+                &self.banks,
+                self.mode,
+                self.question_index,
+                &self.bookmarks,
+                &palette,
+            );
+        }
 
         // add txt to layout
 
@@ -247,43 +396,191 @@ impl App {
         // this will run whenever the progress is saved and display the time and confirmation of saving
         frame.render_widget(
             Paragraph::default().alignment(Alignment::Center).block(
-                Block::new().title(Title::from(self.message.clone()).alignment(Alignment::Right)),
+                Block::new()
+                    .title(Title::from(self.top_message(&palette)).alignment(Alignment::Right)),
             ),
             outer_layout[0],
         );
 
         // add question text and current question status
         // goes in the left middle box
-        frame.render_widget(
-            Paragraph::new(Text::from(q_text))
-                .wrap(ratatui::widgets::Wrap { trim: true })
-                .block(
-                    Block::new()
-                        .borders(Borders::TOP | Borders::RIGHT) // add borders for style
-                        .title(question_index_text.alignment(Alignment::Left)) // add question index in top left border
-                        .title(
-                            Title::from(match q_status {
-                                QStatus::MissingClassification(span)
-                                | QStatus::MissingAnswer(span) => Line::from(span),
-                                _ => Line::from(""),
-                            })
-                            .alignment(Alignment::Center),
-                        ) // add ACTION call to user in top middle border PRN
-                        .padding(ratatui::widgets::Padding::new(1, 1, 1, 1)),
+        let question_block = Block::new()
+            .borders(Borders::TOP | Borders::RIGHT) // add borders for style
+            .title(question_index_text.alignment(Alignment::Left)) // add question index in top left border
+            .title(
+                Title::from(match q_status {
+                    QStatus::Missing(span) => Line::from(span),
+                    QStatus::Done(_) => Line::from(""),
+                })
+                .alignment(Alignment::Center),
+            ) // add ACTION call to user in top middle border PRN
+            .padding(ratatui::widgets::Padding::new(1, 1, 1, 1));
+        let question_area = question_block.inner(inner_layout[0]);
+        frame.render_widget(question_block, inner_layout[0]);
+        stem.push(markdown::Segment::Text(q_text));
+        let max_scroll = markdown::render_segments(frame, question_area, stem, self.scroll);
+        // the padding rows above and below show there's more to scroll to
+        let scroll = self.scroll.min(max_scroll);
+        for (more, y, arrow, key) in [
+            (
+                scroll > 0,
+                question_area.y.saturating_sub(1),
+                "▲",
+                keymap.scroll_up,
+            ),
+            (
+                scroll < max_scroll,
+                question_area.bottom(),
+                "▼",
+                keymap.scroll_down,
+            ),
+        ] {
+            if more {
+                let hint = Line::from(format!("{} {}", arrow, key.label()).dim());
+                let area = Rect::new(question_area.x, y, question_area.width, 1);
+                frame.render_widget(hint.alignment(Alignment::Right), area);
+            }
+        }
+        let drawn = Message::Drawn {
+            page: question_area.height,
+            max_scroll,
+        };
+        // collapsible metadata section (topic, objective, reference) under the instructions
+        let metadata_lines = current_q.metadata.as_ref().map(|metadata| {
+            let mut lines = vec![];
+            for (label, value) in [
+                (t!("metadata.topic"), &metadata.topic),
+                (
+                    t!("metadata.learning_objective"),
+                    &metadata.learning_objective,
                 ),
-            inner_layout[0],
-        );
-        // add instructions
-        frame.render_widget(
-            Paragraph::new(instructions)
+                (t!("metadata.reference"), &metadata.reference),
+            ] {
+                if let Some(value) = value {
+                    lines.push(Line::from(label.bold()));
+                    lines.extend(markdown::to_lines(value, Style::default()));
+                }
+            }
+            // left border + padding on both sides
+            text::wrap(lines, inner_layout[1].width.saturating_sub(3))
+        });
+        // personal notes, shown when the question has one or one is being written
+        let note = match &self.note_editor {
+            Some(text) => Some(format!("{}▏", text)),
+            None => {
+                let (json_path, id) = self.banks.question_id(self.question_index);
+                self.notes.get(json_path, &id).map(str::to_string)
+            }
+        };
+        let note_lines = note.map(|note| {
+            let lines = note
+                .split('\n')
+                .map(|line| Line::from(line.to_string()))
+                .collect();
+            text::wrap(lines, inner_layout[1].width.saturating_sub(3))
+        });
+        let notes_height = note_lines
+            .as_ref()
+            .map_or(0, |lines| lines.len() as u16 + 1);
+        let metadata_height = match &metadata_lines {
+            Some(lines) if self.show_metadata => lines.len() as u16 + 1, // + top border
+            Some(_) => 1,
+            None => 0,
+        };
+        // questions with an image get the bottom of the right box for it
+        let right_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Min(6),
+                Constraint::Length(notes_height),
+                Constraint::Length(metadata_height),
+                match current_q.image_path {
+                    Some(_) => Constraint::Percentage(60),
+                    None => Constraint::Length(0),
+                },
+            ])
+            .split(inner_layout[1]);
+        // add instructions, or the two models' answers side by side
+        let panel = |lines| {
+            Paragraph::new(Text::from(lines))
                 .block(
                     Block::new()
                         .borders(Borders::TOP | Borders::LEFT)
                         .padding(ratatui::widgets::Padding::new(1, 1, 1, 1)),
                 )
-                .wrap(ratatui::widgets::Wrap { trim: true }),
-            inner_layout[1],
-        );
+                .wrap(ratatui::widgets::Wrap { trim: true })
+        };
+        match self.mode {
+            Mode::Compare => {
+                let halves = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(right_layout[0]);
+                for (side, area) in halves.iter().enumerate() {
+                    frame.render_widget(panel(compare::panel(current_q, side, &palette)), *area);
+                }
+            }
+            _ => frame.render_widget(panel(instructions), right_layout[0]),
+        }
+        if let Some(lines) = note_lines {
+            let title = match self.note_editor {
+                Some(_) => format!(" {} ", t!("notes.editing")),
+                None => format!(" {}{} ", t!("notes.title"), keymap.notes.label()),
+            };
+            frame.render_widget(
+                Paragraph::new(lines).block(
+                    Block::new()
+                        .borders(Borders::TOP | Borders::LEFT)
+                        .title(title.fg(palette.action_key))
+                        .padding(ratatui::widgets::Padding::horizontal(1)),
+                ),
+                right_layout[1],
+            );
+        }
+        // add metadata section, only the title is shown while collapsed
+        if let Some(lines) = metadata_lines {
+            let (arrow, lines) = match self.show_metadata {
+                true => ("▾", lines),
+                false => ("▸", vec![]),
+            };
+            frame.render_widget(
+                Paragraph::new(lines).block(
+                    Block::new()
+                        .borders(Borders::TOP | Borders::LEFT)
+                        .title(Line::from(vec![
+                            format!(" {}{}", arrow, t!("metadata.title")).into(),
+                            format!("{} ", keymap.metadata.label())
+                                .fg(palette.action_key)
+                                .bold(),
+                        ]))
+                        .padding(ratatui::widgets::Padding::horizontal(1)),
+                ),
+                right_layout[2],
+            );
+        }
+        // add image box, the image itself is drawn by self.graphics after the frame
+        // when the terminal can't display images, a caption with the file is shown instead
+        let image_area = current_q.image_path.as_ref().map(|image_path| {
+            let image_block = Block::new()
+                .borders(Borders::TOP | Borders::LEFT)
+                .title(t!("image.title", path = image_path));
+            let image_area = image_block.inner(right_layout[3]);
+            if self.graphics.is_supported() {
+                frame.render_widget(image_block, right_layout[3]);
+            } else {
+                frame.render_widget(
+                    Paragraph::new(vec![
+                        Line::from(t!("image.caption", path = image_path).italic()),
+                        Line::from(""),
+                        Line::from(t!("image.unsupported")),
+                    ])
+                    .block(image_block.padding(ratatui::widgets::Padding::new(1, 1, 1, 1)))
+                    .wrap(ratatui::widgets::Wrap { trim: true }),
+                    right_layout[3],
+                );
+            }
+            image_area
+        });
         // Add controls + progress bar
         // progress relates to number of questions left to answer/classify
         frame.render_widget(
@@ -293,168 +590,140 @@ impl App {
                         .title(controls.alignment(Alignment::Center))
                         .borders(Borders::TOP),
                 )
-                .ratio(self.num_answered as f64 / self.questions.len() as f64)
+                .ratio(self.num_answered as f64 / self.banks.len() as f64)
                 .filled_style(
                     Style::default()
-                        .fg(Color::LightCyan)
-                        .bg(Color::Black)
+                        .fg(palette.progress)
+                        .bg(palette.progress_bg)
                         .add_modifier(Modifier::BOLD),
                 )
-                .label(format!(
-                    "Question progress: {}%",
-                    (self.num_answered as f64 * 100_f64 / self.questions.len() as f64).round()
+                .label(t!(
+                    "status.progress",
+                    percent =
+                        (self.num_answered as f64 * 100_f64 / self.banks.len() as f64).round()
                 )),
             outer_layout[2],
         );
-    }
-
-    /// updates the application's state based on user input
-    fn handle_events(&mut self) -> Result<()> {
-        match event::read()? {
-            // it's important to check that the event is a key press event as
-            // crossterm also emits key release and repeat events on Windows.
-            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => self
-                .handle_key_event(key_event)
-                .wrap_err_with(|| format!("handling key event failed:\n{key_event:#?}")),
-            _ => Ok(()),
+        if let Some(selected) = self.jump_list {
+            bookmarks::render_jump_list(
+                frame,
+                outer_layout[1],
+                &self.banks,
+                &self.bookmarks,
+                selected,
+                &palette,
+            );
         }
-    }
-
-    // handle key presses in the temrinal
-    fn handle_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
-        // common controls
-        match key_event.code {
-            KeyCode::Char('q') => self.exit()?, // also calls self.save() on exit
-            KeyCode::Char('s') => self.save()?,
-            KeyCode::Left => self
-                .decrement_index()
-                .wrap_err("overflow substraction error")?,
-            KeyCode::Right => self
-                .increment_index()
-                .wrap_err("overflow addition error somehow")?,
-            _ => {}
-        }
-        // mode specific controls
-        if self.mode == Mode::Classify {
-            match key_event.code {
-                // increment progress bar
-                KeyCode::Char('t') => {
-                    // only increment num_answered if not prev answered.
-                    if self.questions[self.question_index]
-                        .is_higher_order
-                        .is_none()
-                    {
-                        self.increment_num_answered()?;
-                    }
-                    self.questions[self.question_index].is_higher_order = Some(true)
-                }
-                KeyCode::Char('f') => {
-                    // only increment num_answered if not prev answered.
-                    if self.questions[self.question_index]
-                        .is_higher_order
-                        .is_none()
-                    {
-                        self.increment_num_answered()?;
-                    }
-                    self.questions[self.question_index].is_higher_order = Some(false)
-                }
-                _ => {}
-            }
-        }
-        if self.mode == Mode::Answer {
-            if let KeyCode::Char(value) = key_event.code {
-                match value {
-                    '1' | '2' | '3' | '4' | '5' | '6' => {
-                        // hacky wa to do this...
-                        if let Some(human_answer) = get_answer_from_alphanum_option(
-                            &value.to_string(),
-                            &self.questions[self.question_index],
-                        ) {
-                            if self.questions[self.question_index].human_answer.is_none() {
-                                self.increment_num_answered()?;
-                            };
-                            self.questions[self.question_index].human_answer = Some(human_answer);
-                        }
-                    }
-                    _ => {}
-                }
-            }
+        if let Some(selected) = self.sort_menu {
+            sort::render_menu(frame, outer_layout[1], self.order, selected, &palette);
         }
-        Ok(())
-    }
-
-    fn exit(&mut self) -> Result<()> {
-        self.exit = true;
-        save_json(&self.json_path, &self.questions).wrap_err("save_json failed")?;
-        Ok(())
+        if let Some(finder) = &self.topic_finder {
+            let topics = taxonomy::topics(&self.config.taxonomy, &self.banks);
+            taxonomy::render_finder(frame, outer_layout[1], finder, &topics, &palette);
+        }
+        if let Some(finder) = &self.tag_finder {
+            tags::render_finder(
+                frame,
+                outer_layout[1],
+                finder,
+                &tags::all(&self.banks),
+                self.banks.question(self.question_index),
+                &palette,
+            );
+        }
+        if let Some(selected) = self.grid {
+            grid::render(
+                frame,
+                outer_layout[1],
+                &self.banks,
+                self.mode,
+                self.question_index,
+                selected,
+                &palette,
+            );
+        }
+        (image_area.filter(|_| self.graphics.is_supported()), drawn)
     }
 
-    // saves time of save in app.message for state
-    fn save(&mut self) -> Result<()> {
-        // Get the current UTC time
-        let now = Utc::now();
-        save_json(&self.json_path, &self.questions).wrap_err("save_json failed")?;
-        let message = format!("Progress saved at {}", now);
-        self.message = message;
-        Ok(())
+    /// The options struck out on the current question
+    fn eliminated(&self) -> BTreeSet<usize> {
+        match self.config.record_eliminations {
+            true => self.banks.question(self.question_index).eliminated(),
+            false => self
+                .eliminated
+                .get(&self.question_index)
+                .cloned()
+                .unwrap_or_default(),
+        }
     }
 
-    // loops if goes below the first question
-    fn decrement_index(&mut self) -> Result<()> {
-        self.question_index = match self.question_index.checked_sub(1) {
-            Some(new_index) => new_index,
-            None => self.questions.len() - 1,
+    // top right of the screen: last save, or what to do about a changed file
+    fn top_message(&self, palette: &theme::Palette) -> Line<'static> {
+        if let Some(typed) = &self.quit_prompt {
+            let code = "*".repeat(typed.chars().count());
+            return Line::from(t!("proctor.prompt", code = code).fg(palette.missing).bold());
+        }
+        let Some(json_path) = &self.conflict else {
+            return Line::from(self.message.clone());
         };
-        Ok(())
-    }
-    // loops if goes above the last question
-    fn increment_index(&mut self) -> Result<()> {
-        self.question_index = (self.question_index + 1) % self.questions.len();
-        Ok(())
+        let keymap = &self.config.keymap;
+        let prompt = t!(
+            "watch.changed",
+            path = json_path.display(),
+            reload = keymap.reload.label(),
+            merge = keymap.merge.label(),
+            keep = keymap.keep.label()
+        );
+        // why reloading or merging didn't work, if it didn't
+        let prompt = match self.message.is_empty() {
+            true => prompt,
+            false => format!("{} {}", self.message, prompt),
+        };
+        Line::from(prompt.fg(palette.missing).bold())
     }
 
-    fn increment_num_answered(&mut self) -> Result<()> {
-        self.num_answered += 1;
-        Ok(())
+    // not in blinded or proctored sessions, or when there's no key to show
+    fn can_reveal(&self) -> bool {
+        !self.blinded
+            && self.proctor_code.is_none()
+            && !self.banks.question(self.question_index).answer.is_empty()
     }
 }
 
-/// save .json file to a specified path
-fn save_json(json_path: &std::path::PathBuf, questions: &Questions) -> Result<()> {
-    let new_data = serde_json::to_string_pretty(&questions)
-        .wrap_err("Failed to serialize JSON while saving.")?;
-    fs::write(json_path, new_data).wrap_err("Failed to write JSON to file.")?;
-    Ok(())
+fn get_num_answered(mode: &Mode, banks: &Banks) -> usize {
+    banks
+        .questions()
+        .filter(|question| mode.is_done(question))
+        .count()
 }
 
-fn get_answer_from_alphanum_option(option: &str, question: &Question) -> Option<String> {
-    let index = match option {
-        "1" => 0,
-        "2" => 1,
-        "3" => 2,
-        "4" => 3,
-        "5" => 4,
-        "6" => 5,
-        _ => 100000,
-    };
-
-    if index < question.options.len() {
-        Some(question.options[index].clone())
-    } else {
-        None
+// the banks of a session, set or serve with the config's settings applied
+fn open_banks(json_paths: &[PathBuf], config: &config::Config) -> Result<Banks> {
+    let mut banks = Banks::load(json_paths)?;
+    banks.set_key_order(config.key_order);
+    banks.set_audit(config.audit_log);
+    if let Some(rater_id) = &config.rater_id {
+        banks.set_rater_id(rater_id);
+    }
+    if let Some(key) = &config.signing_key {
+        banks.set_signing_key(key);
     }
+    Ok(banks)
 }
 
-fn get_num_answered(mode: &Mode, questions: &Questions) -> usize {
-    match mode {
-        Mode::Classify => questions
-            .iter()
-            .filter(|question| question.is_higher_order.is_some())
-            .count(),
-        Mode::Answer => questions
-            .iter()
-            .filter(|question| question.human_answer.is_some())
-            .count(),
+// output piped into eg. head can be closed early, that isn't an error
+fn ignore_broken_pipe(result: Result<()>) -> Result<()> {
+    match result {
+        Err(err)
+            if err.chain().any(|cause| {
+                cause
+                    .downcast_ref::<std::io::Error>()
+                    .is_some_and(|err| err.kind() == std::io::ErrorKind::BrokenPipe)
+            }) =>
+        {
+            Ok(())
+        }
+        result => result,
     }
 }
 
@@ -462,33 +731,905 @@ fn main() -> Result<()> {
     errors::install_hooks()?;
     // parse cli arguements and load mode and .json
     let args = Cli::parse();
+    if let Some(Command::Completions { shell }) = args.command {
+        let mut command = Cli::command();
+        let name = command.get_name().to_string();
+        clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+        return Ok(());
+    }
+    // settings from the config file, overridden by any flags given
+    let options = args.options;
+    let mut config = config::load(options.config.as_deref(), options.profile.as_deref())?;
+    config.theme = options.theme.unwrap_or(config.theme);
+    config.autosave_interval = options.autosave.unwrap_or(config.autosave_interval);
+    config.rater_id = options.rater.or(config.rater_id);
+    i18n::init(options.lang.as_deref().or(config.lang.as_deref()))?;
+    crypt::init(config.age_identity.as_deref(), &config.age_recipients);
+    dimensions::init(&config.dimensions)?;
+    plugins::init(&config.plugins)?;
 
-    let mode = match args.mode.as_str() {
-        "classify" => Mode::Classify,
-        "answer" => Mode::Answer,
-        _ => {
-            eprintln!("Mode must be either 'classify' or 'answer'");
-            process::exit(1)
+    let mut live_client = None;
+    let (mode, session) = match args.command {
+        Some(Command::Answer(session)) => (Mode::Answer, session),
+        Some(Command::Classify(session)) => (Mode::Classify, session),
+        Some(Command::Rate(session)) => (Mode::Rate, session),
+        Some(Command::Adjudicate(session)) => (Mode::Adjudicate, session),
+        Some(Command::Quiz(session)) => (Mode::Quiz, session),
+        Some(Command::Learn(session)) => (Mode::Learn, session),
+        Some(Command::Dimensions(session)) => (Mode::Dimensions, session),
+        Some(Command::Topic(session)) => (Mode::Topic, session),
+        Some(Command::Plugin(session)) => (Mode::Plugin, session),
+        // the login of the account raters ssh into, each on their own copy
+        Some(Command::Ssh {
+            mode,
+            raters,
+            mut session,
+        }) => {
+            config.rater_id = Some(shared::ssh_rater(&raters)?);
+            session.per_rater = true;
+            (mode, session)
+        }
+        Some(Command::Review {
+            session,
+            llm: false,
+            ..
+        }) => (Mode::Review, session),
+        Some(Command::Review {
+            session,
+            llm: true,
+            model,
+            backend,
+        }) => {
+            let settings = backend.settings(&config.llm, model.as_ref());
+            let client = llm::Client::new(&settings, config.profile.as_deref())?;
+            let mut banks = open_banks(&session.files.resolve_piped()?, &config)?;
+            let template =
+                prompts::template(backend.prompt.as_ref(), config.llm.prompts.review.as_ref())?;
+            let reviewed = review::run(&mut banks, &client, template.as_ref())?;
+            eprintln!(
+                "{}",
+                t!("review.done", count = reviewed, model = client.model)
+            );
+            llm::report(&[client]);
+            return Ok(());
+        }
+        Some(Command::Distractors {
+            session,
+            llm: false,
+            ..
+        }) => (Mode::Distractors, session),
+        Some(Command::Author {
+            session,
+            llm: false,
+            ..
+        }) => (Mode::Author, session),
+        Some(Command::Author {
+            session,
+            llm: true,
+            model,
+            backend,
+        }) => {
+            let settings = backend.settings(&config.llm, model.as_ref());
+            let client = llm::Client::new(&settings, config.profile.as_deref())?;
+            let mut banks = open_banks(&session.files.resolve_piped()?, &config)?;
+            let template =
+                prompts::template(backend.prompt.as_ref(), config.llm.prompts.author.as_ref())?;
+            let drafted = drafts::run(&mut banks, &client, template.as_ref())?;
+            eprintln!(
+                "{}",
+                t!("drafts.done", count = drafted, model = client.model)
+            );
+            llm::report(&[client]);
+            return Ok(());
+        }
+        Some(Command::Compare {
+            session,
+            models,
+            llm: false,
+            ..
+        }) => {
+            if !matches!(models.len(), 0 | 2) {
+                bail!("compare two models, give --model twice");
+            }
+            compare::init(&models);
+            (Mode::Compare, session)
+        }
+        Some(Command::Compare {
+            session,
+            models,
+            llm: true,
+            backend,
+        }) => {
+            let clients = match models.is_empty() {
+                true => vec![llm::Client::new(
+                    &backend.settings(&config.llm, None),
+                    config.profile.as_deref(),
+                )?],
+                false => models
+                    .iter()
+                    .map(|model| {
+                        llm::Client::new(
+                            &backend.settings(&config.llm, Some(model)),
+                            config.profile.as_deref(),
+                        )
+                    })
+                    .collect::<Result<_>>()?,
+            };
+            let mut banks = open_banks(&session.files.resolve_piped()?, &config)?;
+            let template =
+                prompts::template(backend.prompt.as_ref(), config.llm.prompts.compare.as_ref())?;
+            let answered = compare::run(&mut banks, &clients, template.as_ref())?;
+            eprintln!("{}", t!("compare.done", count = answered));
+            llm::report(&clients);
+            return Ok(());
+        }
+        Some(Command::Distractors {
+            session,
+            llm: true,
+            model,
+            backend,
+        }) => {
+            let settings = backend.settings(&config.llm, model.as_ref());
+            let client = llm::Client::new(&settings, config.profile.as_deref())?;
+            let mut banks = open_banks(&session.files.resolve_piped()?, &config)?;
+            let template = prompts::template(
+                backend.prompt.as_ref(),
+                config.llm.prompts.distractors.as_ref(),
+            )?;
+            let proposed = proposals::run(&mut banks, &client, template.as_ref())?;
+            eprintln!(
+                "{}",
+                t!("proposals.done", count = proposed, model = client.model)
+            );
+            llm::report(&[client]);
+            return Ok(());
+        }
+        Some(Command::Embed {
+            json_paths,
+            model,
+            provider,
+            endpoint,
+        }) => {
+            let Some(model) = model.or(config.llm.embedding_model.clone()) else {
+                bail!("no embedding model, set embedding_model in the [llm] table of the config file (eg. text-embedding-3-small, or nomic-embed-text with Ollama)");
+            };
+            let settings = config.llm.with(provider, endpoint.as_ref(), Some(&model));
+            let client = llm::Client::new(&settings, config.profile.as_deref())?;
+            let embedded = embeddings::embed(&Banks::load(&json_paths)?, &client)?;
+            eprintln!(
+                "{}",
+                t!("embeddings.done", count = embedded, model = client.model)
+            );
+            llm::report(&[client]);
+            return Ok(());
+        }
+        Some(Command::Search {
+            query,
+            json_paths,
+            count,
+            json,
+            provider,
+            endpoint,
+        }) => {
+            let banks = Banks::load(&json_paths)?;
+            let embeddings = embeddings::Embeddings::load(&banks)?;
+            // the model the banks were embedded with
+            let settings = config
+                .llm
+                .with(provider, endpoint.as_ref(), Some(&embeddings.model));
+            let client = llm::Client::new(&settings, config.profile.as_deref())?;
+            let hits = embeddings::search(&embeddings, &client, &query, count)?;
+            let mut stdout = std::io::stdout();
+            let written = match json {
+                true => {
+                    let hits: Vec<_> = hits
+                        .iter()
+                        .map(|&(i, similarity)| {
+                            json!({
+                                "question": i + 1,
+                                "id": banks.question(i).id(),
+                                "similarity": similarity,
+                            })
+                        })
+                        .collect();
+                    writeln!(stdout, "{}", serde_json::to_string_pretty(&hits)?)
+                }
+                false => embeddings::print(&banks, &hits, &mut stdout),
+            };
+            return ignore_broken_pipe(written.map_err(Into::into));
+        }
+        Some(Command::Cluster {
+            json_paths,
+            threshold,
+            json,
+        }) => {
+            let banks = Banks::load(&json_paths)?;
+            let embeddings = embeddings::Embeddings::load(&banks)?;
+            let clusters = clusters::of(&banks, &embeddings, threshold);
+            let mut stdout = std::io::stdout();
+            let written = match json {
+                true => writeln!(stdout, "{}", serde_json::to_string_pretty(&clusters)?),
+                false => clusters.print(&banks, &mut stdout),
+            };
+            return ignore_broken_pipe(written.map_err(Into::into));
         }
+        Some(Command::Dedupe {
+            json_paths,
+            embeddings,
+            threshold,
+            json,
+        }) => {
+            let banks = Banks::load(&json_paths)?;
+            let duplicates = match embeddings {
+                true => dedupe::by_embeddings(
+                    &embeddings::Embeddings::load(&banks)?,
+                    threshold.unwrap_or(dedupe::Method::Embeddings.threshold()),
+                ),
+                false => dedupe::by_words(
+                    &banks,
+                    threshold.unwrap_or(dedupe::Method::Words.threshold()),
+                ),
+            };
+            let mut stdout = std::io::stdout();
+            let written = match json {
+                true => writeln!(stdout, "{}", serde_json::to_string_pretty(&duplicates)?),
+                false => duplicates.print(&banks, &mut stdout),
+            };
+            ignore_broken_pipe(written.map_err(Into::into))?;
+            // like lint, so scripts can stop on it
+            if !duplicates.pairs.is_empty() {
+                process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Set {
+            mode,
+            files,
+            mut assignments,
+            from,
+        }) => {
+            if let Some(csv_path) = from {
+                assignments.extend(batch::read_csv(&csv_path)?);
+            }
+            let mut banks = open_banks(&files.resolve_piped()?, &config)?;
+            for warning in banks.check_manifests()? {
+                eprintln!("{}", warning);
+            }
+            batch::apply(
+                &mut banks,
+                mode,
+                &assignments,
+                &format!("set {}", mode.name()),
+            )?;
+            if let Some(patch_path) = &files.patch_out {
+                banks.write_patch(patch_path)?;
+            }
+            banks.save()?;
+            eprintln!("{}", t!("batch.done", count = assignments.len()));
+            return Ok(());
+        }
+        Some(Command::Serve {
+            mode,
+            files,
+            port,
+            host,
+        }) => {
+            if matches!(mode, Mode::Dimensions | Mode::Topic) {
+                bail!("{} mode only works in the terminal", mode.name());
+            }
+            let mut banks = open_banks(&files.resolve()?, &config)?;
+            if banks.is_empty() {
+                bail!("there are no questions to go through");
+            }
+            for warning in banks.check_manifests()? {
+                eprintln!("{}", warning);
+            }
+            let address = format!("{}:{}", host, port);
+            return web::serve(&mut banks, mode, &address, files.patch_out.as_deref());
+        }
+        Some(Command::Host {
+            mode,
+            json_path,
+            port,
+            host,
+        }) => {
+            if matches!(mode, Mode::Dimensions | Mode::Topic) {
+                bail!("{} mode only works in the terminal", mode.name());
+            }
+            return live::host(&json_path, mode, &format!("{}:{}", host, port), &config);
+        }
+        Some(Command::Join { url }) => {
+            let rater_id = config
+                .rater_id
+                .as_deref()
+                .ok_or_eyre("joining needs a rater_id, from --rater or the config file")?;
+            let (client, json_path, mode) = live::Client::join(&url, rater_id)?;
+            live_client = Some(client);
+            (mode, cli::Session::of(vec![json_path]))
+        }
+        Some(Command::Stats {
+            json_paths,
+            json,
+            plot,
+        }) => {
+            let raters = export::load_raters(&json_paths)?;
+            let banks: Vec<&bank::Bank> = raters.iter().map(|rater| &rater.bank).collect();
+            let mut stats = stats::Stats::of(&banks);
+            if raters.len() > 1 {
+                stats.reliability = Some(reliability::of(&raters));
+            }
+            let mut stdout = std::io::stdout();
+            let written = match json {
+                true => writeln!(stdout, "{}", serde_json::to_string_pretty(&stats)?),
+                false => stats.print(&mut stdout).and_then(|_| match plot {
+                    true => plots::print(&raters, &mut stdout),
+                    false => Ok(()),
+                }),
+            };
+            return ignore_broken_pipe(written.map_err(Into::into));
+        }
+        Some(Command::Convert { input, output, to }) => {
+            let mut bank = bank::load_json(&input)?;
+            bank.key_order = config.key_order;
+            bank.set_format(to)?;
+            return ignore_broken_pipe(bank::save_json(&output, &bank));
+        }
+        Some(Command::Import {
+            input,
+            sheet,
+            google_sheet,
+            range,
+            columns,
+            output,
+        }) => {
+            if output.exists() {
+                bail!("{} already exists, not overwriting it", output.display());
+            }
+            let rows = match (input, google_sheet) {
+                (Some(input), _) => import::read_workbook(&input, sheet.as_deref())?,
+                (None, Some(google_sheet)) => {
+                    let key_path = config
+                        .google_service_account
+                        .clone()
+                        .or_else(|| {
+                            env::var_os("GOOGLE_APPLICATION_CREDENTIALS").map(PathBuf::from)
+                        })
+                        .ok_or_eyre(
+                            "reading a Google Sheet needs google_service_account in the config file",
+                        )?;
+                    google::read_sheet(&google_sheet, range.as_deref().unwrap_or("A:Z"), &key_path)?
+                }
+                (None, None) => unreachable!("clap requires one of them"),
+            };
+            let mut bank = import::from_rows(&rows, &columns)?;
+            bank.key_order = config.key_order;
+            bank::save_json(&output, &bank)?;
+            eprintln!(
+                "{}",
+                t!(
+                    "import.done",
+                    count = bank.questions.len(),
+                    path = output.display()
+                )
+            );
+            return Ok(());
+        }
+        Some(Command::Sample {
+            json_path,
+            n,
+            per,
+            seed,
+            output,
+        }) => {
+            if output.exists() {
+                bail!("{} already exists, not overwriting it", output.display());
+            }
+            let mut bank = bank::load_json(&json_path)?;
+            bank.key_order = config.key_order;
+            let drawn = sample::draw(&mut bank, n, per, seed);
+            bank::save_json(&output, &bank)?;
+            for (stratum, count, total) in drawn {
+                let line = match stratum {
+                    Some(name) => t!("sample.stratum", name = name, count = count, total = total),
+                    None if per.is_some() => t!("sample.none", count = count, total = total),
+                    None => t!("sample.drawn", count = count, total = total),
+                };
+                eprintln!("{}", line);
+            }
+            eprintln!("{}", t!("sample.written", path = output.display()));
+            return Ok(());
+        }
+        Some(Command::Lint { json_paths, all }) => {
+            let banks = Banks::load(&json_paths)?;
+            let flagged = match lint::print(&banks, all, &mut std::io::stdout()) {
+                Ok(flagged) => flagged,
+                Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => return Ok(()),
+                Err(err) => return Err(err.into()),
+            };
+            // like verify, so scripts can stop on it
+            if flagged {
+                process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Readability { json_paths, json }) => {
+            let readability = readability::of(&Banks::load(&json_paths)?);
+            let mut stdout = std::io::stdout();
+            let written = match json {
+                true => writeln!(stdout, "{}", serde_json::to_string_pretty(&readability)?),
+                false => readability.print(&mut stdout),
+            };
+            return ignore_broken_pipe(written.map_err(Into::into));
+        }
+        Some(Command::Spellcheck {
+            json_paths,
+            dictionary,
+            allow,
+        }) => {
+            let banks = Banks::load(&json_paths)?;
+            let mut allowlists = config.spelling_allowlist.clone();
+            allowlists.extend(allow);
+            let dictionary = spelling::Dictionary::load(
+                dictionary.as_deref().or(config.dictionary.as_deref()),
+                &allowlists,
+            )?;
+            let flagged = match spelling::print(&banks, &dictionary, &mut std::io::stdout()) {
+                Ok(flagged) => flagged,
+                Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => return Ok(()),
+                Err(err) => return Err(err.into()),
+            };
+            if flagged {
+                process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Print {
+            json_paths,
+            title,
+            answer_sheet,
+            key,
+            form,
+            output,
+        }) => {
+            let mut banks = Banks::load(&json_paths)?;
+            if let Some(form) = &form {
+                banks.filter(|question| question.in_form(form));
+            }
+            let title = title.unwrap_or_else(|| {
+                let name = json_paths[0]
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy();
+                name.split('.').next().unwrap_or_default().to_string()
+            });
+            let mut out: Box<dyn Write> = match output {
+                Some(output) => Box::new(
+                    std::fs::File::create(&output)
+                        .wrap_err_with(|| format!("could not create {}", output.display()))?,
+                ),
+                None => Box::new(std::io::stdout()),
+            };
+            let written = match key {
+                true => paper::print_key(&banks, &title, &mut out),
+                false => paper::print(&banks, &title, answer_sheet, &mut out),
+            };
+            return ignore_broken_pipe(written.map_err(Into::into));
+        }
+        Some(Command::Export {
+            to,
+            json_paths,
+            output,
+        }) => {
+            if to == export::ExportFormat::Embeddings {
+                let banks = Banks::load(&json_paths)?;
+                let embeddings = embeddings::Embeddings::load(&banks)?;
+                if embeddings.stale() > 0 {
+                    eprintln!("{}", t!("embeddings.stale", count = embeddings.stale()));
+                }
+                return match output {
+                    Some(output) if output.extension().is_some_and(|ext| ext == "parquet") => {
+                        embeddings::export_parquet(&banks, &embeddings, &output)
+                    }
+                    Some(output) => {
+                        let file = std::fs::File::create(&output)
+                            .wrap_err_with(|| format!("could not create {}", output.display()))?;
+                        embeddings::export(&banks, &embeddings, file)
+                    }
+                    None => ignore_broken_pipe(embeddings::export(
+                        &banks,
+                        &embeddings,
+                        std::io::stdout(),
+                    )),
+                };
+            }
+            let raters = export::load_raters(&json_paths)?;
+            if to == export::ExportFormat::Wide {
+                let output = output
+                    .as_ref()
+                    .ok_or_eyre("--to wide needs -o, its codebook is written next to it")?;
+                let path = output.with_extension("codebook.csv");
+                let file = std::fs::File::create(&path)
+                    .wrap_err_with(|| format!("could not create {}", path.display()))?;
+                export::codebook(&raters, file)?;
+            }
+            return match output {
+                Some(output) => {
+                    let file = std::fs::File::create(&output)
+                        .wrap_err_with(|| format!("could not create {}", output.display()))?;
+                    export::export(to, &raters, file)
+                }
+                None => ignore_broken_pipe(export::export(to, &raters, std::io::stdout())),
+            };
+        }
+        Some(Command::Grade {
+            json_paths,
+            json,
+            pass_mark,
+        }) => {
+            let pass_mark = pass_mark.or(config.pass_mark);
+            let grades: Vec<grade::Grade> = export::load_raters(&json_paths)?
+                .iter()
+                .map(|rater| grade::Grade::of(rater, pass_mark))
+                .collect();
+            let mut stdout = std::io::stdout();
+            let written = match json {
+                true => writeln!(stdout, "{}", serde_json::to_string_pretty(&grades)?),
+                false => grades.iter().try_for_each(|grade| grade.print(&mut stdout)),
+            };
+            return ignore_broken_pipe(written.map_err(Into::into));
+        }
+        Some(Command::Rasch {
+            json_paths,
+            by_group,
+            json,
+        }) => {
+            let raters = export::load_raters(&json_paths)?;
+            let mut groups: BTreeMap<Option<String>, Vec<&export::Rater>> = BTreeMap::new();
+            for rater in &raters {
+                let group = by_group.then(|| rasch::group(rater)).flatten();
+                groups.entry(group).or_default().push(rater);
+            }
+            let calibrations: Vec<rasch::Calibration> = groups
+                .into_iter()
+                .map(|(group, raters)| rasch::calibrate(group, &raters))
+                .collect();
+            let mut stdout = std::io::stdout();
+            let written = match json {
+                true => writeln!(stdout, "{}", serde_json::to_string_pretty(&calibrations)?),
+                false => rasch::print(&calibrations, &mut stdout),
+            };
+            return ignore_broken_pipe(written.map_err(Into::into));
+        }
+        Some(Command::Equate {
+            form_x,
+            form_y,
+            json,
+        }) => {
+            let equating = equating::equate(
+                &export::load_raters(&form_x)?,
+                &export::load_raters(&form_y)?,
+            )?;
+            let mut stdout = std::io::stdout();
+            let written = match json {
+                true => writeln!(stdout, "{}", serde_json::to_string_pretty(&equating)?),
+                false => equating.print(&mut stdout),
+            };
+            return ignore_broken_pipe(written.map_err(Into::into));
+        }
+        Some(Command::Verify { json_paths }) => {
+            let key = config.signing_key.as_deref().ok_or_eyre(
+                "verifying needs the signing_key the files were signed with in the config file",
+            )?;
+            let mut all_valid = true;
+            for json_path in json_paths {
+                let data = bank::read_json(&json_path)?;
+                let path = json_path.display();
+                let verdict =
+                    signing::verify(&data, key).wrap_err_with(|| format!("in {}", path))?;
+                all_valid &= verdict == signing::Verdict::Valid;
+                println!(
+                    "{}",
+                    match verdict {
+                        signing::Verdict::Valid => t!("verify.valid", path = path),
+                        signing::Verdict::Modified => t!("verify.modified", path = path),
+                        signing::Verdict::Unsigned => t!("verify.unsigned", path = path),
+                    }
+                );
+            }
+            if !all_valid {
+                process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Manifest { json_paths }) => {
+            for json_path in json_paths {
+                let bank = bank::load_json(&json_path)?;
+                let manifest_path = manifest::write(&json_path, &bank)?;
+                println!("{}", t!("manifest.written", path = manifest_path.display()));
+            }
+            return Ok(());
+        }
+        Some(Command::Counterbalance {
+            json_path,
+            roster,
+            raters,
+        }) => {
+            let raters = match roster {
+                Some(roster) => counterbalance::read_roster(&roster)?,
+                None => raters,
+            };
+            for packet in counterbalance::write_packets(&json_path, &raters)? {
+                println!("{}", t!("counterbalance.written", path = packet));
+            }
+            return Ok(());
+        }
+        Some(Command::History) => {
+            let log_dir = events::log_dir(config.profile.as_deref())
+                .ok_or_eyre("no directory with event logs")?;
+            let sessions = history::read_sessions(&log_dir)?;
+            let written = history::print(&sessions, &mut std::io::stdout());
+            return ignore_broken_pipe(written.map_err(Into::into));
+        }
+        Some(Command::Completions { .. }) => unreachable!("handled above"),
+        // `question_cli questions.json`, using --mode or the config's default_mode
+        None => {
+            // `question_cli` on its own offers to resume a recent session
+            let sessions = match args.json_paths.is_empty() {
+                true => recent::load(&config)?,
+                false => vec![],
+            };
+            match args.mode.or(config.default_mode) {
+                Some(mode) if !args.json_paths.is_empty() => {
+                    (mode, cli::Session::of(args.json_paths))
+                }
+                _ if !sessions.is_empty() => {
+                    let mut terminal = tui::init()?;
+                    let picked = recent::pick(&mut terminal, &sessions, &config);
+                    tui::restore()?;
+                    let Some(picked) = picked? else {
+                        return Ok(());
+                    };
+                    let recent = &sessions[picked];
+                    (recent.mode, cli::Session::of(recent.files.clone()))
+                }
+                // nothing to resume either, the help as clap shows it without arguments
+                _ if args.json_paths.is_empty() => {
+                    eprint!("{}", Cli::command().render_help());
+                    process::exit(2)
+                }
+                _ => {
+                    let modes: Vec<&str> = Mode::value_variants().iter().map(Mode::name).collect();
+                    eprintln!("{}", t!("errors.missing_mode", modes = modes.join(", ")));
+                    process::exit(1)
+                }
+            }
+        }
+    };
+    let mut json_paths = session.files.resolve()?;
+    if session.per_rater {
+        let rater_id = config
+            .rater_id
+            .as_deref()
+            .ok_or_eyre("--per-rater needs a rater_id, from --rater or the config file")?;
+        json_paths = json_paths
+            .iter()
+            .map(|json_path| shared::rater_copy(json_path, rater_id))
+            .collect::<Result<_>>()?;
+    }
+    if let Some(name) = &session.session {
+        json_paths = json_paths
+            .iter()
+            .map(|json_path| shared::session_copy(json_path, name))
+            .collect::<Result<_>>()?;
+    }
+    let mut banks = open_banks(&json_paths, &config)?;
+    if banks.is_empty() {
+        bail!("there are no questions to go through");
+    }
+    if mode == Mode::Dimensions && dimensions::all().is_empty() {
+        bail!(
+            "there are no dimensions to classify on, add some to the config file (see the README)"
+        );
+    }
+    if mode == Mode::Plugin
+        && banks
+            .questions()
+            .all(|question| plugins::of(question).is_none())
+    {
+        let names: Vec<&str> = plugins::all().iter().map(|plugin| plugin.name()).collect();
+        bail!(
+            "none of the questions has the \"type\" of a plugin ({})",
+            names.join(", ")
+        );
+    }
+    if mode == Mode::Topic && taxonomy::topics(&config.taxonomy, &banks).is_empty() {
+        eprintln!("{}", t!("topic.no_taxonomy"));
+    }
+    if session.blinded {
+        if matches!(mode, Mode::Quiz | Mode::Learn | Mode::Adjudicate) {
+            bail!("{} needs the answer key, it can't be blinded", mode.name());
+        }
+        let failed: Vec<String> = banks
+            .check_blinding(&proctor::now())
+            .into_iter()
+            .map(|(json_path, questions)| {
+                let questions: Vec<String> = questions.iter().map(|q| q.to_string()).collect();
+                format!(
+                    "{} (questions {})",
+                    json_path.display(),
+                    questions.join(", ")
+                )
+            })
+            .collect();
+        if !failed.is_empty() {
+            banks.save()?; // keeps the failed check in the files
+            bail!(
+                "the answer key, explanations or model reviews were put back into {}, not starting the blinded session",
+                failed.join(", ")
+            );
+        }
+    }
+    counterbalance::apply(&mut banks);
+    let json_paths: Vec<&std::path::Path> = banks.paths().collect();
+    let mut state = state::State::load(&json_paths, notes::user(&config).as_deref())?;
+    let mut question_index = match session.start {
+        Some(start) => start.saturating_sub(1),
+        None => banks.position(state.question).unwrap_or(0),
+    }
+    .min(banks.len() - 1);
+    let mut message = "".to_string();
+    let mut prefilled = 0;
+    let subsetted = match session.distractors {
+        Some(count) => {
+            let rater = notes::user(&config).unwrap_or_default();
+            distractors::subset(&mut banks, count, &rater)?
+        }
+        None => false,
+    };
+    // a bank changed in transit matters more than the prefill count
+    if let Some(warning) = banks.check_manifests()?.first() {
+        message = warning.clone();
+    }
+    if let Some(csv_path) = &session.from {
+        prefilled = batch::prefill(&mut banks, mode, &batch::read_csv(csv_path)?)?;
+        let to_review = banks.questions().filter(|q| !mode.is_done(q)).count();
+        message = t!("batch.prefilled", count = prefilled, left = to_review);
+        question_index = banks
+            .questions()
+            .position(|q| !mode.is_done(q))
+            .unwrap_or(question_index);
+    }
+    // filters are applied once, answering a question doesn't make it disappear
+    let filtered = session.only_unanswered
+        || !session.tags.is_empty()
+        || session.flagged
+        || session.form.is_some();
+    if filtered {
+        banks.filter(|question| {
+            (!session.only_unanswered || !mode.is_done(question))
+                && (session.tags.is_empty()
+                    || question
+                        .tags()
+                        .iter()
+                        .any(|tag| session.tags.iter().any(|t| t == tag)))
+                && (!session.flagged || question.is_flagged())
+                && session
+                    .form
+                    .as_ref()
+                    .is_none_or(|form| question.in_form(form))
+        });
+        if banks.is_empty() {
+            bail!("no questions match the filters");
+        }
+        question_index = banks.position(question_index).unwrap_or(0);
+    }
+    let num_answered: usize = get_num_answered(&mode, &banks);
+
+    if let Some(patch_path) = &session.files.patch_out {
+        banks.write_patch(patch_path)?; // fails before the session starts with several files
+    }
+
+    let proctor_code = match session.proctored {
+        true => Some(config.proctor_code.clone().ok_or_eyre(
+            "a proctored session needs a proctor_code in the config file to allow quitting early",
+        )?),
+        false => None,
+    };
+    if proctor_code.is_some() {
+        banks.start_exam(&proctor::now());
+    }
+
+    let events = match session.event_log || config.event_log || session.proctored {
+        true => {
+            let log_dir = events::log_dir(config.profile.as_deref())
+                .ok_or_eyre("no directory to keep the event log in")?;
+            Some(events::EventLog::create(&log_dir)?)
+        }
+        false => None,
     };
-    let data = fs::read_to_string(&args.json_path)
-        .with_context(|| format!("could not read file: {}", &args.json_path.display()))?;
-    let questions: Questions = serde_json::from_str(&data).wrap_err("JSON not parsable")?;
-    let num_answered: usize = get_num_answered(&mode, &questions);
+
+    // absolute, so `history` can tell banks with the same name apart
+    let files: Vec<_> = banks
+        .paths()
+        .map(|path| {
+            let path = std::fs::canonicalize(path).unwrap_or(path.to_path_buf());
+            path.display().to_string()
+        })
+        .collect();
+    let hooks = hooks::Hooks::new(
+        &config.hooks,
+        json!({"files": files, "mode": mode.name(), "rater_id": config.rater_id}),
+    )?;
 
     let mut terminal = tui::init()?;
 
-    let mut app: App = App::new(
-        args.json_path,
-        questions,
-        0,
-        mode,
-        "".to_string(),
-        false,
-        num_answered,
-    );
+    let mut app: App = App::new(banks, question_index, mode, message, num_answered, config);
+    app.dirty = prefilled > 0 || subsetted;
+    app.patch_out = session.files.patch_out;
+    app.events = events;
+    app.hooks = hooks;
+    app.proctor_code = proctor_code;
+    app.blinded = session.blinded;
+    // the state has indexes among all the questions, filtered out or not
+    app.bookmarks = state
+        .bookmarks
+        .iter()
+        .filter_map(|&index| app.banks.position(index))
+        .collect();
+    app.show_metadata = state.show_metadata;
+    app.show_sidebar = state.show_sidebar;
+    app.notes = notes::Notes::load(app.banks.paths(), notes::user(&app.config).as_deref())?;
+    app.live = live_client;
+    if app.config.track_time {
+        app.timer = Some(timing::Timer::start(&mut app.banks));
+    }
+    let start = json!({
+        "files": files,
+        "mode": mode.name(),
+        "rater_id": app.config.rater_id,
+        "answered": app.num_answered,
+        "total": app.banks.len(),
+    });
+    app.log_event("start", start)?;
 
     app.run(&mut terminal)?;
     tui::restore()?;
+    app.hooks.finish();
+
+    recent::record(
+        &app.config,
+        recent::Recent {
+            files: files.iter().map(PathBuf::from).collect(),
+            mode,
+            answered: app.num_answered,
+            total: app.banks.len(),
+            ended: proctor::now(),
+        },
+    )?;
+    state.question = app.banks.absolute(app.question_index);
+    // bookmarks on questions filtered out this time are kept
+    state
+        .bookmarks
+        .retain(|&index| app.banks.position(index).is_none());
+    state
+        .bookmarks
+        .extend(app.bookmarks.iter().map(|&index| app.banks.absolute(index)));
+    state.show_metadata = app.show_metadata;
+    state.show_sidebar = app.show_sidebar;
+    state.save()?;
+
+    if let Some(url) = &session.submit_url {
+        if app.num_answered < app.banks.len() {
+            eprintln!("{}", t!("submit.unfinished"));
+            return Ok(());
+        }
+        let token = env::var("QUESTION_CLI_SUBMIT_TOKEN")
+            .ok()
+            .or(app.config.submit_token.clone());
+        for json_path in app.banks.paths() {
+            remote::submit(url, token.as_deref(), json_path)?;
+            eprintln!("{}", t!("submit.done", path = json_path.display()));
+        }
+    }
     Ok(())
 }
@@ -0,0 +1,115 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{eyre::WrapErr, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    bank::{Bank, Question},
+    i18n::t,
+};
+
+// A manifest (questions.manifest.json next to questions.json, made with
+// `question_cli manifest`) records sha256 checksums of a bank before it's sent
+// out, so each rater's copy can be checked for changes in transit:
+//
+// { "sha256": "<the whole file>", "questions": ["<question 1>", ...] }
+//
+// A question's checksum covers what raters see (the stem, options, answer key,
+// image_path and metadata) and not the responses, so it still matches once
+// answered. The whole file checksum only matches until the first save, it's
+// checked when there are no per question checksums.
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Manifest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub questions: Vec<String>,
+}
+
+/// questions.json => questions.manifest.json
+pub fn manifest_path(json_path: &Path) -> PathBuf {
+    json_path.with_extension("manifest.json")
+}
+
+/// Writes the manifest of a bank as it is on disk now
+pub fn write(json_path: &Path, bank: &Bank) -> Result<PathBuf> {
+    let manifest = Manifest {
+        sha256: Some(file_checksum(json_path)?),
+        questions: bank.questions.iter().map(question_checksum).collect(),
+    };
+    let manifest_path = manifest_path(json_path);
+    let data = serde_json::to_string_pretty(&manifest)? + "\n";
+    fs::write(&manifest_path, data)
+        .wrap_err_with(|| format!("could not write {}", manifest_path.display()))?;
+    Ok(manifest_path)
+}
+
+/// A warning when the bank doesn't match its manifest, nothing when it does or
+/// there is no manifest
+pub fn check(json_path: &Path, bank: &Bank) -> Result<Option<String>> {
+    let manifest_path = manifest_path(json_path);
+    if !manifest_path.exists() {
+        return Ok(None);
+    }
+    let data = fs::read_to_string(&manifest_path)
+        .wrap_err_with(|| format!("could not read {}", manifest_path.display()))?;
+    let manifest: Manifest = serde_json::from_str(&data)
+        .wrap_err_with(|| format!("invalid manifest {}", manifest_path.display()))?;
+    let path = json_path.display();
+    if !manifest.questions.is_empty() {
+        if manifest.questions.len() != bank.questions.len() {
+            return Ok(Some(t!(
+                "manifest.count",
+                path = path,
+                expected = manifest.questions.len(),
+                found = bank.questions.len()
+            )));
+        }
+        let changed: Vec<String> = bank
+            .questions
+            .iter()
+            .zip(&manifest.questions)
+            .enumerate()
+            .filter(|(_, (question, checksum))| question_checksum(question) != **checksum)
+            .map(|(i, _)| (i + 1).to_string())
+            .collect();
+        return Ok(match changed.is_empty() {
+            true => None,
+            false => Some(t!(
+                "manifest.questions",
+                path = path,
+                questions = changed.join(", ")
+            )),
+        });
+    }
+    match manifest.sha256 {
+        Some(sha256) if sha256 != file_checksum(json_path)? => {
+            Ok(Some(t!("manifest.file", path = path)))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn file_checksum(json_path: &Path) -> Result<String> {
+    let data =
+        fs::read(json_path).wrap_err_with(|| format!("could not read {}", json_path.display()))?;
+    Ok(hex::encode(Sha256::digest(data)))
+}
+
+fn question_checksum(question: &Question) -> String {
+    let content = json!({
+        "question": question.question,
+        "options": question.options,
+        "answer": question.answer,
+        "image_path": question.image_path,
+        "metadata": question.metadata,
+    });
+    hex::encode(Sha256::digest(content.to_string()))
+}
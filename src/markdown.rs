@@ -0,0 +1,436 @@
+use std::sync::OnceLock;
+
+use pulldown_cmark::{
+    Alignment as ColumnAlignment, CodeBlockKind, Event, Options, Parser, Tag, TagEnd,
+};
+use ratatui::{
+    layout::{Alignment, Constraint, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Cell, Paragraph, Row, Table},
+    Frame,
+};
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{FontStyle, ThemeSet},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+
+use crate::{math, text};
+
+// loading the bundled syntaxes/themes is slow, so only do it once
+static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+const CODE_THEME: &str = "base16-ocean.dark";
+
+// A rendered piece of Markdown. Tables get their own segment so they can be
+// drawn with ratatui's Table widget instead of being wrapped as plain text.
+pub enum Segment {
+    Text(Vec<Line<'static>>),
+    Table(TableData),
+    // text wrapped behind a prefix, see text::wrap_hanging (an option and its number)
+    Hanging(Span<'static>, Vec<Line<'static>>),
+}
+
+pub struct TableData {
+    header: Vec<Line<'static>>,
+    rows: Vec<Vec<Line<'static>>>,
+}
+
+/// Converts a Markdown string (question stem or option) into styled segments.
+/// Only the basics our authors use are supported: bold, italics, strikethrough,
+/// inline code, headings, (nested) bullet/numbered lists, pipe tables and fenced
+/// code blocks, which get syntax highlighting when a language is given (```python).
+/// $inline$ and $$display$$ math is converted to Unicode by the math module.
+pub fn to_segments(src: &str, base: Style) -> Vec<Segment> {
+    let src = text::normalize(src);
+    let mut renderer = Renderer::new(base);
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES | Options::ENABLE_MATH;
+    for event in Parser::new_ext(&src, options) {
+        renderer.handle(event);
+    }
+    renderer.finish()
+}
+
+/// Same as to_segments() but flattened to lines, with table rows joined by "│".
+/// Used where there is no room for a table widget (ie. the options).
+pub fn to_lines(src: &str, base: Style) -> Vec<Line<'static>> {
+    to_segments(src, base)
+        .into_iter()
+        .flat_map(|segment| match segment {
+            Segment::Text(lines) => lines,
+            Segment::Hanging(prefix, mut lines) => {
+                match lines.first_mut() {
+                    Some(line) => line.spans.insert(0, prefix),
+                    None => lines.push(Line::from(prefix)),
+                }
+                lines
+            }
+            Segment::Table(table) => std::iter::once(table.header)
+                .chain(table.rows)
+                .map(|cells| {
+                    let mut spans = vec![];
+                    for (i, cell) in cells.into_iter().enumerate() {
+                        if i > 0 {
+                            spans.push(Span::raw(" │ "));
+                        }
+                        spans.extend(cell.spans);
+                    }
+                    Line::from(spans)
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Markdown as HTML for `question_cli serve`, with math converted to Unicode
+/// like in the terminal
+pub fn to_html(src: &str) -> String {
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES | Options::ENABLE_MATH;
+    let events = Parser::new_ext(src, options).map(|event| match event {
+        Event::InlineMath(latex) => Event::Text(math::to_unicode(&latex).into()),
+        Event::DisplayMath(latex) => Event::Text(math::to_unicode(&latex).into()),
+        event => event,
+    });
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, events);
+    html
+}
+
+/// Draws segments top to bottom inside area, the first scroll rows scrolled out
+/// of view. Like a single Paragraph, anything that does not fit is cut off at
+/// the bottom. Returns how far it can be scrolled, 0 when everything fits.
+pub fn render_segments(frame: &mut Frame, area: Rect, segments: Vec<Segment>, scroll: u16) -> u16 {
+    // text is wrapped by the text module rather than Paragraph so CJK/emoji widths are right
+    let segments: Vec<Segment> = segments
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(lines) => Segment::Text(text::wrap(lines, area.width)),
+            Segment::Hanging(prefix, lines) => {
+                Segment::Text(text::wrap_hanging(prefix, lines, area.width))
+            }
+            table => table,
+        })
+        .collect();
+    let height = |segment: &Segment| match segment {
+        Segment::Text(lines) | Segment::Hanging(_, lines) => lines.len() as u16,
+        Segment::Table(table) => table.rows.len() as u16 + 2, // header + its margin
+    };
+    let max_scroll = segments
+        .iter()
+        .map(height)
+        .sum::<u16>()
+        .saturating_sub(area.height);
+    let mut skip = scroll.min(max_scroll);
+    let mut y = area.y;
+    for segment in segments {
+        let remaining = area.bottom().saturating_sub(y);
+        if remaining == 0 {
+            break;
+        }
+        let full_height = height(&segment);
+        if skip >= full_height {
+            skip -= full_height;
+            continue;
+        }
+        let height = match segment {
+            Segment::Text(lines) | Segment::Hanging(_, lines) => {
+                let lines: Vec<Line> = lines.into_iter().skip(skip as usize).collect();
+                let height = (lines.len() as u16).min(remaining);
+                let widget_area = Rect::new(area.x, y, area.width, height);
+                frame.render_widget(Paragraph::new(lines), widget_area);
+                height
+            }
+            // tables only scroll out of view whole
+            Segment::Table(table) => {
+                let height = full_height.min(remaining);
+                let widget_area = Rect::new(area.x, y, area.width, height);
+                frame.render_widget(table.into_widget(), widget_area);
+                height
+            }
+        };
+        skip = 0;
+        y += height;
+    }
+    max_scroll
+}
+
+impl TableData {
+    fn into_widget(self) -> Table<'static> {
+        // size every column to its widest cell
+        let columns = self.header.len();
+        let widths = (0..columns)
+            .map(|i| {
+                let widest = std::iter::once(&self.header)
+                    .chain(&self.rows)
+                    .filter_map(|row| row.get(i))
+                    .map(|cell| cell.width())
+                    .max()
+                    .unwrap_or(0);
+                Constraint::Length(widest as u16)
+            })
+            .collect::<Vec<_>>();
+        let header = Row::new(self.header.into_iter().map(Cell::from))
+            .style(Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED))
+            .bottom_margin(1);
+        let rows = self
+            .rows
+            .into_iter()
+            .map(|row| Row::new(row.into_iter().map(Cell::from)));
+        Table::new(rows, widths).header(header).column_spacing(3)
+    }
+}
+
+// Walks the pulldown_cmark events and builds up ratatui lines
+struct Renderer {
+    styles: Vec<Style>,      // stack of styles, top is the currently active one
+    lists: Vec<Option<u64>>, // stack of open lists, Some(n) for numbered lists
+    segments: Vec<Segment>,
+    lines: Vec<Line<'static>>,
+    current: Vec<Span<'static>>,
+    in_item: bool,                  // true right after a list prefix was pushed
+    code: Option<(String, String)>, // (language, source) of the open code block
+    table: Option<(Vec<ColumnAlignment>, TableData)>, // the table being parsed
+    row: Vec<Line<'static>>,        // cells of the table row being parsed
+}
+
+impl Renderer {
+    fn new(base: Style) -> Renderer {
+        Renderer {
+            styles: vec![base],
+            lists: vec![],
+            segments: vec![],
+            lines: vec![],
+            current: vec![],
+            in_item: false,
+            code: None,
+            table: None,
+            row: vec![],
+        }
+    }
+
+    fn style(&self) -> Style {
+        *self.styles.last().expect("base style is never popped")
+    }
+
+    fn push_style(&mut self, patch: Style) {
+        self.styles.push(self.style().patch(patch));
+    }
+
+    fn pop_style(&mut self) {
+        if self.styles.len() > 1 {
+            self.styles.pop();
+        }
+    }
+
+    fn push_text(&mut self, text: String) {
+        self.in_item = false;
+        self.current.push(Span::styled(text, self.style()));
+    }
+
+    fn flush_line(&mut self) {
+        if !self.current.is_empty() {
+            self.lines
+                .push(Line::from(std::mem::take(&mut self.current)));
+        }
+        self.in_item = false;
+    }
+
+    // blank line between top level blocks, like "\n\n" in the source
+    fn end_block(&mut self) {
+        self.flush_line();
+        if self.lists.is_empty() {
+            self.lines.push(Line::from(""));
+        }
+    }
+
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => match &mut self.code {
+                Some((_, source)) => source.push_str(&text),
+                None => self.push_text(text.into_string()),
+            },
+            Event::Code(code) => {
+                self.push_style(Style::default().fg(Color::LightMagenta));
+                self.push_text(code.into_string());
+                self.pop_style();
+            }
+            Event::InlineMath(latex) => self.push_text(math::to_unicode(&latex)),
+            Event::DisplayMath(latex) => {
+                self.flush_line();
+                self.push_text(math::to_unicode(&latex));
+                self.flush_line();
+            }
+            Event::SoftBreak => self.push_text(" ".to_string()),
+            Event::HardBreak => self.flush_line(),
+            Event::Rule => {
+                self.flush_line();
+                self.lines.push(Line::styled("─".repeat(20), self.style()));
+                self.end_block();
+            }
+            Event::Html(html) | Event::InlineHtml(html) => self.push_text(html.into_string()),
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph if !self.in_item => self.flush_line(),
+            Tag::Heading { .. } => {
+                self.flush_line();
+                self.push_style(Style::default().add_modifier(Modifier::BOLD));
+            }
+            Tag::Emphasis => self.push_style(Style::default().add_modifier(Modifier::ITALIC)),
+            Tag::Strong => self.push_style(Style::default().add_modifier(Modifier::BOLD)),
+            Tag::Strikethrough => {
+                self.push_style(Style::default().add_modifier(Modifier::CROSSED_OUT))
+            }
+            Tag::CodeBlock(kind) => {
+                self.flush_line();
+                let lang = match kind {
+                    CodeBlockKind::Fenced(lang) => lang.to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code = Some((lang, String::new()));
+            }
+            Tag::Table(alignments) => {
+                self.flush_line();
+                let table = TableData {
+                    header: vec![],
+                    rows: vec![],
+                };
+                self.table = Some((alignments, table));
+            }
+            Tag::List(start) => {
+                self.flush_line();
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.flush_line();
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let bullet = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}{}. ", indent, *n - 1)
+                    }
+                    _ => format!("{}• ", indent),
+                };
+                self.current.push(Span::styled(bullet, self.style()));
+                self.in_item = true;
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => self.end_block(),
+            TagEnd::Heading(_) => {
+                self.pop_style();
+                self.end_block();
+            }
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough => self.pop_style(),
+            TagEnd::CodeBlock => {
+                if let Some((lang, source)) = self.code.take() {
+                    self.lines.extend(highlight_code(&source, &lang));
+                }
+                self.end_block();
+            }
+            TagEnd::TableCell => {
+                let alignment = match self
+                    .table
+                    .as_ref()
+                    .and_then(|(alignments, _)| alignments.get(self.row.len()))
+                {
+                    Some(ColumnAlignment::Center) => Alignment::Center,
+                    Some(ColumnAlignment::Right) => Alignment::Right,
+                    _ => Alignment::Left,
+                };
+                let cell = Line::from(std::mem::take(&mut self.current)).alignment(alignment);
+                self.row.push(cell);
+            }
+            TagEnd::TableHead => {
+                if let Some((_, table)) = &mut self.table {
+                    table.header = std::mem::take(&mut self.row);
+                }
+            }
+            TagEnd::TableRow => {
+                if let Some((_, table)) = &mut self.table {
+                    table.rows.push(std::mem::take(&mut self.row));
+                }
+            }
+            TagEnd::Table => {
+                if let Some((_, table)) = self.table.take() {
+                    self.segments
+                        .push(Segment::Text(std::mem::take(&mut self.lines)));
+                    self.segments.push(Segment::Table(table));
+                }
+                self.end_block();
+            }
+            TagEnd::List(_) => {
+                self.lists.pop();
+                self.end_block();
+            }
+            TagEnd::Item => self.flush_line(),
+            _ => {}
+        }
+    }
+
+    fn finish(mut self) -> Vec<Segment> {
+        self.flush_line();
+        // drop the trailing blank line(s) from end_block()
+        while self.lines.last().is_some_and(|line| line.width() == 0) {
+            self.lines.pop();
+        }
+        self.segments.push(Segment::Text(self.lines));
+        self.segments
+    }
+}
+
+/// Highlights a code block with syntect. Every line gets a "│ " gutter so the
+/// block stands out from the stem (and so wrapping never eats the indentation).
+/// Unknown languages fall back to plain text on the same background.
+fn highlight_code(source: &str, lang: &str) -> Vec<Line<'static>> {
+    let syntaxes = SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines);
+    let theme = &THEMES.get_or_init(ThemeSet::load_defaults).themes[CODE_THEME];
+    let syntax = syntaxes
+        .find_syntax_by_token(lang)
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+    let background = theme
+        .settings
+        .background
+        .map(|c| Color::Rgb(c.r, c.g, c.b))
+        .unwrap_or(Color::Black);
+    let block_style = Style::default().bg(background);
+
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    LinesWithEndings::from(source)
+        .map(|line| {
+            let mut spans = vec![Span::styled("│ ", block_style.fg(Color::DarkGray))];
+            match highlighter.highlight_line(line, syntaxes) {
+                Ok(ranges) => spans.extend(ranges.into_iter().map(|(style, text)| {
+                    let mut span_style = block_style.fg(Color::Rgb(
+                        style.foreground.r,
+                        style.foreground.g,
+                        style.foreground.b,
+                    ));
+                    if style.font_style.contains(FontStyle::BOLD) {
+                        span_style = span_style.add_modifier(Modifier::BOLD);
+                    }
+                    if style.font_style.contains(FontStyle::ITALIC) {
+                        span_style = span_style.add_modifier(Modifier::ITALIC);
+                    }
+                    Span::styled(text.trim_end_matches(['\r', '\n']).to_string(), span_style)
+                })),
+                Err(_) => spans.push(Span::styled(
+                    line.trim_end_matches(['\r', '\n']).to_string(),
+                    block_style,
+                )),
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
@@ -0,0 +1,282 @@
+/// Converts a small subset of LaTeX (what shows up in pharmacology calculations)
+/// to plain Unicode: Greek letters, common operators, sub/superscripts, \frac,
+/// \sqrt and \text. Anything unknown is left as written.
+pub fn to_unicode(latex: &str) -> String {
+    let chars: Vec<char> = latex.chars().collect();
+    let mut parser = Parser { chars, pos: 0 };
+    parser.parse_until(None)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.pos += 1;
+        c
+    }
+
+    // converts everything up to the closing char (or the end)
+    fn parse_until(&mut self, close: Option<char>) -> String {
+        let mut out = String::new();
+        while let Some(c) = self.next() {
+            match c {
+                c if Some(c) == close => break,
+                '\\' => out.push_str(&self.command()),
+                '^' => {
+                    let arg = self.argument();
+                    out.push_str(&script(&arg, true));
+                }
+                '_' => {
+                    let arg = self.argument();
+                    out.push_str(&script(&arg, false));
+                }
+                '{' => out.push_str(&self.parse_until(Some('}'))),
+                '}' => {}
+                '~' => out.push(' '),
+                c => out.push(c),
+            }
+        }
+        out
+    }
+
+    // a {group}, a \command or a single char
+    fn argument(&mut self) -> String {
+        while self.peek() == Some(' ') {
+            self.pos += 1;
+        }
+        match self.next() {
+            Some('{') => self.parse_until(Some('}')),
+            Some('\\') => self.command(),
+            Some(c) => c.to_string(),
+            None => String::new(),
+        }
+    }
+
+    // called right after a backslash
+    fn command(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(c) = self.peek().filter(char::is_ascii_alphabetic) {
+            name.push(c);
+            self.pos += 1;
+        }
+        if name.is_empty() {
+            // escaped char or spacing command like \, or \;
+            return match self.next() {
+                Some(',' | ';' | ':' | ' ' | '!') => " ".to_string(),
+                Some('\\') => " ".to_string(),
+                Some(c) => c.to_string(),
+                None => "\\".to_string(),
+            };
+        }
+        match name.as_str() {
+            "frac" | "dfrac" | "tfrac" => {
+                let (num, den) = (self.argument(), self.argument());
+                format!("{}/{}", parenthesize(&num), parenthesize(&den))
+            }
+            "sqrt" => format!("√{}", parenthesize(&self.argument())),
+            "text" | "mathrm" | "mathit" | "mathbf" | "textrm" | "operatorname" => self.argument(),
+            "left" | "right" => String::new(), // \left( is just (
+            _ => match symbol(&name) {
+                Some(symbol) => symbol.to_string(),
+                None => format!("\\{}", name),
+            },
+        }
+    }
+}
+
+// wraps multi-term expressions so a/b stays unambiguous
+fn parenthesize(expr: &str) -> String {
+    if expr.chars().all(|c| c.is_alphanumeric() || c == '.') {
+        expr.to_string()
+    } else {
+        format!("({})", expr)
+    }
+}
+
+// Unicode super/subscripts only exist for some chars, when any are missing
+// fall back to ^(...) / _(...) instead of mixing sizes
+fn script(arg: &str, superscript: bool) -> String {
+    if superscript && arg == "∘" {
+        return "°".to_string(); // ^\circ is how degrees are written
+    }
+    let table = if superscript {
+        SUPERSCRIPTS
+    } else {
+        SUBSCRIPTS
+    };
+    let mapped: Option<String> = arg
+        .chars()
+        .map(|c| match c {
+            '/' | ' ' => Some(c),
+            c => table.iter().find(|(from, _)| *from == c).map(|(_, to)| *to),
+        })
+        .collect();
+    match mapped {
+        Some(mapped) if !arg.is_empty() => mapped,
+        _ => {
+            let marker = if superscript { '^' } else { '_' };
+            if arg.chars().count() == 1 {
+                format!("{}{}", marker, arg)
+            } else {
+                format!("{}({})", marker, arg)
+            }
+        }
+    }
+}
+
+fn symbol(name: &str) -> Option<&'static str> {
+    let symbol = match name {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" | "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" | "vartheta" => "θ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "rho" => "ρ",
+        "sigma" => "σ",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" | "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        "times" => "×",
+        "cdot" => "·",
+        "div" => "÷",
+        "pm" => "±",
+        "mp" => "∓",
+        "le" | "leq" => "≤",
+        "ge" | "geq" => "≥",
+        "ll" => "≪",
+        "gg" => "≫",
+        "ne" | "neq" => "≠",
+        "approx" => "≈",
+        "sim" => "~",
+        "equiv" => "≡",
+        "propto" => "∝",
+        "infty" => "∞",
+        "to" | "rightarrow" => "→",
+        "leftarrow" => "←",
+        "leftrightarrow" | "rightleftharpoons" => "⇌",
+        "uparrow" => "↑",
+        "downarrow" => "↓",
+        "circ" => "∘",
+        "degree" => "°",
+        "sum" => "Σ",
+        "prod" => "Π",
+        "partial" => "∂",
+        "ln" => "ln",
+        "log" => "log",
+        "exp" => "exp",
+        "quad" | "qquad" => "  ",
+        _ => return None,
+    };
+    Some(symbol)
+}
+
+const SUPERSCRIPTS: &[(char, char)] = &[
+    ('0', '⁰'),
+    ('1', '¹'),
+    ('2', '²'),
+    ('3', '³'),
+    ('4', '⁴'),
+    ('5', '⁵'),
+    ('6', '⁶'),
+    ('7', '⁷'),
+    ('8', '⁸'),
+    ('9', '⁹'),
+    ('+', '⁺'),
+    ('-', '⁻'),
+    ('−', '⁻'),
+    ('=', '⁼'),
+    ('(', '⁽'),
+    (')', '⁾'),
+    ('a', 'ᵃ'),
+    ('b', 'ᵇ'),
+    ('c', 'ᶜ'),
+    ('d', 'ᵈ'),
+    ('e', 'ᵉ'),
+    ('f', 'ᶠ'),
+    ('g', 'ᵍ'),
+    ('h', 'ʰ'),
+    ('i', 'ⁱ'),
+    ('j', 'ʲ'),
+    ('k', 'ᵏ'),
+    ('l', 'ˡ'),
+    ('m', 'ᵐ'),
+    ('n', 'ⁿ'),
+    ('o', 'ᵒ'),
+    ('p', 'ᵖ'),
+    ('r', 'ʳ'),
+    ('s', 'ˢ'),
+    ('t', 'ᵗ'),
+    ('u', 'ᵘ'),
+    ('v', 'ᵛ'),
+    ('w', 'ʷ'),
+    ('x', 'ˣ'),
+    ('y', 'ʸ'),
+    ('z', 'ᶻ'),
+];
+
+const SUBSCRIPTS: &[(char, char)] = &[
+    ('0', '₀'),
+    ('1', '₁'),
+    ('2', '₂'),
+    ('3', '₃'),
+    ('4', '₄'),
+    ('5', '₅'),
+    ('6', '₆'),
+    ('7', '₇'),
+    ('8', '₈'),
+    ('9', '₉'),
+    ('+', '₊'),
+    ('-', '₋'),
+    ('−', '₋'),
+    ('=', '₌'),
+    ('(', '₍'),
+    (')', '₎'),
+    ('a', 'ₐ'),
+    ('e', 'ₑ'),
+    ('h', 'ₕ'),
+    ('i', 'ᵢ'),
+    ('j', 'ⱼ'),
+    ('k', 'ₖ'),
+    ('l', 'ₗ'),
+    ('m', 'ₘ'),
+    ('n', 'ₙ'),
+    ('o', 'ₒ'),
+    ('p', 'ₚ'),
+    ('r', 'ᵣ'),
+    ('s', 'ₛ'),
+    ('t', 'ₜ'),
+    ('u', 'ᵤ'),
+    ('v', 'ᵥ'),
+    ('x', 'ₓ'),
+];
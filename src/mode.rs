@@ -0,0 +1,841 @@
+use clap::ValueEnum;
+use color_eyre::{eyre::bail, Report, Result};
+use ratatui::{
+    crossterm::event::KeyCode,
+    style::{Style, Stylize},
+    text::{Line, Span},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    bank::{Banks, Classification, Question},
+    compare, dimensions, drafts, grade,
+    i18n::t,
+    keymap::{self, Keymap},
+    markdown, plugins, proposals, review,
+    theme::Palette,
+};
+
+/// What the rater does with each question. Every mode keeps its responses in
+/// its own field of the question, so one bank can go through several modes.
+/// Adding a mode means adding a variant here and handling it in the methods
+/// below, App itself doesn't need to change.
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    /// Classify questions as higher order or not ("is_higher_order")
+    Classify,
+    /// Answer the questions ("human_answer")
+    #[default]
+    Answer,
+    /// Rate the quality of each question from 1 to 5 ("rating")
+    Rate,
+    /// Pick the final answer where the rater disagreed with the key ("adjudicated_answer")
+    Adjudicate,
+    /// Answer with the correct answer shown right away and a running score
+    Quiz,
+    /// Like quiz, with the explanation and reference shown after answering
+    Learn,
+    /// Classify questions on several axes set in the config, each in its own field
+    Dimensions,
+    /// Assign questions to a topic of the curriculum taxonomy ("topic")
+    Topic,
+    /// Triage the critiques of `review --llm` as valid, dismissed or unsure ("review_triage")
+    Review,
+    /// Approve or reject the distractors proposed by `distractors --llm` ("proposed_distractors")
+    Distractors,
+    /// Edit and accept the explanations drafted by `author --llm` ("draft_explanation")
+    Author,
+    /// Mark which of two models' answers are acceptable ("model_acceptable")
+    Compare,
+    /// Respond to items of a plugin's type, eg. a dose calculator ("plugin_response")
+    Plugin,
+}
+
+// Status line of a question, Missing is also shown in the question box title
+pub enum QStatus {
+    Missing(Span<'static>),
+    Done(Span<'static>),
+}
+
+impl QStatus {
+    // Method to extract the inner Span<'static>
+    pub fn get_span(&self) -> &Span<'static> {
+        match self {
+            QStatus::Missing(span) | QStatus::Done(span) => span,
+        }
+    }
+}
+
+impl Mode {
+    /// Name used on the command line and for the bank's "instructions"
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Classify => "classify",
+            Mode::Answer => "answer",
+            Mode::Rate => "rate",
+            Mode::Adjudicate => "adjudicate",
+            Mode::Quiz => "quiz",
+            Mode::Learn => "learn",
+            Mode::Dimensions => "dimensions",
+            Mode::Topic => "topic",
+            Mode::Review => "review",
+            Mode::Distractors => "distractors",
+            Mode::Author => "author",
+            Mode::Compare => "compare",
+            Mode::Plugin => "plugin",
+        }
+    }
+
+    /// The field of the question the responses are saved in
+    pub fn field(&self) -> &'static str {
+        match self {
+            Mode::Classify => "is_higher_order",
+            Mode::Answer | Mode::Quiz | Mode::Learn => "human_answer",
+            Mode::Rate => "rating",
+            Mode::Adjudicate => "adjudicated_answer",
+            // each axis has its own, see dimensions.rs
+            Mode::Dimensions => "dimensions",
+            Mode::Topic => "topic",
+            Mode::Review => "review_triage",
+            Mode::Distractors => "proposed_distractors",
+            Mode::Author => "draft_explanation",
+            Mode::Compare => "model_acceptable",
+            Mode::Plugin => plugins::FIELD,
+        }
+    }
+
+    /// Whether the rater is done with the question, counted by the progress bar.
+    /// Only disputed questions need adjudicating, reviewed ones triaging, those
+    /// with proposed distractors approving, drafted ones authoring, those two
+    /// models answered comparing and those of a plugin's type a response.
+    pub fn is_done(&self, question: &Question) -> bool {
+        if self.is_skipped(question) {
+            return true;
+        }
+        match self {
+            Mode::Classify => question.is_higher_order.is_some(),
+            Mode::Answer | Mode::Quiz | Mode::Learn => question.human_answer.is_some(),
+            Mode::Rate => question.rating.is_some(),
+            Mode::Adjudicate => question.adjudicated_answer.is_some() || !question.is_disputed(),
+            Mode::Dimensions => dimensions::all()
+                .iter()
+                .all(|dimension| dimensions::value(question, dimension).is_some()),
+            Mode::Topic => question.topic().is_some(),
+            Mode::Review => {
+                review::triage(question).is_some() || review::critique(question).is_none()
+            }
+            Mode::Distractors => proposals::pending(question).is_none(),
+            Mode::Author => drafts::draft(question).is_none(),
+            Mode::Compare => match compare::pair(question) {
+                Some((left, right)) => {
+                    compare::acceptable(question, &left).is_some()
+                        && compare::acceptable(question, &right).is_some()
+                }
+                None => true,
+            },
+            Mode::Plugin => match plugins::of(question) {
+                Some(plugin) => plugin
+                    .is_done(question, plugins::response(question))
+                    .unwrap_or_default(),
+                None => true,
+            },
+        }
+    }
+
+    /// The first question after index the rater isn't done with, from the
+    /// start again past the end, None once they're all done
+    pub fn next_missing(&self, banks: &Banks, index: usize) -> Option<usize> {
+        (index + 1..banks.len())
+            .chain(0..=index)
+            .find(|&next| !self.is_done(banks.question(next)))
+    }
+
+    /// The rater chose to leave the question blank rather than not getting to it
+    pub fn is_skipped(&self, question: &Question) -> bool {
+        question.skipped.iter().any(|field| field == self.field())
+    }
+
+    /// Marks the question as skipped, clearing any response. Not once a
+    /// quiz question is answered or for one with nothing to adjudicate, triage,
+    /// approve, author, compare or respond to with a plugin.
+    pub fn skip(&self, question: &mut Question) -> bool {
+        let answered_quiz =
+            matches!(self, Mode::Quiz | Mode::Learn) && question.human_answer.is_some();
+        let undisputed = *self == Mode::Adjudicate && !question.is_disputed();
+        let unreviewed = match self {
+            Mode::Review => review::critique(question).is_none(),
+            Mode::Distractors => proposals::all(question).is_empty(),
+            Mode::Author => drafts::draft(question).is_none(),
+            Mode::Compare => compare::pair(question).is_none(),
+            Mode::Plugin => plugins::of(question).is_none(),
+            _ => false,
+        };
+        if self.is_skipped(question) || answered_quiz || undisputed || unreviewed {
+            return false;
+        }
+        self.clear(question);
+        question.skipped.push(self.field().to_string());
+        true
+    }
+
+    /// Records the response for a key press, returns whether the question
+    /// changed. dimension is the axis being classified on in dimensions mode.
+    pub fn respond(
+        &self,
+        question: &mut Question,
+        code: KeyCode,
+        keymap: &Keymap,
+        dimension: usize,
+    ) -> bool {
+        if code == keymap.clear.0 {
+            return self.clear(question);
+        }
+        if code == keymap.skip.0 {
+            return self.skip(question);
+        }
+        match self {
+            Mode::Classify => {
+                let value = match code {
+                    code if code == keymap.classify_true.0 => Classification::HigherOrder,
+                    code if code == keymap.classify_false.0 => Classification::LowerOrder,
+                    code if code == keymap.unsure.0 => Classification::Unsure,
+                    // how sure the rater is, 5 for 50% to 0 for 100%, before or
+                    // after classifying
+                    KeyCode::Char(c @ ('0' | '5'..='9')) => {
+                        let confidence = match c {
+                            '0' => 100,
+                            c => c.to_digit(10).unwrap_or_default() as u8 * 10,
+                        };
+                        question.confidence = Some(confidence);
+                        return true;
+                    }
+                    _ => return false,
+                };
+                // a changed judgment, how sure of the old one doesn't apply
+                if question.is_higher_order.is_some_and(|old| old != value) {
+                    question.confidence = None;
+                }
+                question.is_higher_order = Some(value);
+            }
+            // no changing the answer once the correct one has been shown
+            Mode::Quiz | Mode::Learn if question.human_answer.is_some() => return false,
+            Mode::Answer | Mode::Quiz | Mode::Learn => match option_for_key(code, question) {
+                Some(option) => question.human_answer = Some(option),
+                None => return false,
+            },
+            Mode::Rate => match code {
+                KeyCode::Char(c @ '1'..='5') => question.rating = c.to_digit(10).map(|n| n as u8),
+                _ => return false,
+            },
+            Mode::Adjudicate if !question.is_disputed() => return false,
+            Mode::Adjudicate => match option_for_key(code, question) {
+                Some(option) => question.adjudicated_answer = Some(option),
+                None => return false,
+            },
+            Mode::Dimensions => {
+                let Some(dimension) = dimensions::all().get(dimension) else {
+                    return false;
+                };
+                let value = match code {
+                    KeyCode::Char(c @ '1'..='9') => dimension.values.get(c as usize - '1' as usize),
+                    _ => None,
+                };
+                match value {
+                    Some(value) => question
+                        .extra
+                        .insert(dimension.field.clone(), Value::from(value.as_str())),
+                    None => return false,
+                };
+            }
+            // topics are picked in the finder, see taxonomy.rs
+            Mode::Topic => return false,
+            Mode::Review if review::critique(question).is_none() => return false,
+            Mode::Review => {
+                let triage = match code {
+                    code if code == keymap.classify_true.0 => review::TRIAGE[0],
+                    code if code == keymap.classify_false.0 => review::TRIAGE[1],
+                    code if code == keymap.unsure.0 => review::TRIAGE[2],
+                    _ => return false,
+                };
+                question
+                    .extra
+                    .insert("review_triage".to_string(), Value::from(triage));
+            }
+            Mode::Distractors => {
+                let approve = match code {
+                    code if code == keymap.classify_true.0 => true,
+                    code if code == keymap.classify_false.0 => false,
+                    _ => return false,
+                };
+                if !proposals::decide(question, approve) {
+                    return false;
+                }
+            }
+            // the draft itself is edited in App, see keymap.edit
+            Mode::Author => {
+                let accept = match code {
+                    code if code == keymap.classify_true.0 => true,
+                    code if code == keymap.classify_false.0 => false,
+                    _ => return false,
+                };
+                if !drafts::decide(question, accept) {
+                    return false;
+                }
+            }
+            Mode::Compare => {
+                let judgment = match code {
+                    KeyCode::Char(c @ '1'..='4') => compare::JUDGMENTS[c as usize - '1' as usize],
+                    _ => return false,
+                };
+                if !compare::judge(question, judgment) {
+                    return false;
+                }
+            }
+            Mode::Plugin => {
+                let Some(plugin) = plugins::of(question) else {
+                    return false;
+                };
+                let key = keymap::Key(code).name();
+                match plugin.respond(question, plugins::response(question), &key) {
+                    Ok(Some(response)) if plugins::response(question) != Some(&response) => {
+                        question.extra.insert(plugins::FIELD.to_string(), response);
+                    }
+                    _ => return false,
+                }
+            }
+        }
+        question.skipped.retain(|field| field != self.field());
+        true
+    }
+
+    /// Clears the response, like it was never given. Not in quizzes, the
+    /// correct answer has been shown, or for an accepted or discarded draft.
+    pub fn clear(&self, question: &mut Question) -> bool {
+        let skipped = self.is_skipped(question);
+        question.skipped.retain(|field| field != self.field());
+        skipped
+            || match self {
+                Mode::Classify => {
+                    let confidence = question.confidence.take();
+                    question.is_higher_order.take().is_some() || confidence.is_some()
+                }
+                Mode::Answer => question.human_answer.take().is_some(),
+                Mode::Rate => question.rating.take().is_some(),
+                Mode::Adjudicate => question.adjudicated_answer.take().is_some(),
+                Mode::Quiz | Mode::Learn | Mode::Author => false,
+                Mode::Compare => compare::unjudge(question),
+                Mode::Dimensions => {
+                    dimensions::all()
+                        .iter()
+                        .filter_map(|dimension| question.extra.remove(&dimension.field))
+                        .count()
+                        > 0
+                }
+                Mode::Topic => question.extra.remove("topic").is_some(),
+                Mode::Review => question.extra.remove("review_triage").is_some(),
+                Mode::Distractors => proposals::undecide(question),
+                Mode::Plugin => question.extra.remove(plugins::FIELD).is_some(),
+            }
+    }
+
+    /// Records a response given as text, for `question_cli set`: an option
+    /// number or text, true/false when classifying, a rating from 1 to 5 or
+    /// axis=value for dimensions, a topic, valid/dismissed/unsure for a critique
+    /// approve/reject for each proposed distractor in order, eg. approve,reject,
+    /// accept/discard for a drafted explanation, left/right/both/neither for
+    /// the models' answers that are acceptable, or what the question's plugin
+    /// reads as a response
+    pub fn set_value(&self, question: &mut Question, value: &str) -> Result<()> {
+        match self {
+            Mode::Classify => {
+                question.is_higher_order = Some(match value.to_lowercase().as_str() {
+                    "true" => Classification::HigherOrder,
+                    "false" => Classification::LowerOrder,
+                    "unsure" => Classification::Unsure,
+                    _ => bail!("'{}' isn't true, false or unsure", value),
+                })
+            }
+            Mode::Answer | Mode::Quiz | Mode::Learn => {
+                question.human_answer = Some(option_for_value(question, value)?)
+            }
+            Mode::Rate => match value.parse() {
+                Ok(rating @ 1..=5) => question.rating = Some(rating),
+                _ => bail!("'{}' isn't a rating from 1 to 5", value),
+            },
+            Mode::Adjudicate => {
+                question.adjudicated_answer = Some(option_for_value(question, value)?)
+            }
+            Mode::Dimensions => {
+                let Some((field, value)) = value.split_once('=') else {
+                    bail!("'{}' isn't axis=value", value);
+                };
+                let Some(dimension) = dimensions::all().iter().find(|d| d.field == field) else {
+                    bail!("there's no dimension {} in the config", field);
+                };
+                if !dimension.values.iter().any(|v| v == value) {
+                    bail!(
+                        "'{}' isn't one of {}: {}",
+                        value,
+                        field,
+                        dimension.values.join(", ")
+                    );
+                }
+                question.extra.insert(field.to_string(), Value::from(value));
+            }
+            Mode::Topic => question.set_topic(value),
+            Mode::Review => {
+                if !review::TRIAGE.contains(&value) {
+                    bail!("'{}' isn't {}", value, review::TRIAGE.join(", "));
+                }
+                question
+                    .extra
+                    .insert("review_triage".to_string(), Value::from(value));
+            }
+            Mode::Distractors => {
+                let count = proposals::all(question).len();
+                let decisions: Vec<&str> = value.split(',').map(str::trim).collect();
+                if decisions.len() > count {
+                    bail!(
+                        "there are {} proposed distractors, not {}",
+                        count,
+                        decisions.len()
+                    );
+                }
+                proposals::undecide(question);
+                for decision in decisions {
+                    let approve = match decision {
+                        "approve" => true,
+                        "reject" => false,
+                        _ => bail!("'{}' isn't approve or reject", decision),
+                    };
+                    proposals::decide(question, approve);
+                }
+            }
+            Mode::Author => {
+                let accept = match value {
+                    "accept" => true,
+                    "discard" => false,
+                    _ => bail!("'{}' isn't accept or discard", value),
+                };
+                if !drafts::decide(question, accept) {
+                    bail!("there's no drafted explanation to {}", value);
+                }
+            }
+            Mode::Compare => {
+                if !compare::JUDGMENTS.contains(&value) {
+                    bail!("'{}' isn't {}", value, compare::JUDGMENTS.join(", "));
+                }
+                if !compare::judge(question, value) {
+                    bail!(
+                        "fewer than two models answered the question, there's nothing to compare"
+                    );
+                }
+            }
+            Mode::Plugin => {
+                let Some(plugin) = plugins::of(question) else {
+                    bail!("the question's \"type\" has no plugin, there's nothing to respond to");
+                };
+                let response = plugin.parse(question, value)?;
+                question.extra.insert(plugins::FIELD.to_string(), response);
+            }
+        }
+        question.skipped.retain(|field| field != self.field());
+        Ok(())
+    }
+
+    /// Style of an option, ie. highlighting the rater's pick
+    pub fn option_style(&self, question: &Question, option: &str, palette: &Palette) -> Style {
+        let is = |value: &Option<String>| value.as_deref() == Some(option);
+        let selected = Style::default().fg(palette.selected).bold().underlined();
+        match self {
+            Mode::Answer if is(&question.human_answer) => selected,
+            // after answering, the correct option is shown and a wrong pick in red
+            Mode::Quiz | Mode::Learn
+                if question.human_answer.is_some() && option == question.answer =>
+            {
+                selected
+            }
+            Mode::Quiz | Mode::Learn if is(&question.human_answer) => {
+                Style::default().fg(palette.missing).bold()
+            }
+            Mode::Adjudicate if is(&question.adjudicated_answer) => selected,
+            Mode::Adjudicate if question.is_disputed() && is(&question.human_answer) => {
+                Style::default().fg(palette.missing)
+            }
+            Mode::Adjudicate if question.is_disputed() && option == question.answer => {
+                Style::default().fg(palette.info)
+            }
+            _ => Style::default().fg(palette.option),
+        }
+    }
+
+    /// Mode specific entries at the start of the controls bar, for a question
+    /// with that many options shown
+    pub fn controls(
+        &self,
+        keymap: &Keymap,
+        options: usize,
+        palette: &Palette,
+    ) -> Vec<Span<'static>> {
+        match self {
+            Mode::Classify => vec![
+                t!("controls.true").into(),
+                keymap.classify_true.label().fg(palette.action_key).bold(),
+                t!("controls.false").into(),
+                keymap.classify_false.label().fg(palette.action_key).bold(),
+                t!("controls.unsure").into(),
+                keymap.unsure.label().fg(palette.action_key).bold(),
+            ],
+            Mode::Answer | Mode::Quiz | Mode::Learn => vec![
+                t!("controls.enter_answer").into(),
+                option_keys(options).fg(palette.action_key).bold(),
+            ],
+            Mode::Rate => vec![
+                t!("controls.rate").into(),
+                "<1-5>".fg(palette.action_key).bold(),
+            ],
+            Mode::Dimensions => vec![
+                t!("controls.pick").into(),
+                "<1-9>".fg(palette.action_key).bold(),
+                t!("controls.next_dimension").into(),
+                keymap.dimension.label().fg(palette.action_key).bold(),
+            ],
+            Mode::Topic => vec![
+                t!("controls.topic").into(),
+                keymap.topic.label().fg(palette.action_key).bold(),
+            ],
+            Mode::Review => vec![
+                t!("controls.valid").into(),
+                keymap.classify_true.label().fg(palette.action_key).bold(),
+                t!("controls.dismiss").into(),
+                keymap.classify_false.label().fg(palette.action_key).bold(),
+                t!("controls.unsure").into(),
+                keymap.unsure.label().fg(palette.action_key).bold(),
+            ],
+            Mode::Distractors => vec![
+                t!("controls.approve").into(),
+                keymap.classify_true.label().fg(palette.action_key).bold(),
+                t!("controls.reject").into(),
+                keymap.classify_false.label().fg(palette.action_key).bold(),
+            ],
+            Mode::Author => vec![
+                t!("controls.edit").into(),
+                keymap.edit.label().fg(palette.action_key).bold(),
+                t!("controls.accept").into(),
+                keymap.classify_true.label().fg(palette.action_key).bold(),
+                t!("controls.discard").into(),
+                keymap.classify_false.label().fg(palette.action_key).bold(),
+            ],
+            Mode::Compare => vec![
+                t!("controls.acceptable").into(),
+                "<1-4>".fg(palette.action_key).bold(),
+            ],
+            Mode::Adjudicate => vec![
+                t!("controls.final_answer").into(),
+                option_keys(options).fg(palette.action_key).bold(),
+            ],
+            // the plugin's keys are in its panel
+            Mode::Plugin => vec![],
+        }
+    }
+
+    /// Is the question answered, classified... or does it still need an action?
+    pub fn status(&self, banks: &Banks, index: usize, palette: &Palette) -> QStatus {
+        let question = banks.question(index);
+        let missing = |key| QStatus::Missing(t!(key).fg(palette.missing).bold());
+        let done = |text: String| QStatus::Done(text.fg(palette.info));
+        if self.is_skipped(question) {
+            return done(t!("status.skipped").to_string());
+        }
+        match self {
+            Mode::Classify => match question.is_higher_order {
+                Some(classification) => {
+                    let value = classification_label(classification);
+                    let status = t!("status.classification", value = value);
+                    match question.confidence {
+                        Some(confidence) => done(format!(
+                            "{} {}",
+                            status,
+                            t!("status.confidence", percent = confidence)
+                        )),
+                        None => done(status),
+                    }
+                }
+                None => missing("status.missing_classification"),
+            },
+            Mode::Answer => match question.human_answer {
+                Some(_) => done("".to_string()),
+                None => missing("status.missing_answer"),
+            },
+            Mode::Rate => match question.rating {
+                Some(rating) => done(t!("status.rating", value = rating)),
+                None => missing("status.missing_rating"),
+            },
+            Mode::Dimensions => {
+                let missing: Vec<&str> = dimensions::all()
+                    .iter()
+                    .filter(|dimension| dimensions::value(question, dimension).is_none())
+                    .map(|dimension| dimension.field.as_str())
+                    .collect();
+                match missing.is_empty() {
+                    true => done(String::new()),
+                    false => QStatus::Missing(
+                        t!("status.missing_dimensions", axes = missing.join(", "))
+                            .fg(palette.missing)
+                            .bold(),
+                    ),
+                }
+            }
+            Mode::Topic => match question.topic() {
+                Some(topic) => done(t!("status.topic", topic = topic.replace('/', " › "))),
+                None => missing("status.missing_topic"),
+            },
+            Mode::Review => match review::triage(question) {
+                Some(triage) => done(t!("status.triage", value = review::label(triage))),
+                None if review::critique(question).is_some() => missing("status.missing_triage"),
+                None => done(t!("status.not_reviewed").to_string()),
+            },
+            Mode::Distractors => {
+                let decided = |status: &str| {
+                    proposals::all(question)
+                        .iter()
+                        .filter(|proposal| proposal["status"] == status)
+                        .count()
+                };
+                match proposals::pending(question) {
+                    Some(_) => missing("status.missing_approval"),
+                    None if proposals::all(question).is_empty() => {
+                        done(t!("status.no_proposals").to_string())
+                    }
+                    None => done(t!(
+                        "status.proposals",
+                        approved = decided("approved"),
+                        rejected = decided("rejected")
+                    )),
+                }
+            }
+            Mode::Author => match drafts::draft(question) {
+                Some(_) => missing("status.missing_authoring"),
+                None if question.explanation().is_some() => {
+                    done(t!("status.explained").to_string())
+                }
+                None => done(t!("status.no_draft").to_string()),
+            },
+            Mode::Compare => match compare::pair(question) {
+                Some((left, right)) => {
+                    let judged = |model: &str| compare::acceptable(question, model);
+                    match (judged(&left), judged(&right)) {
+                        (Some(true), Some(true)) => done(t!("status.both_acceptable").to_string()),
+                        (Some(false), Some(false)) => {
+                            done(t!("status.neither_acceptable").to_string())
+                        }
+                        (Some(true), Some(false)) => done(t!("status.acceptable", model = left)),
+                        (Some(false), Some(true)) => done(t!("status.acceptable", model = right)),
+                        _ => missing("status.missing_judgment"),
+                    }
+                }
+                None => done(t!("status.no_pair").to_string()),
+            },
+            Mode::Adjudicate => match &question.adjudicated_answer {
+                Some(answer) => done(t!("status.adjudication", value = answer)),
+                None if question.is_disputed() => missing("status.missing_adjudication"),
+                None => done(t!("status.no_dispute").to_string()),
+            },
+            Mode::Quiz | Mode::Learn => {
+                // in points, questions counting for their weight
+                let answered = banks.questions().filter(|q| q.human_answer.is_some());
+                let score = t!(
+                    "status.score",
+                    correct = grade::points(grade::weights(
+                        answered.clone().filter(|q| !q.is_disputed())
+                    )),
+                    answered = grade::points(grade::weights(answered))
+                );
+                match &question.human_answer {
+                    Some(_) if question.is_disputed() => done(format!(
+                        "{} {}",
+                        t!("status.incorrect", answer = question.answer),
+                        score
+                    )),
+                    Some(_) => done(format!("{} {}", t!("status.correct"), score)),
+                    None => missing("status.missing_answer"),
+                }
+            }
+            Mode::Plugin => {
+                let Some(plugin) = plugins::of(question) else {
+                    return done(t!("status.no_plugin").to_string());
+                };
+                let response = plugins::response(question);
+                let error =
+                    |err: Report| QStatus::Missing(format!("{:#}", err).fg(palette.missing).bold());
+                match (plugin.is_done(question, response), response) {
+                    (Ok(true), Some(response)) => match plugin.status(question, response) {
+                        Ok(status) => done(status),
+                        Err(err) => error(err),
+                    },
+                    (Ok(true), None) => done(String::new()),
+                    (Ok(false), _) => missing("status.missing_response"),
+                    (Err(err), _) => error(err),
+                }
+            }
+        }
+    }
+
+    /// Built in instructions for the right panel, used when the bank has none
+    pub fn instructions(&self) -> Vec<Line<'static>> {
+        let (title, body): (_, &[&str]) = match self {
+            Mode::Classify => (
+                t!("instructions.classify.title"),
+                &[
+                    t!("instructions.classify.higher_order"),
+                    t!("instructions.classify.lower_order"),
+                    t!("instructions.classify.confidence"),
+                ],
+            ),
+            Mode::Answer => (
+                t!("instructions.answer.title"),
+                &[t!("instructions.answer.how")],
+            ),
+            Mode::Rate => (
+                t!("instructions.rate.title"),
+                &[t!("instructions.rate.how")],
+            ),
+            Mode::Adjudicate => (
+                t!("instructions.adjudicate.title"),
+                &[
+                    t!("instructions.adjudicate.how"),
+                    t!("instructions.adjudicate.colours"),
+                ],
+            ),
+            Mode::Quiz => (
+                t!("instructions.quiz.title"),
+                &[t!("instructions.quiz.how")],
+            ),
+            Mode::Learn => (
+                t!("instructions.learn.title"),
+                &[t!("instructions.learn.how")],
+            ),
+            Mode::Dimensions => (
+                t!("instructions.dimensions.title"),
+                &[t!("instructions.dimensions.how")],
+            ),
+            Mode::Topic => (
+                t!("instructions.topic.title"),
+                &[t!("instructions.topic.how")],
+            ),
+            Mode::Review => (
+                t!("instructions.review.title"),
+                &[t!("instructions.review.how")],
+            ),
+            Mode::Distractors => (
+                t!("instructions.distractors.title"),
+                &[t!("instructions.distractors.how")],
+            ),
+            Mode::Author => (
+                t!("instructions.author.title"),
+                &[t!("instructions.author.how")],
+            ),
+            Mode::Compare => (
+                t!("instructions.compare.title"),
+                &[t!("instructions.compare.how")],
+            ),
+            Mode::Plugin => (
+                t!("instructions.plugin.title"),
+                &[t!("instructions.plugin.how")],
+            ),
+        };
+        let mut lines = vec![Line::from(title.bold()), Line::from("")];
+        lines.extend(body.iter().map(|text| Line::from(*text)));
+        lines
+    }
+}
+
+/// The explanation and reference of a question, for the right panel once
+/// it's answered in learn mode
+pub fn explanation(question: &Question) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(t!("explanation.title").bold()), Line::from("")];
+    match question.explanation() {
+        Some(explanation) => lines.extend(markdown::to_lines(explanation, Style::default())),
+        None => lines.push(Line::from(t!("explanation.none").italic())),
+    }
+    // why each distractor is wrong, for those that say
+    let distractors: Vec<(usize, usize, &str)> = question
+        .shown_options()
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, i)| question.options[i] != question.answer)
+        .filter_map(|(n, i)| Some((n, i, question.option_explanation(i)?)))
+        .collect();
+    if !distractors.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(t!("explanation.distractors").bold()));
+        for (n, i, text) in distractors {
+            lines.push(Line::from(
+                format!("{} - {}", n + 1, question.options[i]).italic(),
+            ));
+            lines.extend(markdown::to_lines(text, Style::default()));
+        }
+    }
+    let reference = question
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.reference.as_deref());
+    if let Some(reference) = reference {
+        lines.push(Line::from(""));
+        lines.push(Line::from(t!("metadata.reference").bold()));
+        lines.extend(markdown::to_lines(reference, Style::default()));
+    }
+    lines
+}
+
+// option given by its number or its text
+fn option_for_value(question: &Question, value: &str) -> Result<String> {
+    let by_number = value
+        .parse::<usize>()
+        .ok()
+        .and_then(|number| question.options.get(number.checked_sub(1)?));
+    match by_number.or_else(|| question.options.iter().find(|option| *option == value)) {
+        Some(option) => Ok(option.clone()),
+        None => bail!(
+            "'{}' isn't one of the {} options",
+            value,
+            question.options.len()
+        ),
+    }
+}
+
+/// The option a number key stands for, from 0: 1 is the first option and 0
+/// the tenth, for extended matching questions
+pub fn option_index(code: KeyCode) -> Option<usize> {
+    match code {
+        KeyCode::Char('0') => Some(9),
+        KeyCode::Char(c @ '1'..='9') => Some(c as usize - '1' as usize),
+        _ => None,
+    }
+}
+
+// the keys for that many options in the controls bar
+fn option_keys(options: usize) -> String {
+    match options {
+        0..=9 => format!("<1-{}>", options.max(1)),
+        _ => "<1-9, 0>".to_string(),
+    }
+}
+
+// option picked with the number keys, among those shown
+fn option_for_key(code: KeyCode, question: &Question) -> Option<String> {
+    let index = option_index(code)?;
+    let shown = question.shown_options();
+    Some(question.options[*shown.get(index)?].clone())
+}
+
+/// A classification in the rater's language
+pub fn classification_label(classification: Classification) -> &'static str {
+    match classification {
+        Classification::HigherOrder => t!("status.true"),
+        Classification::LowerOrder => t!("status.false"),
+        Classification::Unsure => t!("status.unsure"),
+    }
+}
@@ -0,0 +1,104 @@
+use std::{
+    collections::BTreeMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{eyre::WrapErr, Result};
+
+use crate::config::Config;
+
+// Personal notes on questions ("come back to this", a mnemonic...) are kept out
+// of the shared bank, in a file of their own next to it, one per user:
+// questions.json => questions.alice.notes.json for rater_id "alice" (or the
+// --profile or login name without one). Questions are identified by their "id" field, or
+// their number in the file, like in the audit log.
+//
+// { "q12": "check the 2024 guideline", "3": "mnemonic: MUDPILES" }
+
+/// Who the personal files are for: the rater_id, the --profile or the login
+/// name
+pub fn user(config: &Config) -> Option<String> {
+    config
+        .rater_id
+        .clone()
+        .or_else(|| config.profile.clone())
+        .or_else(|| env::var("USER").ok())
+        .or_else(|| env::var("USERNAME").ok())
+}
+
+/// A file of user's own next to the bank: questions.json =>
+/// questions.alice.<kind>.json
+pub fn personal_path(json_path: &Path, user: Option<&str>, kind: &str) -> PathBuf {
+    let name = json_path.file_name().unwrap_or_default().to_string_lossy();
+    let stem = match name.find(".json") {
+        Some(at) => &name[..at],
+        None => &name,
+    };
+    let name = match user {
+        Some(user) => format!("{}.{}.{}.json", stem, user, kind),
+        None => format!("{}.{}.json", stem, kind),
+    };
+    json_path.with_file_name(name)
+}
+
+#[derive(Debug, Default)]
+pub struct Notes {
+    user: Option<String>,
+    files: BTreeMap<PathBuf, BTreeMap<String, String>>, // by notes file
+}
+
+impl Notes {
+    /// The notes of user (see user()) on the banks, for those that have any
+    pub fn load<'a>(
+        json_paths: impl Iterator<Item = &'a Path>,
+        user: Option<&str>,
+    ) -> Result<Notes> {
+        let mut notes = Notes {
+            user: user.map(str::to_string),
+            files: BTreeMap::new(),
+        };
+        for json_path in json_paths {
+            let notes_path = notes.path(json_path);
+            let data = match fs::read_to_string(&notes_path) {
+                Ok(data) => data,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => {
+                    return Err(err)
+                        .wrap_err_with(|| format!("could not read {}", notes_path.display()))
+                }
+            };
+            let file = serde_json::from_str(&data)
+                .wrap_err_with(|| format!("{} isn't a notes file", notes_path.display()))?;
+            notes.files.insert(notes_path, file);
+        }
+        Ok(notes)
+    }
+
+    /// Where the notes on the bank at json_path are kept
+    pub fn path(&self, json_path: &Path) -> PathBuf {
+        personal_path(json_path, self.user.as_deref(), "notes")
+    }
+
+    pub fn get(&self, json_path: &Path, id: &str) -> Option<&str> {
+        let file = self.files.get(&self.path(json_path))?;
+        file.get(id).map(String::as_str)
+    }
+
+    /// Sets (or with an empty text, removes) a note and saves the notes file
+    pub fn set(&mut self, json_path: &Path, id: &str, text: &str) -> Result<()> {
+        let notes_path = self.path(json_path);
+        let text = text.trim_end();
+        if text.is_empty() && self.get(json_path, id).is_none() {
+            return Ok(()); // nothing written, no new file
+        }
+        let file = self.files.entry(notes_path.clone()).or_default();
+        match text.is_empty() {
+            true => file.remove(id),
+            false => file.insert(id.to_string(), text.to_string()),
+        };
+        let data = serde_json::to_string_pretty(file)? + "\n";
+        fs::write(&notes_path, data)
+            .wrap_err_with(|| format!("could not save the notes in {}", notes_path.display()))
+    }
+}
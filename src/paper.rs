@@ -0,0 +1,102 @@
+use std::io::{self, Write};
+
+use serde_json::Value;
+
+use crate::{bank::Banks, i18n::t, mode};
+
+// `question_cli print` writes the bank as a paper exam in Markdown, for sites
+// that still sit exams on paper. It reads fine as plain text and converts to
+// PDF or Word with eg. pandoc. Questions are numbered across all the files,
+// options are lettered (A, B, C...) and there's an optional answer sheet with a
+// row of boxes per question at the end. With --key it writes the answer key
+// for graders instead, as a table.
+
+/// A, B, C... for option i
+pub fn letter(i: usize) -> char {
+    (b'A' + i as u8) as char
+}
+
+pub fn print(
+    banks: &Banks,
+    title: &str,
+    answer_sheet: bool,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(out, "# {}\n", title)?;
+    writeln!(out, "{}\n", t!("paper.candidate"))?;
+    for (index, question) in banks.questions().enumerate() {
+        writeln!(out, "**{}.** {}\n", index + 1, question.question.trim())?;
+        if let Some(image_path) = banks.image_path(index) {
+            writeln!(out, "![]({})\n", image_path.display())?;
+        }
+        // two trailing spaces are a line break, the options stay on their own lines
+        let options: Vec<String> = question
+            .options
+            .iter()
+            .enumerate()
+            .map(|(i, option)| format!("{}. {}", letter(i), option.trim()))
+            .collect();
+        writeln!(out, "{}\n", options.join("  \n"))?;
+    }
+    if answer_sheet {
+        writeln!(out, "---\n\n## {}\n", t!("paper.answer_sheet"))?;
+        writeln!(out, "{}\n", t!("paper.candidate"))?;
+        let rows: Vec<String> = banks
+            .questions()
+            .enumerate()
+            .map(|(index, question)| {
+                let boxes: Vec<String> = (0..question.options.len())
+                    .map(|i| format!("[ ] {}", letter(i)))
+                    .collect();
+                format!("{:>3}.  {}", index + 1, boxes.join("   "))
+            })
+            .collect();
+        writeln!(out, "```\n{}\n```", rows.join("\n"))?;
+    }
+    Ok(())
+}
+
+pub fn print_key(banks: &Banks, title: &str, out: &mut impl Write) -> io::Result<()> {
+    // the id and classification columns only when the bank has them
+    let has_ids = banks.questions().any(|q| q.extra.contains_key("id"));
+    let has_classifications = banks.questions().any(|q| q.is_higher_order.is_some());
+    writeln!(out, "# {} ({})\n", title, t!("paper.key"))?;
+    let mut header = vec!["#"];
+    if has_ids {
+        header.push("ID");
+    }
+    header.push(t!("paper.answer"));
+    if has_classifications {
+        header.push(t!("paper.higher_order"));
+    }
+    writeln!(out, "| {} |", header.join(" | "))?;
+    writeln!(out, "|{}", " --- |".repeat(header.len()))?;
+    for (index, question) in banks.questions().enumerate() {
+        let mut row = vec![(index + 1).to_string()];
+        if has_ids {
+            row.push(match question.extra.get("id") {
+                Some(Value::String(id)) => id.clone(),
+                Some(id) => id.to_string(),
+                None => String::new(),
+            });
+        }
+        let answer = question
+            .options
+            .iter()
+            .position(|option| *option == question.answer)
+            .map(|i| format!("{}. {}", letter(i), question.answer))
+            .unwrap_or_default();
+        row.push(answer.replace('|', "\\|"));
+        if has_classifications {
+            row.push(
+                question
+                    .is_higher_order
+                    .map(mode::classification_label)
+                    .unwrap_or_default()
+                    .to_string(),
+            );
+        }
+        writeln!(out, "| {} |", row.join(" | "))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use std::io::{self, Write};
+
+use crate::{export::Rater, i18n::t};
+
+// `question_cli stats --plot R01.json R02.json ...` draws quick psychometric
+// checks in the terminal, without exporting: a histogram of the scores (number
+// right), and each question's item characteristic curve as a sparkline. For
+// the curves the copies are split into up to 5 bands by their score, lowest
+// first, and each bar is the proportion of the band who got the question
+// right. A good question rises left to right, a flat or falling one doesn't
+// tell the stronger raters apart.
+
+const BANDS: usize = 5;
+const WIDTH: usize = 40; // of the longest histogram bar
+const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub fn print(raters: &[Rater], out: &mut impl Write) -> io::Result<()> {
+    let Some(first) = raters.first() else {
+        return Ok(());
+    };
+    let scored: Vec<usize> = (0..first.bank.questions.len())
+        .filter(|&i| !first.bank.questions[i].answer.is_empty())
+        .collect();
+    let right = |rater: &Rater, i: usize| rater.bank.questions[i].is_correct() == Some(true);
+    let totals: Vec<usize> = raters
+        .iter()
+        .map(|rater| scored.iter().filter(|&&i| right(rater, i)).count())
+        .collect();
+
+    // after the numbers of stats
+    writeln!(out)?;
+    writeln!(out, "{}", t!("plots.scores"))?;
+    let mut counts = vec![0; scored.len() + 1];
+    for &total in &totals {
+        counts[total] += 1;
+    }
+    let most = counts.iter().copied().max().unwrap_or(0).max(1);
+    let width = scored.len().to_string().len();
+    for (score, &count) in counts.iter().enumerate() {
+        let bar = "█".repeat((count * WIDTH).div_ceil(most));
+        writeln!(out, "  {:>width$} │{} {}", score, bar, count, width = width)?;
+    }
+    writeln!(out)?;
+
+    // copies by score, split in bands of (nearly) the same size
+    let mut order: Vec<usize> = (0..raters.len()).collect();
+    order.sort_by_key(|&r| totals[r]);
+    let bands = BANDS.min(raters.len());
+    let band = |b: usize| &order[b * order.len() / bands..(b + 1) * order.len() / bands];
+
+    writeln!(out, "{}", t!("plots.curves", bands = bands))?;
+    for &i in &scored {
+        let proportions: Vec<f64> = (0..bands)
+            .map(|b| {
+                let members = band(b);
+                let right = members.iter().filter(|&&r| right(&raters[r], i)).count();
+                right as f64 / members.len() as f64
+            })
+            .collect();
+        let curve: String = proportions
+            .iter()
+            .map(|p| LEVELS[((p * LEVELS.len() as f64) as usize).min(LEVELS.len() - 1)])
+            .collect();
+        let question = match first.bank.questions[i].id() {
+            Some(id) => format!("{} ({})", i + 1, id),
+            None => (i + 1).to_string(),
+        };
+        let (low, high) = (proportions[0], proportions[bands - 1]);
+        let mark = match high <= low {
+            true => " *",
+            false => "",
+        };
+        writeln!(
+            out,
+            "  {:<14}{}  {:.2} → {:.2}{}",
+            question, curve, low, high, mark
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,438 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use color_eyre::{
+    eyre::{bail, eyre, WrapErr},
+    Result,
+};
+use ratatui::prelude::*;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{bank::Question, i18n::t, markdown, mode::Mode, theme::Palette};
+
+// Item types beyond multiple choice, eg. a drug-dose calculator, without
+// forking: a question with a "type" is handled by the plugin of that name in
+// `question_cli plugin questions.json`, and its response saved in its
+// "plugin_response". The plugin draws the question's right panel, turns key
+// presses into the response and reads the responses given to `question_cli set`
+// as text. numeric (below) is built in, others are loaded from the config:
+//
+// [[plugins]]
+// path = "plugins/dose.wasm"
+//
+// A .wasm module needs a build with the wasm-plugins feature, a shared library
+// (.so, .dylib, .dll) one with dynamic-plugins. Loaded plugins are asked in
+// JSON, see Loaded, so they can be written in anything that compiles to either.
+
+/// The field of the question the response is saved in, whatever its type
+pub const FIELD: &str = "plugin_response";
+
+/// A kind of item and how raters respond to it
+pub trait Plugin: Send + Sync {
+    /// The "type" of the questions it handles
+    fn name(&self) -> &str;
+
+    /// The right panel for the question, in Markdown
+    fn render(&self, question: &Question, response: Option<&Value>) -> Result<String>;
+
+    /// The response after pressing key (named like in the config: "3", "Enter",
+    /// "Space"...), None if the key does nothing
+    fn respond(
+        &self,
+        question: &Question,
+        response: Option<&Value>,
+        key: &str,
+    ) -> Result<Option<Value>>;
+
+    /// The response for text given to `question_cli set`
+    fn parse(&self, question: &Question, text: &str) -> Result<Value>;
+
+    /// Whether the rater is done with the question
+    fn is_done(&self, _question: &Question, response: Option<&Value>) -> Result<bool> {
+        Ok(response.is_some())
+    }
+
+    /// The response in the status line
+    fn status(&self, _question: &Question, response: &Value) -> Result<String> {
+        Ok(match response {
+            Value::String(text) => text.clone(),
+            value => value.to_string(),
+        })
+    }
+}
+
+/// A plugin to load, the [[plugins]] tables of the config file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Source {
+    pub path: PathBuf,
+}
+
+static PLUGINS: OnceLock<Vec<Box<dyn Plugin>>> = OnceLock::new();
+
+/// Loads the plugins of the config, next to the built in ones
+pub fn init(sources: &[Source]) -> Result<()> {
+    let mut plugins: Vec<Box<dyn Plugin>> = vec![Box::new(Numeric)];
+    for source in sources {
+        let plugin = Loaded::load(&source.path)?;
+        if plugins.iter().any(|other| other.name() == plugin.name()) {
+            bail!(
+                "there's already a plugin for {} questions, {} can't be loaded too",
+                plugin.name(),
+                source.path.display()
+            );
+        }
+        plugins.push(Box::new(plugin));
+    }
+    let _ = PLUGINS.set(plugins);
+    Ok(())
+}
+
+pub fn all() -> &'static [Box<dyn Plugin>] {
+    PLUGINS.get().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// The plugin for the question's "type", if there's one
+pub fn of(question: &Question) -> Option<&'static dyn Plugin> {
+    let name = question.extra.get("type").and_then(Value::as_str)?;
+    all()
+        .iter()
+        .find(|plugin| plugin.name() == name)
+        .map(Box::as_ref)
+}
+
+pub fn response(question: &Question) -> Option<&Value> {
+    question.extra.get(FIELD)
+}
+
+/// The plugin's panel for the question, or the instructions for questions
+/// without one
+pub fn panel(question: &Question, palette: &Palette) -> Vec<Line<'static>> {
+    let Some(plugin) = of(question) else {
+        return Mode::Plugin.instructions();
+    };
+    match plugin.render(question, response(question)) {
+        Ok(markdown) => markdown::to_lines(&markdown, Style::default()),
+        Err(err) => vec![Line::from(
+            t!("plugins.error", error = format!("{:#}", err)).fg(palette.missing),
+        )],
+    }
+}
+
+/// "type": "numeric", answered with a number typed in. The bank can add the
+/// "unit" it's in, shown after it.
+struct Numeric;
+
+impl Numeric {
+    fn unit(question: &Question) -> &str {
+        question
+            .extra
+            .get("unit")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+    }
+}
+
+impl Plugin for Numeric {
+    fn name(&self) -> &str {
+        "numeric"
+    }
+
+    fn render(&self, question: &Question, response: Option<&Value>) -> Result<String> {
+        let typed = response.and_then(Value::as_str).unwrap_or("_");
+        Ok(format!(
+            "**{}**\n\n`{}` {}\n\n{}",
+            t!("plugins.numeric.title"),
+            typed,
+            Numeric::unit(question),
+            t!("plugins.numeric.how")
+        ))
+    }
+
+    fn respond(
+        &self,
+        _question: &Question,
+        response: Option<&Value>,
+        key: &str,
+    ) -> Result<Option<Value>> {
+        if key.len() != 1 || !"0123456789.-".contains(key) {
+            return Ok(None);
+        }
+        let typed = response.and_then(Value::as_str).unwrap_or_default();
+        let typed = format!("{}{}", typed, key);
+        // on the way to a number, like "-" or "12."
+        match format!("{}0", typed).parse::<f64>() {
+            Ok(_) => Ok(Some(Value::from(typed))),
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn parse(&self, _question: &Question, text: &str) -> Result<Value> {
+        let text = text.trim();
+        match text.parse::<f64>() {
+            Ok(_) => Ok(Value::from(text)),
+            Err(_) => bail!("'{}' isn't a number", text),
+        }
+    }
+
+    fn is_done(&self, _question: &Question, response: Option<&Value>) -> Result<bool> {
+        let typed = response.and_then(Value::as_str).unwrap_or_default();
+        Ok(typed.parse::<f64>().is_ok())
+    }
+
+    fn status(&self, question: &Question, response: &Value) -> Result<String> {
+        let typed = response.as_str().unwrap_or_default();
+        Ok(format!("{} {}", typed, Numeric::unit(question))
+            .trim_end()
+            .to_string())
+    }
+}
+
+// sends a request to a loaded plugin and returns its reply
+type Call = Box<dyn Fn(&str) -> Result<String> + Send + Sync>;
+
+/// A plugin loaded from a file. It's asked in JSON and replies in JSON:
+///
+/// {"call": "name"} => {"name": "dose"}
+/// {"call": "render", "question": {...}, "response": ...} => {"markdown": "..."}
+/// {"call": "respond", "question": {...}, "response": ..., "key": "3"} => {"response": ...}
+/// {"call": "parse", "question": {...}, "text": "12.5"} => {"response": ...}
+/// {"call": "is_done", "question": {...}, "response": ...} => {"done": true}
+/// {"call": "status", "question": {...}, "response": ...} => {"status": "12.5 mg"}
+///
+/// The response is null when there's none yet (or, from respond, when the key
+/// does nothing), and any call can reply {"error": "..."}.
+struct Loaded {
+    name: String,
+    path: PathBuf,
+    call: Call,
+}
+
+impl Loaded {
+    fn load(path: &Path) -> Result<Loaded> {
+        let call = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("wasm") => wasm::open(path)?,
+            _ => dynamic::open(path)?,
+        };
+        let mut plugin = Loaded {
+            name: String::new(),
+            path: path.to_path_buf(),
+            call,
+        };
+        let reply = plugin.ask(json!({"call": "name"}))?;
+        plugin.name = reply["name"]
+            .as_str()
+            .ok_or_else(|| eyre!("the plugin {} has no name", path.display()))?
+            .to_string();
+        Ok(plugin)
+    }
+
+    fn ask(&self, request: Value) -> Result<Value> {
+        let reply = (self.call)(&request.to_string())
+            .wrap_err_with(|| format!("the plugin {} failed", self.path.display()))?;
+        let reply: Value = serde_json::from_str(&reply)
+            .wrap_err_with(|| format!("invalid reply from the plugin {}", self.path.display()))?;
+        if let Some(error) = reply.get("error") {
+            bail!(
+                "{}: {}",
+                self.name,
+                error
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or(error.to_string())
+            );
+        }
+        Ok(reply)
+    }
+}
+
+impl Plugin for Loaded {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn render(&self, question: &Question, response: Option<&Value>) -> Result<String> {
+        let reply =
+            self.ask(json!({"call": "render", "question": question, "response": response}))?;
+        Ok(reply["markdown"].as_str().unwrap_or_default().to_string())
+    }
+
+    fn respond(
+        &self,
+        question: &Question,
+        response: Option<&Value>,
+        key: &str,
+    ) -> Result<Option<Value>> {
+        let reply = self.ask(json!({
+            "call": "respond",
+            "question": question,
+            "response": response,
+            "key": key,
+        }))?;
+        Ok(Some(reply["response"].clone()).filter(|response| !response.is_null()))
+    }
+
+    fn parse(&self, question: &Question, text: &str) -> Result<Value> {
+        let reply = self.ask(json!({"call": "parse", "question": question, "text": text}))?;
+        match &reply["response"] {
+            Value::Null => bail!("{} didn't read '{}' as a response", self.name, text),
+            response => Ok(response.clone()),
+        }
+    }
+
+    fn is_done(&self, question: &Question, response: Option<&Value>) -> Result<bool> {
+        let reply =
+            self.ask(json!({"call": "is_done", "question": question, "response": response}))?;
+        Ok(reply["done"].as_bool().unwrap_or_default())
+    }
+
+    fn status(&self, question: &Question, response: &Value) -> Result<String> {
+        let reply =
+            self.ask(json!({"call": "status", "question": question, "response": response}))?;
+        Ok(reply["status"].as_str().unwrap_or_default().to_string())
+    }
+}
+
+// A shared library exporting
+//
+// char *question_cli_plugin(const char *request); // the reply, NUL terminated
+// void question_cli_free(char *reply);
+#[cfg(feature = "dynamic-plugins")]
+mod dynamic {
+    use std::{
+        ffi::{c_char, CStr, CString},
+        path::Path,
+    };
+
+    use color_eyre::{
+        eyre::{bail, WrapErr},
+        Result,
+    };
+    use libloading::Library;
+
+    use super::Call;
+
+    type Ask = unsafe extern "C" fn(*const c_char) -> *mut c_char;
+    type Free = unsafe extern "C" fn(*mut c_char);
+
+    pub fn open(path: &Path) -> Result<Call> {
+        // SAFETY: loading runs the library's initialisers, plugins are trusted
+        // like the config listing them
+        let library = unsafe { Library::new(path) }
+            .wrap_err_with(|| format!("could not load the plugin {}", path.display()))?;
+        // SAFETY: the signatures above are the plugin interface
+        let (ask, free) = unsafe {
+            let ask = *library
+                .get::<Ask>(b"question_cli_plugin\0")
+                .wrap_err_with(|| format!("{} isn't a question_cli plugin", path.display()))?;
+            let free = *library
+                .get::<Free>(b"question_cli_free\0")
+                .wrap_err_with(|| format!("{} isn't a question_cli plugin", path.display()))?;
+            (ask, free)
+        };
+        Ok(Box::new(move |request| {
+            let _loaded = &library; // the functions live as long as it does
+            let request = CString::new(request)?;
+            // SAFETY: the plugin returns a NUL terminated string or null, and
+            // it's freed by the plugin once copied
+            unsafe {
+                let reply = ask(request.as_ptr());
+                if reply.is_null() {
+                    bail!("no reply");
+                }
+                let text = CStr::from_ptr(reply).to_string_lossy().into_owned();
+                free(reply);
+                Ok(text)
+            }
+        }))
+    }
+}
+
+#[cfg(not(feature = "dynamic-plugins"))]
+mod dynamic {
+    use std::path::Path;
+
+    use color_eyre::{eyre::bail, Result};
+
+    use super::Call;
+
+    pub fn open(path: &Path) -> Result<Call> {
+        bail!(
+            "this build can't load the plugin {}, build question_cli with --features dynamic-plugins",
+            path.display()
+        )
+    }
+}
+
+// A WebAssembly module without imports exporting its memory and
+//
+// alloc(len: i32) -> i32 // where to write a request of len bytes
+// call(ptr: i32, len: i32) -> i64 // the reply, its address << 32 | its length
+#[cfg(feature = "wasm-plugins")]
+mod wasm {
+    use std::{fs, path::Path, sync::Mutex};
+
+    use color_eyre::{
+        eyre::{eyre, WrapErr},
+        Result,
+    };
+    use wasmi::{Engine, Linker, Module, Store};
+
+    use super::Call;
+
+    pub fn open(path: &Path) -> Result<Call> {
+        let wasm = fs::read(path)
+            .wrap_err_with(|| format!("could not read the plugin {}", path.display()))?;
+        let engine = Engine::default();
+        let module = Module::new(&engine, &wasm)
+            .wrap_err_with(|| format!("invalid WebAssembly in {}", path.display()))?;
+        let mut store = Store::new(&engine, ());
+        let instance = Linker::<()>::new(&engine)
+            .instantiate(&mut store, &module)
+            .and_then(|instance| instance.start(&mut store))
+            .wrap_err_with(|| format!("could not start the plugin {}", path.display()))?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| eyre!("{} exports no memory", path.display()))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&store, "alloc")
+            .wrap_err_with(|| format!("{} isn't a question_cli plugin", path.display()))?;
+        let call = instance
+            .get_typed_func::<(i32, i32), i64>(&store, "call")
+            .wrap_err_with(|| format!("{} isn't a question_cli plugin", path.display()))?;
+        let store = Mutex::new(store);
+        Ok(Box::new(move |request| {
+            let mut store = store.lock().map_err(|_| eyre!("an earlier call failed"))?;
+            let len = request.len() as i32;
+            let ptr = alloc.call(&mut *store, len)?;
+            memory
+                .write(&mut *store, ptr as usize, request.as_bytes())
+                .map_err(|err| eyre!("{}", err))?;
+            let reply = call.call(&mut *store, (ptr, len))? as u64;
+            let mut bytes = vec![0; (reply & 0xffff_ffff) as usize];
+            memory
+                .read(&*store, (reply >> 32) as usize, &mut bytes)
+                .map_err(|err| eyre!("{}", err))?;
+            Ok(String::from_utf8(bytes)?)
+        }))
+    }
+}
+
+#[cfg(not(feature = "wasm-plugins"))]
+mod wasm {
+    use std::path::Path;
+
+    use color_eyre::{eyre::bail, Result};
+
+    use super::Call;
+
+    pub fn open(path: &Path) -> Result<Call> {
+        bail!(
+            "this build can't load the plugin {}, build question_cli with --features wasm-plugins",
+            path.display()
+        )
+    }
+}
@@ -0,0 +1,135 @@
+use ratatui::{
+    crossterm::event::KeyCode,
+    prelude::*,
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+};
+
+// Lists drawn over the middle of the screen (bookmarks, sort orders...), one
+// item is highlighted and picked with the arrows or by its number. Finders are
+// lists searched by typing instead (topics, tags).
+
+/// What a key press does to an open list
+pub enum Choice {
+    Highlight(usize),
+    Pick(usize),
+    Close,
+}
+
+/// Arrows move the highlight, Enter or an item's number picks it, any other
+/// key closes the list
+pub fn choose(code: KeyCode, selected: usize, count: usize) -> Choice {
+    match code {
+        KeyCode::Up => Choice::Highlight(selected.saturating_sub(1)),
+        KeyCode::Down => Choice::Highlight((selected + 1).min(count.saturating_sub(1))),
+        KeyCode::Enter => Choice::Pick(selected),
+        KeyCode::Char(c) => match c.to_digit(10) {
+            Some(number) if (1..=count).contains(&(number as usize)) => {
+                Choice::Pick(number as usize - 1)
+            }
+            Some(_) => Choice::Highlight(selected),
+            None => Choice::Close,
+        },
+        _ => Choice::Close,
+    }
+}
+
+/// A list searched by typing, open
+#[derive(Debug, Default)]
+pub struct Finder {
+    pub query: String,
+    pub selected: usize,
+}
+
+impl Finder {
+    /// (item, whether it's new) for the items matching the query, then the
+    /// query itself as a new item when it isn't one already
+    pub fn choices(&self, items: &[String]) -> Vec<(String, bool)> {
+        let mut choices: Vec<(String, bool)> = search(items, &self.query)
+            .into_iter()
+            .map(|item| (item.clone(), false))
+            .collect();
+        let query = self.query.trim();
+        let exists = items.iter().any(|item| item.eq_ignore_ascii_case(query));
+        if !query.is_empty() && !exists {
+            choices.push((query.to_string(), true));
+        }
+        choices
+    }
+
+    /// Typing goes in the query, the arrows move the highlight, Enter picks
+    /// and Esc closes the finder
+    pub fn choose(&mut self, code: KeyCode, count: usize) -> Choice {
+        match code {
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.selected = 0;
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.selected = 0;
+            }
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.selected = (self.selected + 1).min(count.saturating_sub(1)),
+            KeyCode::Enter if count > 0 => return Choice::Pick(self.selected),
+            KeyCode::Esc => return Choice::Close,
+            _ => {}
+        }
+        Choice::Highlight(self.selected)
+    }
+}
+
+/// The items matching query, best first: fewest characters between the
+/// matched ones, then the shortest
+pub fn search<'a>(items: &'a [String], query: &str) -> Vec<&'a String> {
+    let query = query.to_lowercase();
+    let mut found: Vec<(usize, &String)> = items
+        .iter()
+        .filter_map(|item| Some((gaps(&item.to_lowercase(), &query)?, item)))
+        .collect();
+    found.sort_by_key(|(gaps, item)| (*gaps, item.len()));
+    found.into_iter().map(|(_, item)| item).collect()
+}
+
+// None if query's characters aren't all in item in order
+fn gaps(item: &str, query: &str) -> Option<usize> {
+    let mut chars = item.chars().enumerate();
+    let mut last = None;
+    let mut gaps = 0;
+    for wanted in query.chars() {
+        let (at, _) = chars.find(|(_, c)| *c == wanted)?;
+        gaps += last.map_or(0, |last| at - last - 1);
+        last = Some(at);
+    }
+    Some(gaps)
+}
+
+/// The list over the middle of area, sized to fit the items
+pub fn list(frame: &mut Frame, area: Rect, title: &str, items: Vec<ListItem>, selected: usize) {
+    let width = (area.width * 3 / 5).max(30);
+    let area = centered(area, width, items.len() as u16 + 2);
+    let list = List::new(items)
+        .block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title(format!(" {} ", title)),
+        )
+        .highlight_style(Style::default().reversed());
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(
+        list,
+        area,
+        &mut ListState::default().with_selected(Some(selected)),
+    );
+}
+
+/// A width by height rect in the middle of area, shrunk to fit in it
+pub fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
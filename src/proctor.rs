@@ -0,0 +1,22 @@
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+// Proctored sessions (--proctored), for running a bank as an actual assessment:
+// - quitting before every question is done asks for the proctor_code from the
+//   config file, so a candidate can't leave (and come back later) on their own
+// - the event log is on, every navigation and answer is timestamped
+// - the bank is stamped with when the exam was started and ended:
+//   "exam": { "started": "2026-01-05T14:03:11Z", "ended": "2026-01-05T15:01:40Z" }
+
+/// Start and end of a proctored session, saved in the bank
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Exam {
+    pub started: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ended: Option<String>,
+}
+
+/// RFC 3339 to the second, UTC
+pub fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
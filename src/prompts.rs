@@ -0,0 +1,106 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{
+    eyre::{bail, WrapErr},
+    Result,
+};
+use serde::Deserialize;
+
+use crate::{bank::Question, llm};
+
+// The prompts of the --llm commands can be swapped for your own, to try
+// variations without recompiling: a TOML file with the system message and the
+// message asking about each question, where {{question}}, {{options}} and
+// {{answer}} are filled in ({{count}} too for distractors, how many are
+// wanted):
+//
+// system = "You are a board-certified internist sitting a medical exam."
+// prompt = """
+// {{question}}
+//
+// {{options}}
+// Reply with the number of the best option on the first line, as "Answer: 2".
+// """
+//
+// Given with --prompt, or for every run in the config:
+//
+// [llm.prompts]
+// compare = "prompts/answer.toml"
+//
+// Without a system message the built-in one is kept. Replies are cached by
+// prompt, so a new template asks again.
+
+const VARIABLES: [&str; 4] = ["question", "options", "answer", "count"];
+
+/// Templates to use instead of the built-in prompts, the [llm.prompts] table of
+/// the config file
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Prompts {
+    pub review: Option<PathBuf>,
+    pub distractors: Option<PathBuf>,
+    pub author: Option<PathBuf>,
+    pub compare: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Template {
+    pub system: Option<String>,
+    pub prompt: String,
+}
+
+impl Template {
+    pub fn load(path: &Path) -> Result<Template> {
+        let data = fs::read_to_string(path)
+            .wrap_err_with(|| format!("could not read prompt template: {}", path.display()))?;
+        let template: Template = toml::from_str(&data)
+            .wrap_err_with(|| format!("invalid prompt template: {}", path.display()))?;
+        // a typo would go to the model as is
+        let mut rest = template.prompt.as_str();
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start..].find("}}") else {
+                break;
+            };
+            let name = rest[start + 2..start + end].trim();
+            if !VARIABLES.contains(&name) {
+                bail!(
+                    "unknown variable {{{{{}}}}} in {}, the variables are {}",
+                    name,
+                    path.display(),
+                    VARIABLES.map(|v| format!("{{{{{}}}}}", v)).join(", ")
+                );
+            }
+            rest = &rest[start + end + 2..];
+        }
+        Ok(template)
+    }
+
+    /// The prompt for the question, count being how many distractors are wanted
+    pub fn render(&self, question: &Question, count: usize) -> String {
+        let options = llm::options(question);
+        let mut prompt = self.prompt.clone();
+        for (name, value) in [
+            ("question", question.question.trim()),
+            ("options", options.trim_end()),
+            ("answer", question.answer.as_str()),
+            ("count", &count.to_string()),
+        ] {
+            prompt = prompt
+                .replace(&format!("{{{{{}}}}}", name), value)
+                .replace(&format!("{{{{ {} }}}}", name), value);
+        }
+        prompt
+    }
+}
+
+/// The template given with --prompt, or else the config's for the command
+pub fn template(given: Option<&PathBuf>, configured: Option<&PathBuf>) -> Result<Option<Template>> {
+    given
+        .or(configured)
+        .map(|path| Template::load(path))
+        .transpose()
+}
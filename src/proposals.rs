@@ -0,0 +1,219 @@
+use color_eyre::Result;
+use ratatui::{
+    style::{Style, Stylize},
+    text::{Line, Span},
+};
+use serde_json::{json, Value};
+
+use crate::{
+    bank::{Banks, Question},
+    i18n::t,
+    llm::{self, Client},
+    proctor,
+    prompts::Template,
+    theme::Palette,
+};
+
+// `question_cli distractors --llm questions.json` asks the model of the
+// config's [llm] table (see llm.rs) for more distractors for the questions
+// with fewer than 4 options. They aren't options yet: they're kept in the
+// question as machine generated, pending, for the author to approve or reject
+// one by one in `question_cli distractors questions.json`. Approving one adds
+// it to the options.
+//
+// "proposed_distractors": [
+//   {"text": "Amlodipine", "model": "gpt-4o", "proposed": "2024-05-02T14:03:11Z", "status": "pending"}
+// ]
+
+const OPTIONS: usize = 4; // the usual one best answer and 3 distractors
+
+const INSTRUCTIONS: &str = "You write distractors for multiple choice questions for medical \
+exams: wrong options that are plausible to a student who doesn't know the answer, \
+homogeneous with the other options in content, grammar and length, and clearly wrong to \
+one who does. Reply with the distractors only, one per line, without numbers or \
+explanations.";
+
+/// The proposals of the question, each a JSON object
+pub fn all(question: &Question) -> &[Value] {
+    question
+        .extra
+        .get("proposed_distractors")
+        .and_then(Value::as_array)
+        .map_or(&[], Vec::as_slice)
+}
+
+fn status(proposal: &Value) -> &str {
+    proposal["status"].as_str().unwrap_or("pending")
+}
+
+/// The proposal the author decides on next
+pub fn pending(question: &Question) -> Option<usize> {
+    all(question)
+        .iter()
+        .position(|proposal| status(proposal) == "pending")
+}
+
+/// Approves (adding it to the options) or rejects the next pending proposal
+pub fn decide(question: &mut Question, approve: bool) -> bool {
+    let Some(index) = pending(question) else {
+        return false;
+    };
+    let Some(proposals) = question
+        .extra
+        .get_mut("proposed_distractors")
+        .and_then(Value::as_array_mut)
+    else {
+        return false;
+    };
+    let proposal = &mut proposals[index];
+    proposal["status"] = Value::from(if approve { "approved" } else { "rejected" });
+    if approve {
+        let text = proposal["text"].as_str().unwrap_or_default().to_string();
+        question.options.push(text);
+    }
+    true
+}
+
+/// Puts every proposal back to pending, taking the approved ones out of the options
+pub fn undecide(question: &mut Question) -> bool {
+    let Some(proposals) = question
+        .extra
+        .get_mut("proposed_distractors")
+        .and_then(Value::as_array_mut)
+    else {
+        return false;
+    };
+    let mut changed = false;
+    for proposal in proposals.iter_mut() {
+        match status(proposal) {
+            "pending" => continue,
+            "approved" => {
+                let text = proposal["text"].as_str().unwrap_or_default();
+                if let Some(i) = question.options.iter().rposition(|o| o == text) {
+                    question.options.remove(i);
+                }
+            }
+            _ => {}
+        }
+        proposal["status"] = Value::from("pending");
+        changed = true;
+    }
+    changed
+}
+
+// the model's reply, one distractor per line, less any list markers and those
+// already options
+fn parse(reply: &str, question: &Question, count: usize) -> Vec<String> {
+    let mut distractors: Vec<String> = vec![];
+    for line in reply.lines() {
+        let line = line
+            .trim()
+            .trim_start_matches(|c: char| c.is_ascii_digit() || matches!(c, '-' | '*' | '•'))
+            .trim_start_matches(['.', ')'])
+            .trim();
+        let taken = |text: &String| text.eq_ignore_ascii_case(line);
+        if line.is_empty() || question.options.iter().any(taken) || distractors.iter().any(taken) {
+            continue;
+        }
+        distractors.push(line.to_string());
+    }
+    distractors.truncate(count);
+    distractors
+}
+
+/// Has the model propose distractors for the questions with fewer than 4
+/// options that have an answer key and no proposals yet, saving after each
+/// one, with the template's prompt if given. Returns how many questions got
+/// some.
+pub fn run(banks: &mut Banks, client: &Client, template: Option<&Template>) -> Result<usize> {
+    let prompts: Vec<(usize, String)> = (0..banks.len())
+        .filter(|&index| {
+            let question = banks.question(index);
+            question.options.len() < OPTIONS
+                && !question.answer.is_empty()
+                && all(question).is_empty()
+        })
+        .map(|index| {
+            let question = banks.question(index);
+            let count = OPTIONS - question.options.len();
+            let prompt = match template {
+                Some(template) => template.render(question, count),
+                None => format!(
+                    "{}\nAnswer: {}\n\nWrite {} more distractors.",
+                    llm::item(question),
+                    question.answer,
+                    count
+                ),
+            };
+            (index, prompt)
+        })
+        .collect();
+    let system = template.and_then(|template| template.system.as_deref());
+    let (mut done, mut proposed) = (0, 0);
+    client.complete_all(system.unwrap_or(INSTRUCTIONS), &prompts, |index, reply| {
+        done += 1;
+        eprintln!(
+            "{}",
+            t!(
+                "proposals.progress",
+                number = index + 1,
+                done = done,
+                total = prompts.len()
+            )
+        );
+        let question = banks.question(index);
+        let distractors = parse(&reply.text, question, OPTIONS - question.options.len());
+        if distractors.is_empty() {
+            return Ok(());
+        }
+        let now = proctor::now();
+        banks.update(index, "propose distractors", |question| {
+            let proposals: Vec<Value> = distractors
+                .iter()
+                .map(|text| {
+                    json!({
+                        "text": text,
+                        "model": client.model,
+                        "proposed": now,
+                        "status": "pending",
+                    })
+                })
+                .collect();
+            question
+                .extra
+                .insert("proposed_distractors".to_string(), Value::from(proposals));
+            llm::note_usage(question, "distractors", client, reply.usage);
+            true
+        })?;
+        banks.save()?;
+        proposed += 1;
+        Ok(())
+    })?;
+    Ok(proposed)
+}
+
+/// The proposals for the right panel, the next pending one marked
+pub fn panel(question: &Question, palette: &Palette) -> Vec<Line<'static>> {
+    let proposals = all(question);
+    if proposals.is_empty() {
+        return vec![Line::from(t!("proposals.none").italic())];
+    }
+    let next = pending(question);
+    let mut lines = vec![Line::from(t!("proposals.title").bold()), Line::from("")];
+    for (i, proposal) in proposals.iter().enumerate() {
+        let text = proposal["text"].as_str().unwrap_or_default().to_string();
+        let model = proposal["model"].as_str().unwrap_or_default();
+        let (mark, style) = match status(proposal) {
+            "approved" => ("✓ ", Style::default().fg(palette.selected)),
+            "rejected" => ("✗ ", Style::default().fg(palette.missing).crossed_out()),
+            _ if next == Some(i) => ("▶ ", Style::default().bold()),
+            _ => ("  ", Style::default()),
+        };
+        lines.push(Line::from(vec![
+            Span::raw(mark),
+            Span::styled(text, style),
+            format!(" ({})", model).dim(),
+        ]));
+    }
+    lines
+}
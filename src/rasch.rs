@@ -0,0 +1,395 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::{bank::Question, export::Rater, i18n::t, stats::mean};
+
+// `question_cli rasch R01.json R02.json ...` calibrates the bank's questions
+// from raters' answered copies with the Rasch model (1PL): each question's
+// difficulty and each rater's ability, in logits on the same scale, the
+// difficulties centred on 0. Estimated by joint maximum likelihood, with the
+// usual (L - 1) / L correction of its bias for L questions, applied to both.
+//
+// Unanswered questions are missing, not wrong. Raters who got every question
+// right or wrong, and questions every rater got right or wrong, have no finite
+// estimate and are left out (and then whoever becomes extreme without them).
+// With --by-group the copies are calibrated separately for each "group" set at
+// their top level ("student", "resident"...), to compare the difficulties.
+
+const ITERATIONS: usize = 500;
+const CONVERGED: f64 = 1e-5; // largest change in an iteration, in logits
+
+/// A question, None for the estimates when it was left out
+#[derive(Debug, Serialize)]
+pub struct Item {
+    pub question: usize, // from 1
+    pub id: Option<String>,
+    pub difficulty: Option<f64>,
+    pub se: Option<f64>,
+    pub correct: usize,
+    pub answered: usize,
+}
+
+/// A rater, None for the estimates when they were left out
+#[derive(Debug, Serialize)]
+pub struct Person {
+    pub rater: String,
+    pub ability: Option<f64>,
+    pub se: Option<f64>,
+    pub correct: usize,
+    pub answered: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Calibration {
+    pub group: Option<String>,
+    pub items: Vec<Item>,
+    pub persons: Vec<Person>,
+}
+
+/// The copies' "group", None for those without one
+pub fn group(rater: &Rater) -> Option<String> {
+    rater
+        .bank
+        .extra
+        .get("group")
+        .and_then(|group| group.as_str())
+        .map(str::to_string)
+}
+
+pub fn calibrate(group: Option<String>, raters: &[&Rater]) -> Calibration {
+    // responses[person][item], None when unanswered
+    let responses: Vec<Vec<Option<bool>>> = raters
+        .iter()
+        .map(|rater| {
+            let questions = rater.bank.questions.iter();
+            questions.map(Question::is_correct).collect()
+        })
+        .collect();
+    let fit = fit(&responses);
+    let items = (0..fit.items.len())
+        .map(|i| {
+            let column: Vec<bool> = responses.iter().filter_map(|row| row[i]).collect();
+            Item {
+                question: i + 1,
+                id: raters[0].bank.questions[i].id(),
+                difficulty: fit.items[i].map(|(difficulty, _)| difficulty),
+                se: fit.items[i].map(|(_, se)| se),
+                correct: column.iter().filter(|&&x| x).count(),
+                answered: column.len(),
+            }
+        })
+        .collect();
+    let persons = raters
+        .iter()
+        .enumerate()
+        .map(|(p, rater)| {
+            let row: Vec<bool> = responses[p].iter().flatten().copied().collect();
+            Person {
+                rater: rater.id.clone(),
+                ability: fit.persons[p].map(|(ability, _)| ability),
+                se: fit.persons[p].map(|(_, se)| se),
+                correct: row.iter().filter(|&&x| x).count(),
+                answered: row.len(),
+            }
+        })
+        .collect();
+    Calibration {
+        group,
+        items,
+        persons,
+    }
+}
+
+// the estimates with their standard errors, None for those left out
+struct Fit {
+    items: Vec<Option<(f64, f64)>>,
+    persons: Vec<Option<(f64, f64)>>,
+}
+
+// from responses[person][item], None when unanswered
+fn fit(responses: &[Vec<Option<bool>>]) -> Fit {
+    let count = responses.first().map_or(0, Vec::len);
+
+    // leaving one out can make others extreme, until none are
+    let mut persons = vec![true; responses.len()];
+    let mut items = vec![true; count];
+    loop {
+        let mut changed = false;
+        for (p, row) in responses.iter().enumerate() {
+            let row = (0..count).filter(|&i| items[i]).map(|i| row[i]);
+            if persons[p] && extreme(row) {
+                persons[p] = false;
+                changed = true;
+            }
+        }
+        for i in 0..count {
+            let column = (0..responses.len()).filter(|&p| persons[p]);
+            if items[i] && extreme(column.map(|p| responses[p][i])) {
+                items[i] = false;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let mut ability = vec![0.0; responses.len()];
+    let mut difficulty = vec![0.0; count];
+    let calibrated: Vec<usize> = (0..count).filter(|&i| items[i]).collect();
+    let included: Vec<usize> = (0..responses.len()).filter(|&p| persons[p]).collect();
+    for _ in 0..ITERATIONS {
+        let mut largest: f64 = 0.0;
+        for &i in &calibrated {
+            let (observed, expected, info) =
+                item_sums(responses, &included, i, &ability, difficulty[i]);
+            // Newton steps, kept small while far from the estimate
+            let step = ((expected - observed) / info).clamp(-1.0, 1.0);
+            difficulty[i] += step;
+            largest = largest.max(step.abs());
+        }
+        let mean = mean(calibrated.iter().map(|&i| difficulty[i]));
+        for &i in &calibrated {
+            difficulty[i] -= mean;
+        }
+        for &p in &included {
+            let (observed, expected, info) =
+                person_sums(&responses[p], &calibrated, ability[p], &difficulty);
+            let step = ((observed - expected) / info).clamp(-1.0, 1.0);
+            ability[p] += step;
+            largest = largest.max(step.abs());
+        }
+        if largest < CONVERGED {
+            break;
+        }
+    }
+
+    let length = calibrated.len() as f64;
+    let correction = match length > 1.0 {
+        true => (length - 1.0) / length,
+        false => 1.0,
+    };
+    let items = (0..count)
+        .map(|i| {
+            items[i].then(|| {
+                let (_, _, info) = item_sums(responses, &included, i, &ability, difficulty[i]);
+                (difficulty[i] * correction, 1.0 / info.sqrt())
+            })
+        })
+        .collect();
+    let persons = (0..responses.len())
+        .map(|p| {
+            persons[p].then(|| {
+                let (_, _, info) = person_sums(&responses[p], &calibrated, ability[p], &difficulty);
+                (ability[p] * correction, 1.0 / info.sqrt())
+            })
+        })
+        .collect();
+    Fit { items, persons }
+}
+
+// chance of a right answer
+fn probability(ability: f64, difficulty: f64) -> f64 {
+    1.0 / (1.0 + (difficulty - ability).exp())
+}
+
+// (observed score, expected score, information)
+type Sums = (f64, f64, f64);
+
+fn sums(responses: impl Iterator<Item = (bool, f64)>) -> Sums {
+    responses.fold((0.0, 0.0, 0.0), |(observed, expected, info), (x, prob)| {
+        let x = if x { 1.0 } else { 0.0 };
+        (observed + x, expected + prob, info + prob * (1.0 - prob))
+    })
+}
+
+// of question i, over the persons answering it
+fn item_sums(
+    responses: &[Vec<Option<bool>>],
+    persons: &[usize],
+    i: usize,
+    ability: &[f64],
+    difficulty: f64,
+) -> Sums {
+    sums(
+        persons
+            .iter()
+            .filter_map(|&p| Some((responses[p][i]?, probability(ability[p], difficulty)))),
+    )
+}
+
+// of a person, over the items they answered
+fn person_sums(row: &[Option<bool>], items: &[usize], ability: f64, difficulty: &[f64]) -> Sums {
+    sums(
+        items
+            .iter()
+            .filter_map(|&i| Some((row[i]?, probability(ability, difficulty[i])))),
+    )
+}
+
+// all right, all wrong or nothing answered
+fn extreme(responses: impl Iterator<Item = Option<bool>>) -> bool {
+    let answered: Vec<bool> = responses.flatten().collect();
+    answered.iter().all(|&x| x) || answered.iter().all(|&x| !x)
+}
+
+// why there's no estimate
+fn left_out(correct: usize, answered: usize) -> String {
+    match (correct, answered) {
+        (_, 0) => t!("rasch.unanswered"),
+        (0, _) => t!("rasch.all_wrong"),
+        (correct, answered) if correct == answered => t!("rasch.all_right"),
+        _ => t!("rasch.left_out"),
+    }
+    .to_string()
+}
+
+fn estimate(value: Option<f64>, se: Option<f64>, correct: usize, answered: usize) -> String {
+    match (value, se) {
+        (Some(value), Some(se)) => format!("{:+.2} ({:.2})", value, se),
+        _ => left_out(correct, answered),
+    }
+}
+
+/// The difficulties (a column per group) then the abilities
+pub fn print(calibrations: &[Calibration], out: &mut impl Write) -> io::Result<()> {
+    let Some(first) = calibrations.first() else {
+        return Ok(());
+    };
+    let mut header = vec![t!("rasch.question").to_string(), "id".to_string()];
+    header.extend(calibrations.iter().map(|c| match &c.group {
+        Some(group) => group.clone(),
+        None => t!("rasch.difficulty").to_string(),
+    }));
+    let mut rows = vec![header];
+    for (i, item) in first.items.iter().enumerate() {
+        let mut row = vec![
+            item.question.to_string(),
+            item.id.clone().unwrap_or_default(),
+        ];
+        row.extend(calibrations.iter().map(|c| {
+            let item = &c.items[i];
+            estimate(item.difficulty, item.se, item.correct, item.answered)
+        }));
+        rows.push(row);
+    }
+    table(&rows, out)?;
+    writeln!(out)?;
+
+    let mut rows = vec![vec![
+        t!("rasch.rater").to_string(),
+        t!("rasch.group").to_string(),
+        t!("rasch.ability").to_string(),
+        t!("rasch.correct").to_string(),
+    ]];
+    for calibration in calibrations {
+        for person in &calibration.persons {
+            rows.push(vec![
+                person.rater.clone(),
+                calibration.group.clone().unwrap_or_default(),
+                estimate(person.ability, person.se, person.correct, person.answered),
+                format!("{}/{}", person.correct, person.answered),
+            ]);
+        }
+    }
+    // no group column when there are none
+    if calibrations.iter().all(|c| c.group.is_none()) {
+        for row in &mut rows {
+            row.remove(1);
+        }
+    }
+    table(&rows, out)
+}
+
+// left aligned columns, the first row a header
+fn table(rows: &[Vec<String>], out: &mut impl Write) -> io::Result<()> {
+    let columns = rows.first().map_or(0, Vec::len);
+    let widths: Vec<usize> = (0..columns)
+        .map(|c| {
+            rows.iter()
+                .map(|row| row[c].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        writeln!(out, "{}", cells.join("  ").trim_end())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 1 right, 0 wrong, . unanswered
+    fn matrix(rows: &[&str]) -> Vec<Vec<Option<bool>>> {
+        rows.iter()
+            .map(|row| {
+                row.chars()
+                    .map(|c| match c {
+                        '1' => Some(true),
+                        '0' => Some(false),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn responses() -> Vec<Vec<Option<bool>>> {
+        matrix(&[
+            "1110", "1100", "1010", "1101", "0100", // 3, 2, 2, 3 and 1 right
+            "1111", // all right
+            "0000", // all wrong
+        ])
+    }
+
+    #[test]
+    fn difficulties_are_centred() {
+        let fit = fit(&responses());
+        let difficulties: Vec<f64> = fit.items.iter().flatten().map(|(d, _)| *d).collect();
+        assert_eq!(difficulties.len(), 4);
+        assert!(mean(difficulties.into_iter()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn harder_questions_and_better_raters_go_up() {
+        let fit = fit(&responses());
+        let difficulty = |i: usize| fit.items[i].unwrap().0;
+        let ability = |p: usize| fit.persons[p].unwrap().0;
+        // right 4, 4, 2 and 1 times
+        assert!((difficulty(0) - difficulty(1)).abs() < 1e-6);
+        assert!(difficulty(0) < 0.0);
+        assert!(difficulty(1) < difficulty(2));
+        assert!(difficulty(2) < difficulty(3));
+        assert!(difficulty(3) > 0.0);
+        // the same score is the same ability
+        assert!((ability(0) - ability(3)).abs() < 1e-6);
+        assert!((ability(1) - ability(2)).abs() < 1e-6);
+        assert!(ability(4) < ability(1));
+        assert!(ability(1) < ability(0));
+    }
+
+    #[test]
+    fn extreme_raters_and_questions_are_left_out() {
+        let fit = fit(&responses());
+        assert!(fit.persons[5].is_none());
+        assert!(fit.persons[6].is_none());
+        assert!(fit.persons[..5].iter().all(Option::is_some));
+
+        // everyone got the first question right, and without it the last two
+        // raters got everything they answered wrong
+        let fit = super::fit(&matrix(&["110", "101", "100", "1.0"]));
+        assert!(fit.items[0].is_none());
+        assert!(fit.items[1..].iter().all(Option::is_some));
+        assert!(fit.persons[..2].iter().all(Option::is_some));
+        assert!(fit.persons[2..].iter().all(Option::is_none));
+    }
+}
@@ -0,0 +1,223 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::{
+    bank::{Banks, Classification, Question},
+    i18n::t,
+};
+
+// `question_cli readability questions.json` measures the reading burden of each
+// stem: its words, sentences and Flesch-Kincaid grade level (the school grade
+// that can read it, roughly). Stems far longer or harder than the rest of the
+// bank are flagged, outside Tukey's fences (1.5 interquartile ranges past the
+// quartiles), and the averages of higher and lower order questions are
+// compared, so one kind doesn't take more reading than the other. Syllables are
+// counted by vowel groups, a close enough estimate for English.
+
+/// A stem's numbers
+#[derive(Debug, Serialize)]
+pub struct Stem {
+    pub question: usize, // from 1
+    pub id: Option<String>,
+    pub words: usize,
+    pub sentences: usize,
+    pub grade: f64,
+    pub long: bool, // an outlier by words
+    pub hard: bool, // an outlier by grade
+}
+
+/// The averages of the stems classified the same way
+#[derive(Debug, Serialize)]
+pub struct Group {
+    pub classification: Option<Classification>,
+    pub questions: usize,
+    pub words: f64,
+    pub grade: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Readability {
+    pub stems: Vec<Stem>,
+    pub groups: Vec<Group>,
+}
+
+pub fn of(banks: &Banks) -> Readability {
+    let mut stems: Vec<Stem> = (0..banks.len())
+        .map(|index| {
+            let question = banks.question(index);
+            let (words, sentences, syllables) = count(&question.question);
+            let grade = match words {
+                0 => 0.0,
+                _ => {
+                    0.39 * words as f64 / sentences as f64 + 11.8 * syllables as f64 / words as f64
+                        - 15.59
+                }
+            };
+            Stem {
+                question: index + 1,
+                id: question.id(),
+                words,
+                sentences,
+                grade,
+                long: false,
+                hard: false,
+            }
+        })
+        .collect();
+    let long = above_fence(stems.iter().map(|stem| stem.words as f64).collect());
+    let hard = above_fence(stems.iter().map(|stem| stem.grade).collect());
+    for stem in &mut stems {
+        stem.long = long.is_some_and(|fence| stem.words as f64 > fence);
+        stem.hard = hard.is_some_and(|fence| stem.grade > fence);
+    }
+
+    let classifications = [
+        Some(Classification::HigherOrder),
+        Some(Classification::LowerOrder),
+        None,
+    ];
+    let groups = classifications
+        .into_iter()
+        .filter_map(|classification| {
+            let members: Vec<&Stem> = stems
+                .iter()
+                .filter(|stem| classified(banks.question(stem.question - 1)) == classification)
+                .collect();
+            let n = members.len() as f64;
+            (!members.is_empty()).then(|| Group {
+                classification,
+                questions: members.len(),
+                words: members.iter().map(|stem| stem.words as f64).sum::<f64>() / n,
+                grade: members.iter().map(|stem| stem.grade).sum::<f64>() / n,
+            })
+        })
+        .collect();
+    Readability { stems, groups }
+}
+
+// unsure ones go with the unclassified
+fn classified(question: &Question) -> Option<Classification> {
+    question
+        .is_higher_order
+        .filter(|classification| *classification != Classification::Unsure)
+}
+
+// (words, sentences, syllables), math and code left out
+fn count(text: &str) -> (usize, usize, usize) {
+    let mut words = 0;
+    let mut sentences = 0;
+    let mut syllables = 0;
+    for token in text.split_whitespace() {
+        if token.starts_with(['$', '`']) || !token.chars().any(char::is_alphabetic) {
+            continue;
+        }
+        words += 1;
+        syllables += self::syllables(token);
+        if token.ends_with(['.', '?', '!']) {
+            sentences += 1;
+        }
+    }
+    // the last sentence may not end with a full stop
+    if words > 0 && !text.trim_end().ends_with(['.', '?', '!']) {
+        sentences += 1;
+    }
+    (words, sentences.max(1), syllables)
+}
+
+// groups of vowels, less a silent final e
+fn syllables(word: &str) -> usize {
+    let word: Vec<char> = word
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .collect();
+    let vowel = |c: &char| "aeiouy".contains(*c);
+    let mut count = 0;
+    let mut previous = false;
+    for c in &word {
+        let is_vowel = vowel(c);
+        if is_vowel && !previous {
+            count += 1;
+        }
+        previous = is_vowel;
+    }
+    let silent_e = word.len() > 2
+        && word.last() == Some(&'e')
+        && !vowel(&word[word.len() - 2])
+        && word[word.len() - 2] != 'l';
+    if silent_e && count > 1 {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+// Q3 + 1.5 IQR, None with too few to tell
+fn above_fence(mut values: Vec<f64>) -> Option<f64> {
+    if values.len() < 4 {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let quartile = |q: f64| {
+        let at = q * (values.len() - 1) as f64;
+        let (low, high) = (at.floor() as usize, at.ceil() as usize);
+        values[low] + (values[high] - values[low]) * (at - low as f64)
+    };
+    let (q1, q3) = (quartile(0.25), quartile(0.75));
+    Some(q3 + 1.5 * (q3 - q1))
+}
+
+impl Readability {
+    pub fn print(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(
+            out,
+            "{:<16}{:>7}{:>11}{:>7}",
+            t!("readability.question"),
+            t!("readability.words"),
+            t!("readability.sentences"),
+            t!("readability.grade")
+        )?;
+        for stem in &self.stems {
+            let question = match &stem.id {
+                Some(id) => format!("{} ({})", stem.question, id),
+                None => stem.question.to_string(),
+            };
+            let mut flags = vec![];
+            if stem.long {
+                flags.push(t!("readability.long"));
+            }
+            if stem.hard {
+                flags.push(t!("readability.hard"));
+            }
+            let row = format!(
+                "{:<16}{:>7}{:>11}{:>7.1}  {}",
+                question,
+                stem.words,
+                stem.sentences,
+                stem.grade,
+                flags.join(", ")
+            );
+            writeln!(out, "{}", row.trim_end())?;
+        }
+        writeln!(out)?;
+        for group in &self.groups {
+            let name = match group.classification {
+                Some(Classification::HigherOrder) => t!("readability.higher_order"),
+                Some(_) => t!("readability.lower_order"),
+                None => t!("readability.unclassified"),
+            };
+            writeln!(
+                out,
+                "{}",
+                t!(
+                    "readability.group",
+                    name = name,
+                    count = group.questions,
+                    words = format!("{:.0}", group.words),
+                    grade = format!("{:.1}", group.grade)
+                )
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use std::{fs, io, path::PathBuf};
+
+use chrono::{DateTime, Local};
+use color_eyre::{eyre::WrapErr, Result};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    prelude::*,
+    widgets::{Block, Borders, List, ListItem, ListState, Padding},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Config, i18n::t, mode::Mode, tui::Tui};
+
+// Every session is remembered in ~/.local/share/question_cli/recent.json with
+// how far along it was when it ended, so `question_cli` on its own can offer
+// to resume one instead of needing the file paths again.
+
+const KEEP: usize = 20;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Recent {
+    pub files: Vec<PathBuf>, // absolute
+    pub mode: Mode,
+    pub answered: usize,
+    pub total: usize,
+    pub ended: String, // RFC 3339
+}
+
+fn path(config: &Config) -> Option<PathBuf> {
+    crate::config::data_dir(config.profile.as_deref()).map(|dir| dir.join("recent.json"))
+}
+
+/// The sessions, last ended first
+pub fn load(config: &Config) -> Result<Vec<Recent>> {
+    let Some(path) = path(config) else {
+        return Ok(vec![]);
+    };
+    match fs::read_to_string(&path) {
+        Ok(data) => serde_json::from_str(&data)
+            .wrap_err_with(|| format!("{} isn't a list of sessions", path.display())),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(vec![]),
+        Err(err) => Err(err).wrap_err_with(|| format!("could not read {}", path.display())),
+    }
+}
+
+/// Puts the session first, replacing an earlier one on the same files and mode
+pub fn record(config: &Config, session: Recent) -> Result<()> {
+    let Some(path) = path(config) else {
+        return Ok(());
+    };
+    let mut sessions = load(config)?;
+    sessions.retain(|recent| recent.files != session.files || recent.mode != session.mode);
+    sessions.insert(0, session);
+    sessions.truncate(KEEP);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).wrap_err_with(|| format!("could not create {}", dir.display()))?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(&sessions)? + "\n")
+        .wrap_err_with(|| format!("could not save {}", path.display()))
+}
+
+/// Lets the rater pick a session to resume, None if they quit
+pub fn pick(terminal: &mut Tui, sessions: &[Recent], config: &Config) -> Result<Option<usize>> {
+    let keymap = &config.keymap;
+    let mut selected = 0;
+    loop {
+        terminal.draw(|frame| ui(frame, sessions, selected, config))?;
+        let Event::Key(key_event) = event::read()? else {
+            continue;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key_event.code {
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Down => selected = (selected + 1).min(sessions.len() - 1),
+            KeyCode::Enter => return Ok(Some(selected)),
+            code if code == keymap.quit.0 || code == KeyCode::Esc => return Ok(None),
+            KeyCode::Char(c) => match c.to_digit(10) {
+                Some(number) if (1..=sessions.len()).contains(&(number as usize)) => {
+                    return Ok(Some(number as usize - 1))
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+fn ui(frame: &mut Frame, sessions: &[Recent], selected: usize, config: &Config) {
+    let palette = config.theme.palette();
+    let items: Vec<ListItem> = sessions
+        .iter()
+        .enumerate()
+        .map(|(i, recent)| {
+            let percent = match recent.total {
+                0 => 0,
+                total => recent.answered * 100 / total,
+            };
+            let files: Vec<String> = recent
+                .files
+                .iter()
+                .map(|file| file.display().to_string())
+                .collect();
+            // unfinished sessions stand out
+            let progress = format!("{:>3}%", percent);
+            let progress = match recent.answered < recent.total {
+                true => progress.fg(palette.missing).bold(),
+                false => progress.fg(palette.selected),
+            };
+            let ended = DateTime::parse_from_rfc3339(&recent.ended)
+                .map(|ended| {
+                    ended
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                })
+                .unwrap_or_default();
+            ListItem::new(vec![
+                Line::from(vec![
+                    format!("{}. ", i + 1).fg(palette.action_key).bold(),
+                    progress,
+                    format!("  {:<10} ", recent.mode.name()).into(),
+                    files.join(", ").into(),
+                ]),
+                Line::from(
+                    format!("        {}/{}  {}", recent.answered, recent.total, ended).dim(),
+                ),
+            ])
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title(format!(" {} ", t!("recent.title")))
+                .title_bottom(Line::from(t!("recent.controls")).centered())
+                .padding(Padding::horizontal(1)),
+        )
+        .highlight_style(Style::default().reversed());
+    frame.render_stateful_widget(
+        list,
+        frame.size(),
+        &mut ListState::default().with_selected(Some(selected)),
+    );
+}
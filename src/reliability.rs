@@ -0,0 +1,172 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::{
+    export::Rater,
+    i18n::t,
+    stats::{mean, variance},
+};
+
+// Internal consistency of a bank from raters' answered copies, printed by
+// `question_cli stats R01.json R02.json ...`: KR-20, which is Cronbach's alpha
+// for questions scored right or wrong, and what it would be without each
+// question, to find the ones that don't measure what the rest do. Unanswered
+// questions count as wrong, those without an answer key aren't scored.
+
+#[derive(Debug, Serialize)]
+pub struct Reliability {
+    pub copies: usize,
+    pub kr20: Option<f64>, // None without at least 2 questions and varying scores
+    pub items: Vec<Item>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Item {
+    pub question: usize, // from 1
+    pub id: Option<String>,
+    pub right: f64, // proportion of copies
+    pub alpha_if_deleted: Option<f64>,
+}
+
+pub fn of(raters: &[Rater]) -> Reliability {
+    let Some(first) = raters.first() else {
+        return Reliability {
+            copies: 0,
+            kr20: None,
+            items: vec![],
+        };
+    };
+    let scored: Vec<usize> = (0..first.bank.questions.len())
+        .filter(|&i| !first.bank.questions[i].answer.is_empty())
+        .collect();
+    // scores[copy][item]
+    let scores: Vec<Vec<f64>> = raters
+        .iter()
+        .map(|rater| {
+            let questions = &rater.bank.questions;
+            let right = |i: usize| questions[i].is_correct() == Some(true);
+            scored
+                .iter()
+                .map(|&i| if right(i) { 1.0 } else { 0.0 })
+                .collect()
+        })
+        .collect();
+    let items = scored
+        .iter()
+        .enumerate()
+        .map(|(column, &i)| Item {
+            question: i + 1,
+            id: first.bank.questions[i].id(),
+            right: mean(scores.iter().map(|row| row[column])),
+            alpha_if_deleted: alpha(&scores, Some(column)),
+        })
+        .collect();
+    Reliability {
+        copies: raters.len(),
+        kr20: alpha(&scores, None),
+        items,
+    }
+}
+
+// Cronbach's alpha of the items, without one of them
+fn alpha(scores: &[Vec<f64>], without: Option<usize>) -> Option<f64> {
+    let columns: Vec<usize> = (0..scores.first()?.len())
+        .filter(|&c| Some(c) != without)
+        .collect();
+    let k = columns.len() as f64;
+    if k < 2.0 {
+        return None;
+    }
+    let items: f64 = columns
+        .iter()
+        .map(|&c| variance(scores.iter().map(|row| row[c])))
+        .sum();
+    let total = variance(
+        scores
+            .iter()
+            .map(|row| columns.iter().map(|&c| row[c]).sum()),
+    );
+    (total > 0.0).then(|| k / (k - 1.0) * (1.0 - items / total))
+}
+
+impl Reliability {
+    pub fn print(&self, out: &mut impl Write) -> io::Result<()> {
+        let kr20 = match self.kr20 {
+            Some(kr20) => format!("{:.2}", kr20),
+            None => "-".to_string(),
+        };
+        writeln!(
+            out,
+            "{}",
+            t!("reliability.kr20", value = kr20, copies = self.copies)
+        )?;
+        writeln!(
+            out,
+            "  {:<14}{:>6}  {}",
+            t!("reliability.question"),
+            t!("reliability.right"),
+            t!("reliability.alpha_if_deleted")
+        )?;
+        for item in &self.items {
+            let alpha = match item.alpha_if_deleted {
+                Some(alpha) => format!("{:.2}", alpha),
+                None => "-".to_string(),
+            };
+            // the ones that raise alpha when taken out stand out
+            let mark = match (item.alpha_if_deleted, self.kr20) {
+                (Some(without), Some(with)) if without > with => " *",
+                _ => "",
+            };
+            let question = match &item.id {
+                Some(id) => format!("{} ({})", item.question, id),
+                None => item.question.to_string(),
+            };
+            writeln!(
+                out,
+                "  {:<14}{:>5.0}%  {}{}",
+                question,
+                item.right * 100.0,
+                alpha,
+                mark
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 4 copies of 3 questions, one more right on each: item variances
+    // .1875 + .25 + .1875 = .625, totals 3 2 1 0 vary by 1.25
+    fn scores() -> Vec<Vec<f64>> {
+        vec![
+            vec![1.0, 1.0, 1.0],
+            vec![1.0, 1.0, 0.0],
+            vec![1.0, 0.0, 0.0],
+            vec![0.0, 0.0, 0.0],
+        ]
+    }
+
+    #[test]
+    fn kr20_of_a_small_matrix() {
+        // 3/2 * (1 - .625 / 1.25)
+        let kr20 = alpha(&scores(), None).unwrap();
+        assert!((kr20 - 0.75).abs() < 1e-9, "{}", kr20);
+    }
+
+    #[test]
+    fn alpha_without_the_last_question() {
+        // .1875 + .25 = .4375 over totals 2 2 1 0 varying by .6875: 2 * 4/11
+        let without = alpha(&scores(), Some(2)).unwrap();
+        assert!((without - 8.0 / 11.0).abs() < 1e-9, "{}", without);
+    }
+
+    #[test]
+    fn no_alpha_without_two_questions_or_varying_scores() {
+        assert_eq!(alpha(&[vec![1.0, 0.0], vec![0.0, 1.0]], Some(0)), None);
+        assert_eq!(alpha(&[vec![1.0, 0.0], vec![0.0, 1.0]], None), None);
+    }
+}
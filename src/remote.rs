@@ -0,0 +1,118 @@
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{eyre::WrapErr, Result};
+
+use crate::{bank::Bank, crypt, i18n::t};
+
+// Question banks can be given as an http(s) link so raters don't have to
+// download attachments. The bank is saved in the current directory, named after
+// the end of the link, and answers are saved to that copy. The link is written
+// next to it (cardio.json => cardio.url), so running the same command again
+// resumes on that copy, while a file of the same name that came from elsewhere
+// is left alone: the download is named cardio-2.json (or -3, -4...) instead.
+// Encrypted banks keep their .json.age, and are saved still encrypted.
+//
+// Finished banks can be sent back the same way with --submit-url, which POSTs
+// each saved file as is, so raters don't have to email them.
+
+pub fn is_url(path: &Path) -> bool {
+    let path = path.to_string_lossy();
+    path.starts_with("https://") || path.starts_with("http://")
+}
+
+/// Downloads the bank at url unless there is a local copy, returns the copy's path
+pub fn fetch(url: &str) -> Result<PathBuf> {
+    let name = local_path(url);
+    let (stem, extension) = split_name(&name);
+    let mut local_path = name.clone();
+    for n in 2.. {
+        if !local_path.exists() {
+            break;
+        }
+        if fs::read_to_string(source_path(&local_path)).is_ok_and(|source| source.trim() == url) {
+            return Ok(local_path);
+        }
+        local_path = PathBuf::from(format!("{}-{}{}", stem, n, extension));
+    }
+    let mut data = vec![];
+    ureq::get(url)
+        .call()
+        .wrap_err_with(|| format!("could not download {}", url))?
+        .into_reader()
+        .read_to_end(&mut data)
+        .wrap_err_with(|| format!("could not download {}", url))?;
+    // checked before writing anything so a bad link doesn't leave a file behind
+    let json = match crypt::is_encrypted(&local_path) {
+        true => crypt::decrypt(&local_path, &data),
+        false => String::from_utf8(data.clone()).wrap_err("not text"),
+    };
+    Bank::from_json(&json.wrap_err_with(|| format!("in {}", url))?)
+        .wrap_err_with(|| format!("in {}", url))?;
+    fs::write(&local_path, data)
+        .wrap_err_with(|| format!("could not save {}", local_path.display()))?;
+    let source_path = source_path(&local_path);
+    fs::write(&source_path, format!("{}\n", url))
+        .wrap_err_with(|| format!("could not save {}", source_path.display()))?;
+    if local_path != name {
+        let (name, path) = (name.display(), local_path.display());
+        eprintln!("{}", t!("download.renamed", name = name, path = path));
+    }
+    Ok(local_path)
+}
+
+// where the copy was downloaded from: cardio.json (or cardio.json.age) => cardio.url
+fn source_path(local_path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.url", split_name(local_path).0))
+}
+
+// cardio.json.age => (cardio, .json.age)
+fn split_name(local_path: &Path) -> (String, String) {
+    let name = local_path.to_string_lossy();
+    let at = name.rfind(".json").unwrap_or(name.len());
+    (name[..at].to_string(), name[at..].to_string())
+}
+
+// https://example.com/banks/cardio.json?dl=1 => cardio.json, exam.json.age stays
+// exam.json.age
+fn local_path(url: &str) -> PathBuf {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let name = path
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty() && !name.contains(':'))
+        .unwrap_or("questions");
+    match name.ends_with(".json") || name.ends_with(".json.age") {
+        true => PathBuf::from(name),
+        false => PathBuf::from(format!("{}.json", name)),
+    }
+}
+
+/// POSTs a saved bank to a collection endpoint, with token as a bearer token
+pub fn submit(url: &str, token: Option<&str>, json_path: &Path) -> Result<()> {
+    let data =
+        fs::read(json_path).wrap_err_with(|| format!("could not read {}", json_path.display()))?;
+    let name = json_path.file_name().unwrap_or_default().to_string_lossy();
+    let content_type = match crate::crypt::is_encrypted(json_path) {
+        true => "application/octet-stream",
+        false => "application/json",
+    };
+    let mut request = ureq::post(url)
+        .set("Content-Type", content_type)
+        .set("X-Filename", &name);
+    if let Some(token) = token {
+        request = request.set("Authorization", &format!("Bearer {}", token));
+    }
+    request.send_bytes(&data).wrap_err_with(|| {
+        format!(
+            "could not submit {} to {}, it is saved and can be sent another way",
+            json_path.display(),
+            url
+        )
+    })?;
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use color_eyre::Result;
+use ratatui::{
+    style::{Style, Stylize},
+    text::Line,
+};
+use serde_json::{json, Value};
+
+use crate::{
+    bank::{Banks, Question},
+    i18n::t,
+    llm::{self, Client},
+    markdown, proctor,
+    prompts::Template,
+};
+
+// `question_cli review --llm questions.json` asks the model of the config's
+// [llm] table (see llm.rs) to critique each question against item-writing
+// guidelines. The critique is saved in the question for a person to go
+// through, `question_cli review questions.json` shows it next to the question
+// and the reviewer marks it valid (the question needs fixing), dismissed or
+// unsure, saved as "review_triage":
+//
+// "llm_review": {"model": "gpt-4o", "critique": "...", "reviewed": "2024-05-02T14:03:11Z"},
+// "review_triage": "valid"
+//
+// Every question is asked about on each run, but the replies are cached (see
+// llm.rs): only new or edited questions go to the model, so a run that stopped
+// can be started again. A question whose critique changes loses its triage.
+
+const GUIDELINES: &str = "You review multiple choice questions for medical exams against \
+item-writing guidelines (NBME): a focused stem that can be answered without the options, \
+one best answer, homogeneous and plausible distractors, no cues to the answer (grammar, \
+length, repeated words, absolute terms), no \"all/none of the above\", no negatives in \
+the stem, no trivia, no ambiguity and a correct answer key. List each problem you find on \
+its own line starting with \"- \", briefly and with how to fix it. If there are none, \
+reply \"No issues.\" only.";
+
+pub const TRIAGE: [&str; 3] = ["valid", "dismissed", "unsure"];
+
+/// The model's critique of the question, if it's been reviewed
+pub fn critique(question: &Question) -> Option<&str> {
+    question.extra.get("llm_review")?.get("critique")?.as_str()
+}
+
+/// What the reviewer made of the critique
+pub fn triage(question: &Question) -> Option<&str> {
+    question.extra.get("review_triage").and_then(Value::as_str)
+}
+
+fn prompt(question: &Question) -> String {
+    let mut prompt = llm::item(question);
+    if !question.answer.is_empty() {
+        prompt.push_str(&format!("\nAnswer key: {}\n", question.answer));
+    }
+    prompt
+}
+
+/// Has the model review the questions, saving after each new critique, with
+/// the template's prompt if given. Returns how many got one.
+pub fn run(banks: &mut Banks, client: &Client, template: Option<&Template>) -> Result<usize> {
+    let prompts: Vec<(usize, String)> = (0..banks.len())
+        .map(|index| {
+            let question = banks.question(index);
+            let prompt = match template {
+                Some(template) => template.render(question, 0),
+                None => prompt(question),
+            };
+            (index, prompt)
+        })
+        .collect();
+    let system = template.and_then(|template| template.system.as_deref());
+    let (mut done, mut reviewed) = (0, 0);
+    client.complete_all(system.unwrap_or(GUIDELINES), &prompts, |index, reply| {
+        done += 1;
+        eprintln!(
+            "{}",
+            t!(
+                "review.progress",
+                number = index + 1,
+                done = done,
+                total = prompts.len()
+            )
+        );
+        let question = banks.question(index);
+        let model = question
+            .extra
+            .get("llm_review")
+            .map(|review| &review["model"]);
+        if critique(question) == Some(reply.text.as_str()) && model == Some(&json!(client.model)) {
+            return Ok(());
+        }
+        banks.update(index, "review", |question| {
+            let review = json!({
+                "model": client.model,
+                "critique": reply.text,
+                "reviewed": proctor::now(),
+            });
+            question.extra.insert("llm_review".to_string(), review);
+            question.extra.remove("review_triage");
+            llm::note_usage(question, "review", client, reply.usage);
+            true
+        })?;
+        banks.save()?;
+        reviewed += 1;
+        Ok(())
+    })?;
+    Ok(reviewed)
+}
+
+/// The critique for the right panel
+pub fn panel(question: &Question) -> Vec<Line<'static>> {
+    let Some(critique) = critique(question) else {
+        return vec![Line::from(t!("review.none").italic())];
+    };
+    let model = question.extra["llm_review"]["model"]
+        .as_str()
+        .unwrap_or_default();
+    let mut lines = vec![
+        Line::from(t!("review.title", model = model).bold()),
+        Line::from(""),
+    ];
+    lines.extend(markdown::to_lines(critique, Style::default()));
+    lines
+}
+
+/// A triage value in the reviewer's language
+pub fn label(triage: &str) -> &'static str {
+    match triage {
+        "valid" => t!("review.valid"),
+        "dismissed" => t!("review.dismissed"),
+        _ => t!("review.unsure"),
+    }
+}
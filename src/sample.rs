@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+
+use clap::ValueEnum;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::bank::{Bank, Question};
+
+// `question_cli sample questions.json -n 20 -o pilot.json` draws a random subset
+// of the bank, eg. to pilot it. With --per tag (or topic) it's n questions of
+// each tag instead, so the subset keeps the blueprint of the full bank. A
+// question with several tags counts for each of them, and strata with fewer
+// than n questions are taken whole. Questions without a tag are a stratum of
+// their own. The subset keeps the order of the bank and its settings.
+
+/// What the strata are
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Stratum {
+    /// The questions' "tags", a question with several is in each of them
+    Tag,
+    /// The question's "topic" (topic mode), or the topic of its metadata
+    Topic,
+}
+
+impl Stratum {
+    fn of(self, question: &Question) -> Vec<String> {
+        match self {
+            Stratum::Tag => question.tags().into_iter().map(str::to_string).collect(),
+            Stratum::Topic => question
+                .topic()
+                .or_else(|| question.metadata.as_ref()?.topic.as_deref())
+                .map(str::to_string)
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// (stratum, drawn, out of) for the summary, None for the questions without one
+pub type Drawn = Vec<(Option<String>, usize, usize)>;
+
+/// Keeps count questions of the bank, or of each stratum with per
+pub fn draw(bank: &mut Bank, count: usize, per: Option<Stratum>, seed: Option<u64>) -> Drawn {
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    // the questions of each stratum, by their index in the bank
+    let mut strata: BTreeMap<Option<String>, Vec<usize>> = BTreeMap::new();
+    for (i, question) in bank.questions.iter().enumerate() {
+        let names = per.map(|per| per.of(question)).unwrap_or_default();
+        match names.is_empty() {
+            true => strata.entry(None).or_default().push(i),
+            false => {
+                for name in names {
+                    strata.entry(Some(name)).or_default().push(i);
+                }
+            }
+        }
+    }
+    let mut kept = vec![false; bank.questions.len()];
+    let mut drawn = vec![];
+    for (name, mut indexes) in strata {
+        let total = indexes.len();
+        // a question drawn for another of its tags isn't drawn again
+        let already = indexes.iter().filter(|&&i| kept[i]).count();
+        indexes.retain(|&i| !kept[i]);
+        indexes.shuffle(&mut rng);
+        let picked = count.saturating_sub(already).min(indexes.len());
+        for &i in &indexes[..picked] {
+            kept[i] = true;
+        }
+        drawn.push((name, already + picked, total));
+    }
+    let mut i = 0;
+    bank.questions.retain(|_| {
+        i += 1;
+        kept[i - 1]
+    });
+    drawn
+}
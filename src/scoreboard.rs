@@ -0,0 +1,233 @@
+use std::{sync::Mutex, time::Duration};
+
+use color_eyre::Result;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    prelude::*,
+    widgets::{block::Title, Block, Borders, Padding, Paragraph, Wrap},
+};
+
+use crate::{
+    bank::{Bank, Question},
+    config::Config,
+    i18n::t,
+    live::Room,
+    markdown,
+    mode::Mode,
+    tui::Tui,
+};
+
+// The instructor's side of a live session (`question_cli host`): how the
+// responses to a question are spread, with the correct option marked, and a
+// running scoreboard of the participants. Redrawn as responses come in.
+
+pub fn run(
+    terminal: &mut Tui,
+    bank: &Bank,
+    mode: Mode,
+    room: &Mutex<Room>,
+    url: &str,
+    config: &Config,
+) -> Result<()> {
+    let keymap = &config.keymap;
+    let mut index = 0;
+    loop {
+        terminal.draw(|frame| {
+            let room = room.lock().unwrap();
+            ui(frame, bank, mode, &room, index, url, config);
+        })?;
+        // wake up regularly to show new responses
+        if !event::poll(Duration::from_millis(250))? {
+            continue;
+        }
+        match event::read()? {
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                match key_event.code {
+                    code if code == keymap.quit.0 || code == KeyCode::Esc => return Ok(()),
+                    code if code == keymap.prev.0 => {
+                        index = (index + bank.questions.len() - 1) % bank.questions.len()
+                    }
+                    code if code == keymap.next.0 => index = (index + 1) % bank.questions.len(),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn ui(
+    frame: &mut Frame,
+    bank: &Bank,
+    mode: Mode,
+    room: &Room,
+    index: usize,
+    url: &str,
+    config: &Config,
+) {
+    let palette = config.theme.palette();
+    let keymap = &config.keymap;
+    let question = &bank.questions[index];
+    let number = index + 1;
+
+    let outer_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(2),
+        ])
+        .split(frame.size());
+    let inner_layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(outer_layout[1]);
+
+    let connected = room.participants.values().filter(|p| p.connected).count();
+    let top = match room.message.is_empty() {
+        true => t!("live.title", url = url, connected = connected),
+        false => room.message.clone(),
+    };
+    frame.render_widget(
+        Paragraph::new(top).alignment(Alignment::Right),
+        outer_layout[0],
+    );
+
+    // the question with a bar per response
+    let responses: Vec<&String> = room
+        .participants
+        .values()
+        .filter_map(|participant| participant.responses.get(&number))
+        .collect();
+    let bar_width = inner_layout[0].width.saturating_sub(16) as usize;
+    let mut lines = markdown::to_lines(&question.question, Style::default());
+    lines.push(Line::default());
+    for (value, label) in choices(question, mode) {
+        let count = responses
+            .iter()
+            .filter(|response| ***response == value)
+            .count();
+        let correct = mode == Mode::Answer && value == question.answer;
+        let style = match correct {
+            true => Style::default().fg(palette.selected).bold(),
+            false => Style::default().fg(palette.option),
+        };
+        let mark = if correct { " ✓" } else { "" };
+        lines.push(Line::styled(format!("{}{}", label, mark), style));
+        let percent = count * 100 / responses.len().max(1);
+        let bar = "█".repeat(bar_width * count / responses.len().max(1));
+        lines.push(Line::from(vec![
+            bar.fg(palette.progress),
+            format!(" {} ({}%)", count, percent).into(),
+        ]));
+    }
+    let question_title = Title::from(Line::from(vec![
+        t!("question.label").into(),
+        number.to_string().fg(palette.action_key),
+        t!("question.of").into(),
+        bank.questions.len().to_string().fg(palette.action_key),
+        " ".into(),
+    ]));
+    let responses_title = Title::from(format!(
+        " {} ",
+        t!("live.responses", count = responses.len())
+    ))
+    .alignment(Alignment::Right);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(
+                Block::new()
+                    .borders(Borders::TOP | Borders::RIGHT)
+                    .title(question_title)
+                    .title(responses_title)
+                    .padding(Padding::uniform(1)),
+            )
+            .wrap(Wrap { trim: false }),
+        inner_layout[0],
+    );
+
+    // scoreboard, by correct answers when there is a key and then by answers given
+    let mut rows: Vec<(&String, usize, usize, bool)> = room
+        .participants
+        .iter()
+        .map(|(rater, participant)| {
+            let correct = participant
+                .responses
+                .iter()
+                .filter(|(number, response)| {
+                    bank.questions
+                        .get(number.wrapping_sub(1))
+                        .is_some_and(|q| !q.answer.is_empty() && q.answer == **response)
+                })
+                .count();
+            (
+                rater,
+                correct,
+                participant.responses.len(),
+                participant.connected,
+            )
+        })
+        .collect();
+    rows.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.cmp(&a.2)).then(a.0.cmp(b.0)));
+    let scored = mode == Mode::Answer;
+    let mut lines = vec![];
+    for (rank, (rater, correct, answered, connected)) in rows.into_iter().enumerate() {
+        let dot = match connected {
+            true => "●".fg(palette.selected),
+            false => "○".into(),
+        };
+        let score = match scored {
+            true => t!("live.score", correct = correct, answered = answered),
+            false => t!("live.answered", answered = answered),
+        };
+        lines.push(Line::from(vec![
+            format!("{:>2}. ", rank + 1).fg(palette.action_key),
+            dot,
+            format!(" {}  ", rater).bold(),
+            score.into(),
+        ]));
+    }
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::new()
+                .borders(Borders::TOP | Borders::LEFT)
+                .title(format!(" {} ", t!("live.scoreboard")))
+                .padding(Padding::uniform(1)),
+        ),
+        inner_layout[1],
+    );
+
+    let controls = Line::from(vec![
+        t!("controls.prev").into(),
+        keymap.prev.label().fg(palette.key).bold(),
+        t!("controls.next").into(),
+        keymap.next.label().fg(palette.key).bold(),
+        t!("controls.quit").into(),
+        format!("{} ", keymap.quit.label())
+            .fg(palette.quit_key)
+            .bold(),
+    ]);
+    frame.render_widget(
+        Block::new()
+            .borders(Borders::TOP)
+            .title(Title::from(controls).alignment(Alignment::Center)),
+        outer_layout[2],
+    );
+}
+
+// (response value, label) of everything a participant can pick
+fn choices(question: &Question, mode: Mode) -> Vec<(String, String)> {
+    match mode {
+        Mode::Classify => vec![
+            ("true".to_string(), t!("controls.true").trim().to_string()),
+            ("false".to_string(), t!("controls.false").trim().to_string()),
+        ],
+        Mode::Rate => (1..=5).map(|n| (n.to_string(), n.to_string())).collect(),
+        _ => question
+            .options
+            .iter()
+            .enumerate()
+            .map(|(i, option)| (option.clone(), format!("{}. {}", i + 1, option)))
+            .collect(),
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    env, fs,
+    io::IsTerminal,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{
+    eyre::{bail, WrapErr},
+    Result,
+};
+
+use crate::bank;
+
+// With --per-rater every rater works on their own copy of the bank, made the
+// first time they open it: questions.json => questions.alice.json for rater_id
+// "alice". This is for hosting sessions on a shared machine.
+//
+// `question_cli ssh` is the server side of that over SSH: sshd runs it for
+// every connection to the quiz account, in /etc/ssh/sshd_config:
+//
+// Match User quiz
+//     ForceCommand /usr/local/bin/question_cli ssh --raters /srv/banks/raters /srv/banks/cardio.json
+//     ExposeAuthInfo yes
+//
+// With ExposeAuthInfo sshd tells it the key the rater logged in with
+// ($SSH_USER_AUTH), which is looked up in the raters file for their rater_id:
+//
+// alice ssh-ed25519 AAAA... alice@laptop
+//
+// so `ssh quiz@host` opens the interface on alice's own copy, and every
+// connection is its own process and session. The keys also have to be in
+// ~quiz/.ssh/authorized_keys for sshd to let them in.
+//
+// Named sessions (--session attempt2) work the same way, for taking a quiz
+// again: questions.json => questions.attempt2.json, made without the responses
+// so each attempt starts afresh and they can be compared afterwards.
+
+// questions.json => questions.<label>.json, the label goes before the
+// extensions: exam.json.age => exam.<label>.json.age
+pub fn copy_path(json_path: &Path, label: &str, what: &str) -> Result<PathBuf> {
+    if label.is_empty()
+        || !label
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        bail!(
+            "{} '{}' can't be used in a file name, use letters, numbers, - and _",
+            what,
+            label
+        );
+    }
+    let name = json_path.file_name().unwrap_or_default().to_string_lossy();
+    let copy_name = match name.find(".json") {
+        Some(at) => format!("{}.{}{}", &name[..at], label, &name[at..]),
+        None => format!("{}.{}", name, label),
+    };
+    Ok(json_path.with_file_name(copy_name))
+}
+
+/// The rater_id of the key the SSH connection was made with, from the raters
+/// file
+pub fn ssh_rater(raters: &Path) -> Result<String> {
+    if env::var_os("SSH_CONNECTION").is_none() {
+        bail!("question_cli ssh is run by sshd for each connection, see the README");
+    }
+    let Some(auth) = env::var_os("SSH_USER_AUTH") else {
+        bail!("sshd doesn't say which key was used, set ExposeAuthInfo yes in sshd_config");
+    };
+    if !std::io::stdout().is_terminal() {
+        bail!("there's no terminal, connect with ssh -t");
+    }
+    // "publickey ssh-ed25519 AAAA...", one line per method used
+    let auth = fs::read_to_string(&auth)
+        .wrap_err_with(|| format!("could not read {}", Path::new(&auth).display()))?;
+    let keys: Vec<(&str, &str)> = auth
+        .lines()
+        .filter_map(|line| line.strip_prefix("publickey "))
+        .filter_map(|key| key.split_once(' '))
+        .map(|(kind, blob)| (kind, blob.split_whitespace().next().unwrap_or_default()))
+        .collect();
+    let list = fs::read_to_string(raters)
+        .wrap_err_with(|| format!("could not read {}", raters.display()))?;
+    for line in list.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let (Some(rater_id), Some(kind), Some(blob)) = (words.next(), words.next(), words.next())
+        else {
+            bail!(
+                "{}: expected a rater_id and a key on \"{}\"",
+                raters.display(),
+                line
+            );
+        };
+        if keys.contains(&(kind, blob)) {
+            return Ok(rater_id.to_string());
+        }
+    }
+    bail!("the key you connected with isn't in the raters file, ask for it to be added")
+}
+
+/// The rater's copy of a bank, copied from it if it doesn't exist yet
+pub fn rater_copy(json_path: &Path, rater_id: &str) -> Result<PathBuf> {
+    let copy_path = copy_path(json_path, rater_id, "rater_id")?;
+    if !copy_path.exists() {
+        fs::copy(json_path, &copy_path).wrap_err_with(|| {
+            format!(
+                "could not copy {} to {}",
+                json_path.display(),
+                copy_path.display()
+            )
+        })?;
+    }
+    Ok(copy_path)
+}
+
+/// The named session's copy of a bank, made from it without the responses if
+/// it doesn't exist yet
+pub fn session_copy(json_path: &Path, name: &str) -> Result<PathBuf> {
+    let copy_path = copy_path(json_path, name, "session name")?;
+    if !copy_path.exists() {
+        let mut bank = bank::load_json(json_path)?;
+        for question in &mut bank.questions {
+            question.clear_responses();
+        }
+        // the time and exam times of the other attempt
+        bank.time_spent = None;
+        bank.exam = None;
+        bank::save_json(&copy_path, &bank)
+            .wrap_err_with(|| format!("could not create {}", copy_path.display()))?;
+    }
+    Ok(copy_path)
+}
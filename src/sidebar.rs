@@ -0,0 +1,54 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, List, ListItem, ListState},
+};
+
+use crate::{bank::Banks, bookmarks::Bookmarks, mode::Mode, theme::Palette};
+
+// The question index down the left of the screen (Tab), with where each
+// question stands: ✓ done, ⚑ flagged for review, ↷ skipped, · still missing.
+// While it's open the up and down arrows go through the list.
+
+pub const WIDTH: u16 = 28;
+
+pub fn render(
+    frame: &mut Frame,
+    area: Rect,
+    banks: &Banks,
+    mode: Mode,
+    current: usize,
+    bookmarks: &Bookmarks,
+    palette: &Palette,
+) {
+    let items: Vec<ListItem> = banks
+        .questions()
+        .enumerate()
+        .map(|(index, question)| {
+            let glyph = match (mode.is_done(question), question.is_flagged()) {
+                (_, true) => "⚑".fg(palette.info),
+                _ if mode.is_skipped(question) => "↷".fg(palette.option),
+                (true, false) => "✓".fg(palette.selected),
+                (false, false) => "·".fg(palette.missing),
+            };
+            let bookmark = match bookmarks.contains(&index) {
+                true => "★",
+                false => " ",
+            };
+            let stem = question.question.trim();
+            let stem = stem.lines().next().unwrap_or_default();
+            ListItem::new(Line::from(vec![
+                glyph,
+                format!("{}{:>4} ", bookmark, index + 1).fg(palette.action_key),
+                stem.to_string().into(),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::new().borders(Borders::TOP | Borders::RIGHT))
+        .highlight_style(Style::default().reversed());
+    frame.render_stateful_widget(
+        list,
+        area,
+        &mut ListState::default().with_selected(Some(current)),
+    );
+}
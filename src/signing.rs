@@ -0,0 +1,61 @@
+use color_eyre::{eyre::WrapErr, Result};
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+
+use crate::bank::to_pretty;
+
+// With signing_key set in the config file, saved banks get a "signature": an
+// HMAC-SHA256, with that shared secret, of the file as it would be saved
+// without the signature. `question_cli verify` checks that a returned file
+// wasn't edited afterwards (eg. answers changed in a text editor), which can't
+// be done without the key.
+
+const PREFIX: &str = "hmac-sha256:";
+
+/// Whether a file's signature matches its contents
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Valid,
+    Modified,
+    Unsigned,
+}
+
+/// Adds the signature as the last key of a bank object
+pub fn sign(value: &mut Value, key: &str) -> Result<()> {
+    let Some(map) = value.as_object_mut() else {
+        return Ok(()); // a plain list, which Bank doesn't produce when signing
+    };
+    map.shift_remove("signature");
+    let signature = mac(&to_pretty(value)?, key).finalize().into_bytes();
+    value["signature"] = format!("{}{}", PREFIX, hex::encode(signature)).into();
+    Ok(())
+}
+
+/// Checks the contents of a saved file against its signature
+pub fn verify(data: &str, key: &str) -> Result<Verdict> {
+    let mut value: Value = serde_json::from_str(data).wrap_err("JSON not parsable")?;
+    let signature = match value
+        .as_object_mut()
+        .and_then(|map| map.shift_remove("signature"))
+    {
+        Some(Value::String(signature)) => signature,
+        _ => return Ok(Verdict::Unsigned),
+    };
+    let Some(Ok(signature)) = signature.strip_prefix(PREFIX).map(hex::decode) else {
+        return Ok(Verdict::Modified);
+    };
+    Ok(
+        match mac(&to_pretty(&value)?, key).verify_slice(&signature) {
+            Ok(()) => Verdict::Valid,
+            Err(_) => Verdict::Modified,
+        },
+    )
+}
+
+fn mac(data: &str, key: &str) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("HMAC takes any key length");
+    mac.update(data.as_bytes());
+    mac
+}
@@ -0,0 +1,108 @@
+use rand::seq::SliceRandom;
+use ratatui::{prelude::*, widgets::ListItem};
+use serde_json::Value;
+
+use crate::{
+    bank::{Banks, Question},
+    i18n::t,
+    popup,
+    theme::Palette,
+};
+
+// The order questions are gone through in, picked from a menu (`o`). Only the
+// navigation changes, questions are saved in the order of the file.
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Order {
+    /// As in the files
+    #[default]
+    Original,
+    Random,
+    /// Shortest first, stem and options together
+    Length,
+    /// By the questions' "difficulty" field, lowest first
+    Difficulty,
+    /// By their first tag, alphabetically
+    Tag,
+}
+
+pub const ORDERS: [Order; 5] = [
+    Order::Original,
+    Order::Random,
+    Order::Length,
+    Order::Difficulty,
+    Order::Tag,
+];
+
+impl Order {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Order::Original => t!("sort.original"),
+            Order::Random => t!("sort.random"),
+            Order::Length => t!("sort.length"),
+            Order::Difficulty => t!("sort.difficulty"),
+            Order::Tag => t!("sort.tag"),
+        }
+    }
+}
+
+/// Reorders the questions gone through, filtered out ones stay out
+pub fn apply(banks: &mut Banks, order: Order) {
+    let mut questions: Vec<(usize, &Question)> = (0..banks.len())
+        .map(|index| (banks.absolute(index), banks.question(index)))
+        .collect();
+    match order {
+        Order::Original => questions.sort_by_key(|(index, _)| *index),
+        Order::Random => questions.shuffle(&mut rand::thread_rng()),
+        Order::Length => questions.sort_by_key(|(_, question)| length(question)),
+        // questions without one go last
+        Order::Difficulty => questions.sort_by(|(_, a), (_, b)| {
+            let key = |q: &Question| q.extra.get("difficulty").and_then(Value::as_f64);
+            match (key(a), key(b)) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (a, b) => b.is_some().cmp(&a.is_some()),
+            }
+        }),
+        Order::Tag => questions.sort_by_key(|(_, question)| {
+            let tag = question.tags().into_iter().min().map(str::to_lowercase);
+            (tag.is_none(), tag)
+        }),
+    }
+    let view = questions.into_iter().map(|(index, _)| index).collect();
+    banks.set_view(view);
+}
+
+fn length(question: &Question) -> usize {
+    question.question.chars().count()
+        + question
+            .options
+            .iter()
+            .map(|option| option.chars().count())
+            .sum::<usize>()
+}
+
+/// The menu of orders, the current one marked
+pub fn render_menu(
+    frame: &mut Frame,
+    area: Rect,
+    current: Order,
+    selected: usize,
+    palette: &Palette,
+) {
+    let items: Vec<ListItem> = ORDERS
+        .iter()
+        .enumerate()
+        .map(|(i, order)| {
+            let mark = match *order == current {
+                true => " ✓",
+                false => "",
+            };
+            ListItem::new(Line::from(vec![
+                format!("{}. ", i + 1).fg(palette.action_key).bold(),
+                order.label().into(),
+                mark.fg(palette.selected),
+            ]))
+        })
+        .collect();
+    popup::list(frame, area, t!("sort.title"), items, selected);
+}
@@ -0,0 +1,203 @@
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{
+    eyre::{bail, WrapErr},
+    Result,
+};
+
+use crate::{bank::Banks, i18n::t};
+
+// `question_cli spellcheck questions.json` reports the likely typos in each
+// question's stem and options, with the closest known word, for reviewers to
+// fix before the bank goes out. Words are looked up in a word list (the
+// config's dictionary, /usr/share/dict/words by default), the medical terms of
+// dictionaries/medical.txt and the allowlists given (spelling_allowlist or
+// --allow), all one word per line. Abbreviations (ECG, mRNA), words with
+// digits, math and code are left alone.
+
+const DEFAULT_DICTIONARY: &str = "/usr/share/dict/words";
+const MEDICAL: &str = include_str!("../dictionaries/medical.txt");
+
+pub struct Dictionary {
+    words: HashSet<String>, // lowercase
+}
+
+// the words of a list, without comments and hunspell's /flags
+fn words(list: &str) -> impl Iterator<Item = String> + '_ {
+    list.lines()
+        .map(|line| line.split('/').next().unwrap_or_default().trim())
+        .filter(|word| !word.is_empty() && !word.starts_with('#'))
+        .map(str::to_lowercase)
+}
+
+impl Dictionary {
+    pub fn load(dictionary: Option<&Path>, allowlists: &[PathBuf]) -> Result<Dictionary> {
+        let path = dictionary.unwrap_or(Path::new(DEFAULT_DICTIONARY));
+        let list = match fs::read_to_string(path) {
+            Ok(list) => list,
+            Err(_) if dictionary.is_none() => bail!(
+                "no word list in {}, install one (eg. the wamerican package) or set dictionary in the config file",
+                DEFAULT_DICTIONARY
+            ),
+            Err(err) => {
+                return Err(err).wrap_err_with(|| format!("could not read {}", path.display()))
+            }
+        };
+        let mut words: HashSet<String> = self::words(&list).chain(self::words(MEDICAL)).collect();
+        for allowlist in allowlists {
+            let list = fs::read_to_string(allowlist)
+                .wrap_err_with(|| format!("could not read {}", allowlist.display()))?;
+            words.extend(self::words(&list));
+        }
+        Ok(Dictionary { words })
+    }
+
+    /// Known as it is, or as a plural or possessive of a known word
+    pub fn knows(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        let word = word.trim_end_matches("'s").trim_end_matches('\'');
+        self.words.contains(word)
+            || ["s", "es"].iter().any(|suffix| {
+                word.strip_suffix(suffix)
+                    .is_some_and(|stem| self.words.contains(stem))
+            })
+    }
+
+    /// The closest known word, at most 2 edits away
+    pub fn suggest(&self, word: &str) -> Option<&str> {
+        let word = word.to_lowercase();
+        let length = word.chars().count();
+        self.words
+            .iter()
+            .filter(|known| known.chars().count().abs_diff(length) <= 2)
+            .map(|known| (distance(word.chars(), known.chars()), known))
+            .filter(|(distance, _)| *distance <= 2)
+            .min()
+            .map(|(_, known)| known.as_str())
+    }
+
+    /// The words of text it doesn't know, in order and once each
+    pub fn typos(&self, text: &str) -> Vec<String> {
+        let mut typos: Vec<String> = vec![];
+        for word in checked_words(text) {
+            if !self.knows(word) && !typos.iter().any(|typo| typo == word) {
+                typos.push(word.to_string());
+            }
+        }
+        typos
+    }
+}
+
+// the words worth checking, without $math$, `code` and links
+fn checked_words(text: &str) -> Vec<&str> {
+    let mut words = vec![];
+    let mut skipping: Option<char> = None;
+    for token in text.split_whitespace() {
+        if let Some(end) = skipping {
+            if token.contains(end) {
+                skipping = None;
+            }
+            continue;
+        }
+        match token.chars().next() {
+            // till the closing one, which may be in the same token
+            Some(open @ ('$' | '`')) => {
+                if !token[1..].contains(open) {
+                    skipping = Some(open);
+                }
+                continue;
+            }
+            _ if token.contains("://") || token.starts_with("www.") => continue,
+            _ => {}
+        }
+        for word in token.split(|c: char| !c.is_alphanumeric() && c != '\'') {
+            let word = word.trim_matches('\'');
+            let letters = word.chars().count();
+            // abbreviations, units and doses, and mixed case like mRNA
+            let skip = letters < 3
+                || word.chars().any(|c| c.is_numeric())
+                || word.chars().skip(1).any(char::is_uppercase);
+            if !skip {
+                words.push(word);
+            }
+        }
+    }
+    words
+}
+
+/// Levenshtein distance, between the chars of words or the words of stems
+pub fn distance<T: PartialEq>(
+    a: impl IntoIterator<Item = T>,
+    b: impl IntoIterator<Item = T>,
+) -> usize {
+    let b: Vec<T> = b.into_iter().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.into_iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Prints the typos of each question that has any, and how many have. True if
+/// any do.
+pub fn print(banks: &Banks, dictionary: &Dictionary, out: &mut impl Write) -> io::Result<bool> {
+    let mut flagged = 0;
+    for index in 0..banks.len() {
+        let question = banks.question(index);
+        let mut parts = vec![(t!("spelling.stem").to_string(), &question.question)];
+        parts.extend(
+            question
+                .options
+                .iter()
+                .enumerate()
+                .map(|(i, option)| (t!("spelling.option", number = i + 1), option)),
+        );
+        let mut lines = vec![];
+        for (part, text) in parts {
+            let typos: Vec<String> = dictionary
+                .typos(text)
+                .into_iter()
+                .map(|typo| match dictionary.suggest(&typo) {
+                    Some(known) => format!("{} ({}?)", typo, known),
+                    None => typo,
+                })
+                .collect();
+            if !typos.is_empty() {
+                lines.push(format!("  {}: {}", part, typos.join(", ")));
+            }
+        }
+        if lines.is_empty() {
+            continue;
+        }
+        flagged += 1;
+        let id = question
+            .id()
+            .map(|id| format!(" ({})", id))
+            .unwrap_or_default();
+        writeln!(
+            out,
+            "{}",
+            t!("spelling.question", number = index + 1, id = id)
+        )?;
+        for line in lines {
+            writeln!(out, "{}", line)?;
+        }
+    }
+    writeln!(
+        out,
+        "{}",
+        t!("spelling.summary", flagged = flagged, total = banks.len())
+    )?;
+    Ok(flagged > 0)
+}
@@ -0,0 +1,61 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{eyre::WrapErr, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{bookmarks::Bookmarks, notes};
+
+// Where the rater was in a session (the question on screen, bookmarks...) is
+// kept in a file of their own next to the first bank, so reopening it picks up
+// exactly there without adding noise to the shared bank:
+// questions.json => questions.alice.session.json. It only applies to the same
+// set of files, opening them with others starts afresh. Time spent on the
+// questions doesn't go here, track_time saves it in the bank itself.
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct State {
+    files: Vec<PathBuf>, // absolute
+    pub question: usize, // index in the session, from 0
+    pub bookmarks: Bookmarks,
+    pub show_metadata: bool,
+    pub show_sidebar: bool,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl State {
+    /// The state saved for this set of files, or a fresh one
+    pub fn load(json_paths: &[&Path], user: Option<&str>) -> Result<State> {
+        let files: Vec<PathBuf> = json_paths
+            .iter()
+            .map(|path| fs::canonicalize(path).unwrap_or(path.to_path_buf()))
+            .collect();
+        let path = notes::personal_path(json_paths[0], user, "session");
+        let saved = match fs::read_to_string(&path) {
+            Ok(data) => serde_json::from_str(&data)
+                .wrap_err_with(|| format!("{} isn't a session file", path.display()))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => State::default(),
+            Err(err) => {
+                return Err(err).wrap_err_with(|| format!("could not read {}", path.display()))
+            }
+        };
+        Ok(match saved.files == files {
+            true => State { path, ..saved },
+            false => State {
+                files,
+                path,
+                ..State::default()
+            },
+        })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let data = serde_json::to_string_pretty(self)? + "\n";
+        fs::write(&self.path, data)
+            .wrap_err_with(|| format!("could not save the session in {}", self.path.display()))
+    }
+}
@@ -0,0 +1,155 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::{
+    bank::{Bank, Classification, Question},
+    i18n::t,
+    reliability::Reliability,
+};
+
+/// Progress through a bank, or raters' copies of it added up, printed by
+/// `question_cli stats`
+#[derive(Debug, Serialize)]
+pub struct Stats {
+    pub questions: usize,
+    pub answered: usize,
+    pub correct: usize, // human_answer matches the answer key
+    pub classified: usize,
+    pub higher_order: usize,
+    pub unsure: usize,
+    // with answer_history, answers changed after the first one and which way
+    pub changed: usize,
+    pub wrong_to_right: usize,
+    pub right_to_wrong: usize,
+    // with several copies, see reliability.rs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reliability: Option<Reliability>,
+}
+
+impl Stats {
+    pub fn of(banks: &[&Bank]) -> Stats {
+        let questions: Vec<&Question> = banks.iter().flat_map(|bank| &bank.questions).collect();
+        // (first answer right, last answer right) for the answers that changed
+        let changes: Vec<(bool, bool)> = questions
+            .iter()
+            .filter_map(|q| {
+                let history = q.answer_history();
+                let (first, last) = (history.first()?, history.last()?);
+                (first != last).then(|| (*first == q.answer, *last == q.answer))
+            })
+            .collect();
+        Stats {
+            questions: questions.len(),
+            answered: questions
+                .iter()
+                .filter(|q| q.human_answer.is_some())
+                .count(),
+            correct: questions
+                .iter()
+                .filter(|q| q.human_answer.as_ref() == Some(&q.answer))
+                .count(),
+            classified: questions
+                .iter()
+                .filter(|q| q.is_higher_order.is_some())
+                .count(),
+            higher_order: questions
+                .iter()
+                .filter(|q| q.is_higher_order == Some(Classification::HigherOrder))
+                .count(),
+            unsure: questions
+                .iter()
+                .filter(|q| q.is_higher_order == Some(Classification::Unsure))
+                .count(),
+            changed: changes.len(),
+            wrong_to_right: changes.iter().filter(|c| **c == (false, true)).count(),
+            right_to_wrong: changes.iter().filter(|c| **c == (true, false)).count(),
+            reliability: None,
+        }
+    }
+
+    pub fn print(&self, out: &mut impl Write) -> io::Result<()> {
+        let percent = |n: usize| match self.questions {
+            0 => 0,
+            total => n * 100 / total,
+        };
+        writeln!(out, "{}", t!("stats.questions", count = self.questions))?;
+        writeln!(
+            out,
+            "{}",
+            t!(
+                "stats.answered",
+                count = self.answered,
+                percent = percent(self.answered)
+            )
+        )?;
+        writeln!(
+            out,
+            "{}",
+            t!(
+                "stats.correct",
+                count = self.correct,
+                answered = self.answered
+            )
+        )?;
+        writeln!(
+            out,
+            "{}",
+            t!(
+                "stats.classified",
+                count = self.classified,
+                percent = percent(self.classified)
+            )
+        )?;
+        writeln!(
+            out,
+            "{}",
+            t!(
+                "stats.higher_order",
+                count = self.higher_order,
+                classified = self.classified
+            )
+        )?;
+        writeln!(
+            out,
+            "{}",
+            t!(
+                "stats.unsure",
+                count = self.unsure,
+                classified = self.classified
+            )
+        )?;
+        if self.changed > 0 {
+            writeln!(
+                out,
+                "{}",
+                t!(
+                    "stats.changed",
+                    count = self.changed,
+                    gained = self.wrong_to_right,
+                    lost = self.right_to_wrong
+                )
+            )?;
+        }
+        if let Some(reliability) = &self.reliability {
+            writeln!(out)?;
+            reliability.print(out)?;
+        }
+        Ok(())
+    }
+}
+
+/// 0 for no values, shared by reliability.rs and rasch.rs
+pub fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+    match count {
+        0 => 0.0,
+        count => sum / count as f64,
+    }
+}
+
+/// Population variance, dividing by the count
+pub fn variance(values: impl Iterator<Item = f64> + Clone) -> f64 {
+    let average = mean(values.clone());
+    mean(values.map(|v| (v - average).powi(2)))
+}
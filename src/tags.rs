@@ -0,0 +1,57 @@
+use ratatui::{prelude::*, widgets::ListItem};
+
+use crate::{
+    bank::{Banks, Question},
+    i18n::t,
+    popup::{self, Finder},
+    theme::Palette,
+};
+
+// Questions' "tags" (content areas, systems...) can be edited while going
+// through the bank in any mode: `T` opens the tag finder, type part of a tag
+// to find it among those already used in the session, and Enter adds it to the
+// current question or removes it if it's there. A tag nobody has used yet is
+// offered too. The finder stays open for the next tag until Esc.
+
+/// Every tag used in the session, sorted
+pub fn all(banks: &Banks) -> Vec<String> {
+    let mut tags: Vec<String> = banks
+        .questions()
+        .flat_map(Question::tags)
+        .map(str::to_string)
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// The finder over the middle of area, the question's own tags checked
+pub fn render_finder(
+    frame: &mut Frame,
+    area: Rect,
+    finder: &Finder,
+    tags: &[String],
+    question: &Question,
+    palette: &Palette,
+) {
+    let own = question.tags();
+    let mut items: Vec<ListItem> = finder
+        .choices(tags)
+        .into_iter()
+        .map(|(tag, new)| match new {
+            true => ListItem::new(t!("tags.add", tag = tag).fg(palette.action_key)),
+            false => {
+                let mark = match own.contains(&tag.as_str()) {
+                    true => "✓ ".fg(palette.selected),
+                    false => "  ".into(),
+                };
+                ListItem::new(Line::from(vec![mark, tag.into()]))
+            }
+        })
+        .collect();
+    if items.is_empty() {
+        items.push(ListItem::new(t!("tags.none").dim()));
+    }
+    let title = format!("{} {}▏", t!("tags.search"), finder.query);
+    popup::list(frame, area, &title, items, finder.selected);
+}
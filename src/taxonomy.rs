@@ -0,0 +1,57 @@
+use ratatui::{prelude::*, widgets::ListItem};
+
+use crate::{
+    bank::Banks,
+    i18n::t,
+    popup::{self, Finder},
+    theme::Palette,
+};
+
+// The topics of the curriculum blueprint questions are mapped to in topic
+// mode, saved in their "topic" field. They're paths from the broadest topic
+// down, in the config or the bank's own "taxonomy" (or both):
+//
+// taxonomy = ["Cardiology/Arrhythmia", "Cardiology/Heart failure", "Renal/AKI"]
+//
+// Enter opens the finder: type part of a topic, in order but not necessarily
+// together ("crdhf" finds Cardiology/Heart failure), and pick it with the arrows
+// and Enter. A topic that isn't there yet can be added to the bank's taxonomy.
+
+/// The topics of the config and banks, with the broader topics they're under
+pub fn topics(config: &[String], banks: &Banks) -> Vec<String> {
+    let mut topics: Vec<String> = config
+        .iter()
+        .chain(banks.files().flat_map(|(_, bank)| &bank.taxonomy))
+        .flat_map(|topic| {
+            // Cardiology/Arrhythmia is also under Cardiology
+            let parts: Vec<&str> = topic.split('/').map(str::trim).collect();
+            (1..=parts.len()).map(move |n| parts[..n].join("/"))
+        })
+        .collect();
+    topics.sort();
+    topics.dedup();
+    topics
+}
+
+/// The finder over the middle of area
+pub fn render_finder(
+    frame: &mut Frame,
+    area: Rect,
+    finder: &Finder,
+    topics: &[String],
+    palette: &Palette,
+) {
+    let mut items: Vec<ListItem> = finder
+        .choices(topics)
+        .into_iter()
+        .map(|(topic, new)| match new {
+            true => ListItem::new(t!("topic.add", topic = topic).fg(palette.action_key)),
+            false => ListItem::new(topic.replace('/', " › ")),
+        })
+        .collect();
+    if items.is_empty() {
+        items.push(ListItem::new(t!("topic.none").dim()));
+    }
+    let title = format!("{} {}▏", t!("topic.search"), finder.query);
+    popup::list(frame, area, &title, items, finder.selected);
+}
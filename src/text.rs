@@ -0,0 +1,153 @@
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+const TAB: &str = "    ";
+
+/// Rewrites the few things terminals and unicode-width disagree on, so the
+/// columns ratatui computes match what is actually drawn:
+/// - tabs become spaces (a tab is 1 column to unicode-width)
+/// - emoji skin tone modifiers are dropped (👍🏽 is measured as 4 columns, drawn as 2)
+/// - zero width joiners are dropped so 👨‍👩‍👧 is drawn as the 3 emoji it is measured as
+/// - other control chars are removed
+pub fn normalize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\t' => out.push_str(TAB),
+            '\n' => out.push(c),
+            '\u{200D}' | '\u{1F3FB}'..='\u{1F3FF}' => {}
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Wraps styled lines to width columns, measuring graphemes with unicode-width.
+/// Unlike ratatui's wrapper this can break between CJK characters (which have no
+/// spaces between words) and never splits a double width grapheme across lines.
+/// Leading whitespace is kept on the first line so list/code indentation survives.
+pub fn wrap(lines: Vec<Line<'static>>, width: u16) -> Vec<Line<'static>> {
+    if width == 0 {
+        return lines;
+    }
+    let width = width as usize;
+    let mut wrapped = vec![];
+    for line in lines {
+        let (line_style, alignment) = (line.style, line.alignment);
+        let mut rows: Vec<Vec<(String, Style)>> = vec![];
+        let mut row: Vec<(String, Style)> = vec![];
+        let mut row_width = 0;
+        for unit in units(&line) {
+            let unit_width: usize = unit.iter().map(|(g, _)| g.width()).sum();
+            let is_space = unit.iter().all(|(g, _)| g.trim().is_empty());
+            if is_space {
+                if row_width + unit_width > width {
+                    rows.push(std::mem::take(&mut row));
+                    row_width = 0;
+                } else if row_width > 0 || rows.is_empty() {
+                    row_width += unit_width;
+                    row.extend(unit);
+                }
+                continue;
+            }
+            if row_width + unit_width > width && row_width > 0 {
+                trim_end(&mut row);
+                rows.push(std::mem::take(&mut row));
+                row_width = 0;
+            }
+            // a single word wider than the pane gets split wherever it has to
+            for (grapheme, style) in unit {
+                let grapheme_width = grapheme.width();
+                if row_width + grapheme_width > width && row_width > 0 {
+                    rows.push(std::mem::take(&mut row));
+                    row_width = 0;
+                }
+                row_width += grapheme_width;
+                row.push((grapheme, style));
+            }
+        }
+        trim_end(&mut row);
+        rows.push(row);
+        wrapped.extend(rows.into_iter().map(|row| {
+            let mut line = Line::from(merge(row)).style(line_style);
+            line.alignment = alignment;
+            line
+        }));
+    }
+    wrapped
+}
+
+/// Wraps lines behind prefix, the rows after the first indented to line up
+/// under the text: an option's "10 - " and the option wrapped next to it
+pub fn wrap_hanging(
+    prefix: Span<'static>,
+    lines: Vec<Line<'static>>,
+    width: u16,
+) -> Vec<Line<'static>> {
+    let indent = prefix.width() as u16;
+    let mut wrapped = wrap(lines, width.saturating_sub(indent).max(1));
+    if wrapped.is_empty() {
+        wrapped.push(Line::default());
+    }
+    for (i, line) in wrapped.iter_mut().enumerate() {
+        let lead = match i {
+            0 => prefix.clone(),
+            _ => Span::raw(" ".repeat(indent as usize)),
+        };
+        line.spans.insert(0, lead);
+    }
+    wrapped
+}
+
+// Splits a line into the pieces wrapping can't break: runs of whitespace, single
+// double width graphemes (CJK, emoji) and words made of everything else.
+fn units(line: &Line<'static>) -> Vec<Vec<(String, Style)>> {
+    let mut units: Vec<Vec<(String, Style)>> = vec![];
+    let mut word: Vec<(String, Style)> = vec![];
+    let mut word_is_space = false;
+    for span in &line.spans {
+        for grapheme in span.content.graphemes(true) {
+            let is_space = grapheme.trim().is_empty();
+            let piece = (grapheme.to_string(), span.style);
+            if grapheme.width() > 1 {
+                if !word.is_empty() {
+                    units.push(std::mem::take(&mut word));
+                }
+                units.push(vec![piece]);
+                continue;
+            }
+            if !word.is_empty() && is_space != word_is_space {
+                units.push(std::mem::take(&mut word));
+            }
+            word_is_space = is_space;
+            word.push(piece);
+        }
+    }
+    if !word.is_empty() {
+        units.push(word);
+    }
+    units
+}
+
+fn trim_end(row: &mut Vec<(String, Style)>) {
+    while row.last().is_some_and(|(g, _)| g.trim().is_empty()) {
+        row.pop();
+    }
+}
+
+// joins neighbouring graphemes with the same style back into spans
+fn merge(row: Vec<(String, Style)>) -> Vec<Span<'static>> {
+    let mut spans: Vec<Span<'static>> = vec![];
+    for (grapheme, style) in row {
+        match spans.last_mut() {
+            Some(span) if span.style == style => span.content.to_mut().push_str(&grapheme),
+            _ => spans.push(Span::styled(grapheme, style)),
+        }
+    }
+    spans
+}
@@ -0,0 +1,67 @@
+use clap::ValueEnum;
+use ratatui::style::Color;
+use serde::Deserialize;
+
+/// Colour schemes, picked with `theme` in the config file or --theme
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    #[default]
+    Default,
+    // for terminals with a white background
+    Light,
+    HighContrast,
+}
+
+/// The colours used by the UI for a theme
+pub struct Palette {
+    pub key: Color,        // navigation keys in the controls bar
+    pub action_key: Color, // answer/classify keys and question numbers
+    pub quit_key: Color,
+    pub option: Color,
+    pub selected: Color, // the rater's current answer
+    pub missing: Color,  // MISSING ANSWER/CLASSIFICATION
+    pub info: Color,
+    pub progress: Color,
+    pub progress_bg: Color,
+}
+
+impl Theme {
+    pub fn palette(&self) -> Palette {
+        match self {
+            Theme::Default => Palette {
+                key: Color::Blue,
+                action_key: Color::Cyan,
+                quit_key: Color::Red,
+                option: Color::Yellow,
+                selected: Color::Green,
+                missing: Color::Red,
+                info: Color::Blue,
+                progress: Color::LightCyan,
+                progress_bg: Color::Black,
+            },
+            Theme::Light => Palette {
+                key: Color::Blue,
+                action_key: Color::Magenta,
+                quit_key: Color::Red,
+                option: Color::Black,
+                selected: Color::Green,
+                missing: Color::Red,
+                info: Color::Blue,
+                progress: Color::Blue,
+                progress_bg: Color::Gray,
+            },
+            Theme::HighContrast => Palette {
+                key: Color::White,
+                action_key: Color::LightYellow,
+                quit_key: Color::LightRed,
+                option: Color::White,
+                selected: Color::LightGreen,
+                missing: Color::LightRed,
+                info: Color::LightCyan,
+                progress: Color::White,
+                progress_bg: Color::Black,
+            },
+        }
+    }
+}
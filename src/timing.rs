@@ -0,0 +1,100 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::bank::Banks;
+
+// With track_time on, how long a rater spends on each question is added up and
+// saved into the bank as a "time_spent" block, so time can be compared between
+// raters (the bank's rater_id) without going through the event log:
+//
+// "time_spent": { "sessions": 2, "active_seconds": 95.4, "idle_seconds": 312.0,
+//                 "dwell_seconds": [12.5, 40.1, ...] }
+//
+// Time counts as idle once there has been no key press for IDLE_AFTER, eg. the
+// rater went for a coffee with the session open. dwell_seconds is the active
+// time on each question, in the order of the file. Totals add up across sessions.
+
+const IDLE_AFTER: Duration = Duration::from_secs(60);
+
+/// The summary block saved in the bank
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct TimeSpent {
+    pub sessions: u32,
+    #[serde(serialize_with = "tenths")]
+    pub active_seconds: f64,
+    #[serde(serialize_with = "tenths")]
+    pub idle_seconds: f64,
+    #[serde(serialize_with = "all_tenths")]
+    pub dwell_seconds: Vec<f64>,
+}
+
+// rounded to 0.1s in the file, the precision doesn't mean anything beyond that
+fn round(seconds: f64) -> f64 {
+    (seconds * 10.0).round() / 10.0
+}
+
+fn tenths<S: Serializer>(seconds: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(round(*seconds))
+}
+
+fn all_tenths<S: Serializer>(seconds: &[f64], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(seconds.iter().map(|seconds| round(*seconds)))
+}
+
+/// Splits the session's time into active and idle time per question, until
+/// it is added to the banks on save
+#[derive(Debug)]
+pub struct Timer {
+    last_tick: Instant,
+    last_input: Instant,
+    active: Vec<Duration>, // per question across the session
+    idle: Vec<Duration>,
+}
+
+impl Timer {
+    pub fn start(banks: &mut Banks) -> Timer {
+        banks.start_timing();
+        let now = Instant::now();
+        Timer {
+            last_tick: now,
+            last_input: now,
+            active: vec![Duration::ZERO; banks.len()],
+            idle: vec![Duration::ZERO; banks.len()],
+        }
+    }
+
+    /// Counts the time since the last tick towards the question on screen
+    pub fn tick(&mut self, question_index: usize) {
+        let now = Instant::now();
+        let idle_from = (self.last_input + IDLE_AFTER).clamp(self.last_tick, now);
+        if let (Some(active), Some(idle)) = (
+            self.active.get_mut(question_index),
+            self.idle.get_mut(question_index),
+        ) {
+            *active += idle_from - self.last_tick;
+            *idle += now - idle_from;
+        }
+        self.last_tick = now;
+    }
+
+    /// A key was pressed, the rater is active again
+    pub fn input(&mut self) {
+        self.last_input = Instant::now();
+    }
+
+    /// Adds the time counted so far to the banks and starts counting from zero
+    pub fn flush(&mut self, banks: &mut Banks) {
+        for (index, (active, idle)) in self.active.iter_mut().zip(&mut self.idle).enumerate() {
+            if index < banks.len() {
+                banks.add_time(index, *active, *idle);
+            }
+            *active = Duration::ZERO;
+            *idle = Duration::ZERO;
+        }
+        // questions can come and go when a file changed on disk is reloaded
+        self.active.resize(banks.len(), Duration::ZERO);
+        self.idle.resize(banks.len(), Duration::ZERO);
+    }
+}
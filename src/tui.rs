@@ -1,4 +1,7 @@
-use std::io::{self, stdout, Stdout};
+use std::{
+    io::{self, stdout, Stdout},
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use ratatui::{
     backend::CrosstermBackend,
@@ -12,15 +15,31 @@ use ratatui::{
 /// A type alias for the terminal type used in this application
 pub type Tui = Terminal<CrosstermBackend<Stdout>>;
 
+// so errors outside the interface (ie. `serve`, `set`) don't print escape codes
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
 /// Initialize the terminal
 pub fn init() -> io::Result<Tui> {
+    resume()?;
+    Terminal::new(CrosstermBackend::new(stdout()))
+}
+
+/// Takes the terminal back after an error restored it (see errors.rs) that
+/// was caught and shown in the interface instead, returns whether it had to
+pub fn resume() -> io::Result<bool> {
+    if ACTIVE.swap(true, Ordering::SeqCst) {
+        return Ok(false);
+    }
     execute!(stdout(), EnterAlternateScreen)?;
     enable_raw_mode()?;
-    Terminal::new(CrosstermBackend::new(stdout()))
+    Ok(true)
 }
 
 /// Restore the terminal to its original state
 pub fn restore() -> io::Result<()> {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return Ok(());
+    }
     execute!(stdout(), LeaveAlternateScreen)?;
     disable_raw_mode()?;
     Ok(())
@@ -0,0 +1,1012 @@
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use color_eyre::{eyre::WrapErr, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use serde_json::json;
+
+use crate::{
+    bank, dimensions, drafts, get_num_answered, grid, i18n::t, keymap, mode, mode::Mode, popup,
+    proctor, sort, tags, taxonomy, App,
+};
+
+// The interface goes model, message, update, the Elm way: App is the state,
+// everything that happens to it (a key press, time passing, a frame drawn)
+// comes in as a Message, and App::update is the only place it changes. App::ui
+// only reads it, so a session can be played without a terminal:
+//
+//     let mut app = App::new(banks, 0, Mode::Answer, String::new(), 0, config);
+//     app.update(Message::Next)?;
+//     app.update(Message::Key(KeyCode::Char('1').into()))?;
+//     assert_eq!(app.question_index, 1);
+//     assert_eq!(app.num_answered, 1);
+//
+// A key press goes to what's open (a popup, an editor...), otherwise it's
+// turned into the message of its control, then goes to the mode as a Respond.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Key(KeyEvent),
+    Tick,                                 // no key for a while, see read
+    Drawn { page: u16, max_scroll: u16 }, // the question pane's height and how far it scrolls
+    ImageFailed(String),
+    // the file changed on disk
+    Reload,
+    Merge,
+    Keep,
+    // the common controls
+    Quit,
+    Save,
+    ToggleMetadata,
+    ToggleReveal,
+    ToggleBookmark,
+    OpenBookmarks,
+    NextDimension,
+    OpenTopicFinder,
+    EditDraft,
+    ScrollDown,
+    ScrollUp,
+    Eliminate,
+    OpenTags,
+    ToggleSidebar,
+    ToggleAutoAdvance,
+    OpenSort,
+    Sort(sort::Order),
+    OpenGrid,
+    OpenNote,
+    GoTo(usize),
+    Prev,
+    Next,
+    Respond(KeyCode), // the mode's response to the key, eg. an option's number
+}
+
+/// The next message from the terminal, Tick when there's no key press for
+/// half a second so autosave and changes on disk are noticed without one
+pub fn read() -> Result<Message> {
+    if !event::poll(Duration::from_millis(500))? {
+        return Ok(Message::Tick);
+    }
+    match event::read()? {
+        // it's important to check that the event is a key press event as
+        // crossterm also emits key release and repeat events on Windows.
+        Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+            Ok(Message::Key(key_event))
+        }
+        _ => Ok(Message::Tick),
+    }
+}
+
+impl App {
+    /// updates the application's state with a message
+    pub fn update(&mut self, message: Message) -> Result<()> {
+        // the time until now was spent on the question on screen before this message
+        if let Some(timer) = &mut self.timer {
+            timer.tick(self.question_index);
+        }
+        let from = self.question_index;
+        self.apply(message)?;
+        if self.question_index != from {
+            let to = self.question_index;
+            self.log_event("navigate", json!({"from": from + 1, "to": to + 1}))?;
+        }
+        self.check_disk();
+        self.autosave()
+    }
+
+    fn apply(&mut self, message: Message) -> Result<()> {
+        match message {
+            Message::Key(key_event) => self
+                .on_key(key_event)
+                .wrap_err_with(|| format!("handling key event failed:\n{key_event:#?}"))?,
+            Message::Tick => {}
+            Message::Drawn { page, max_scroll } => {
+                self.page = page;
+                self.max_scroll = max_scroll;
+                if self.shown.map(|(index, _)| index) != Some(self.question_index) {
+                    self.shown = Some((self.question_index, Instant::now()));
+                }
+            }
+            Message::ImageFailed(error) => self.message = t!("status.image_error", error = error),
+            Message::Reload | Message::Merge | Message::Keep => self.resolve(message)?,
+            Message::Quit => self.quit()?,
+            Message::Save => self.save()?,
+            Message::ToggleMetadata => self.show_metadata = !self.show_metadata,
+            Message::ToggleReveal => self.toggle_reveal()?,
+            Message::ToggleBookmark => {
+                let added = self.bookmarks.insert(self.question_index);
+                if !added {
+                    self.bookmarks.remove(&self.question_index);
+                }
+                self.message = match added {
+                    true => t!("bookmarks.added"),
+                    false => t!("bookmarks.removed"),
+                }
+                .to_string();
+            }
+            Message::OpenBookmarks => match self.bookmarks.is_empty() {
+                true => self.message = t!("bookmarks.none").to_string(),
+                false => self.jump_list = Some(0),
+            },
+            Message::NextDimension => {
+                // none configured, see dimensions::next_missing
+                self.dimension = (self.dimension + 1) % dimensions::all().len().max(1);
+            }
+            Message::OpenTopicFinder => self.topic_finder = Some(popup::Finder::default()),
+            Message::EditDraft => {
+                let question = self.banks.question(self.question_index);
+                if let Some(draft) = drafts::draft(question) {
+                    self.draft_editor = Some(draft.to_string());
+                }
+            }
+            Message::ScrollDown => {
+                let step = self.page.saturating_sub(1).max(1);
+                self.scroll = (self.scroll + step).min(self.max_scroll);
+            }
+            Message::ScrollUp => {
+                let step = self.page.saturating_sub(1).max(1);
+                self.scroll = self.scroll.saturating_sub(step);
+            }
+            Message::Eliminate => {
+                self.eliminating = true;
+                self.message = t!("status.eliminate").to_string();
+            }
+            Message::OpenTags => self.tag_finder = Some(popup::Finder::default()),
+            Message::ToggleSidebar => self.show_sidebar = !self.show_sidebar,
+            Message::ToggleAutoAdvance => {
+                self.auto_advance = !self.auto_advance;
+                self.message = match self.auto_advance {
+                    true => t!("status.auto_advance_on"),
+                    false => t!("status.auto_advance_off"),
+                }
+                .to_string();
+            }
+            Message::OpenSort => {
+                let current = sort::ORDERS.iter().position(|order| *order == self.order);
+                self.sort_menu = Some(current.unwrap_or_default());
+            }
+            Message::Sort(order) => self.sort(order),
+            Message::OpenGrid => self.grid = Some(self.question_index),
+            Message::OpenNote => {
+                let (json_path, id) = self.banks.question_id(self.question_index);
+                let note = self.notes.get(json_path, &id).unwrap_or_default();
+                self.note_editor = Some(note.to_string());
+            }
+            Message::GoTo(index) => self.go_to(index),
+            Message::Prev => self
+                .decrement_index()
+                .wrap_err("overflow substraction error")?,
+            Message::Next => self
+                .increment_index()
+                .wrap_err("overflow addition error somehow")?,
+            Message::Respond(code) => self.respond(code)?,
+        }
+        Ok(())
+    }
+
+    // handle key presses in the temrinal
+    fn on_key(&mut self, key_event: KeyEvent) -> Result<()> {
+        if let Some(timer) = &mut self.timer {
+            timer.input();
+        }
+        let keymap = &self.config.keymap;
+        // the file changed on disk, nothing is saved until the rater picks what to do
+        if self.conflict.is_some() {
+            let resolution = match key_event.code {
+                code if code == keymap.reload.0 => Some(Message::Reload),
+                code if code == keymap.merge.0 => Some(Message::Merge),
+                code if code == keymap.keep.0 => Some(Message::Keep),
+                _ => None,
+            };
+            if let Some(message) = resolution {
+                return self.apply(message);
+            }
+        }
+        // typing the proctor code to quit a proctored session early
+        if let Some(typed) = &mut self.quit_prompt {
+            match key_event.code {
+                KeyCode::Char(c) => typed.push(c),
+                KeyCode::Backspace => {
+                    typed.pop();
+                }
+                KeyCode::Esc => self.quit_prompt = None,
+                KeyCode::Enter if Some(&*typed) == self.proctor_code.as_ref() => {
+                    self.quit_prompt = None;
+                    self.exit()?;
+                }
+                KeyCode::Enter => {
+                    self.quit_prompt = None;
+                    self.message = t!("proctor.wrong_code").to_string();
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+        // picking a bookmark to jump to
+        if let Some(selected) = self.jump_list {
+            self.jump_list = None;
+            match popup::choose(key_event.code, selected, self.bookmarks.len()) {
+                popup::Choice::Highlight(selected) => self.jump_list = Some(selected),
+                popup::Choice::Pick(pick) => {
+                    if let Some(&index) = self.bookmarks.iter().nth(pick) {
+                        self.apply(Message::GoTo(index))?;
+                    }
+                }
+                popup::Choice::Close => {}
+            }
+            return Ok(());
+        }
+        // picking the order to go through the questions in
+        if let Some(selected) = self.sort_menu {
+            self.sort_menu = None;
+            match popup::choose(key_event.code, selected, sort::ORDERS.len()) {
+                popup::Choice::Highlight(selected) => self.sort_menu = Some(selected),
+                popup::Choice::Pick(pick) => self.apply(Message::Sort(sort::ORDERS[pick]))?,
+                popup::Choice::Close => {}
+            }
+            return Ok(());
+        }
+        // looking over the progress grid
+        if let Some(selected) = self.grid {
+            self.grid = None;
+            match grid::choose(key_event.code, selected, &self.banks, self.mode) {
+                popup::Choice::Highlight(selected) => self.grid = Some(selected),
+                popup::Choice::Pick(index) => self.apply(Message::GoTo(index))?,
+                popup::Choice::Close => {}
+            }
+            return Ok(());
+        }
+        // looking for the question's topic, typing goes in the search
+        if let Some(finder) = &mut self.topic_finder {
+            let topics = taxonomy::topics(&self.config.taxonomy, &self.banks);
+            let choices = finder.choices(&topics);
+            match finder.choose(key_event.code, choices.len()) {
+                popup::Choice::Highlight(_) => {}
+                popup::Choice::Close => self.topic_finder = None,
+                popup::Choice::Pick(selected) => {
+                    self.topic_finder = None;
+                    let (topic, new) = choices[selected].clone();
+                    if new {
+                        self.banks.add_topic(self.question_index, &topic);
+                        self.dirty = true;
+                    }
+                    self.record(|question| {
+                        let changed = question.topic() != Some(topic.as_str());
+                        question.set_topic(&topic);
+                        changed
+                    })?;
+                }
+            }
+            return Ok(());
+        }
+        // editing the question's tags, the finder stays open for the next one
+        if let Some(finder) = &mut self.tag_finder {
+            let choices = finder.choices(&tags::all(&self.banks));
+            match finder.choose(key_event.code, choices.len()) {
+                popup::Choice::Highlight(_) => {}
+                popup::Choice::Close => self.tag_finder = None,
+                popup::Choice::Pick(selected) => {
+                    *finder = popup::Finder::default();
+                    let tag = &choices[selected].0;
+                    // tags aren't responses, nothing to count or move on from
+                    self.banks.update(self.question_index, "tag", |question| {
+                        question.toggle_tag(tag);
+                        true
+                    })?;
+                    self.dirty = true;
+                }
+            }
+            return Ok(());
+        }
+        // striking out an option, any key that isn't one of their numbers cancels
+        if self.eliminating {
+            self.eliminating = false;
+            self.message.clear();
+            let shown = self.banks.question(self.question_index).shown_options();
+            if let Some(&option) = mode::option_index(key_event.code).and_then(|n| shown.get(n)) {
+                self.toggle_eliminated(option)?;
+            }
+            return Ok(());
+        }
+        // writing a note, every key goes in it until Esc
+        if let Some(text) = &mut self.note_editor {
+            match key_event.code {
+                KeyCode::Char(c) => text.push(c),
+                KeyCode::Enter => text.push('\n'),
+                KeyCode::Backspace => {
+                    text.pop();
+                }
+                KeyCode::Esc => {
+                    let text = self.note_editor.take().unwrap_or_default();
+                    let (json_path, id) = self.banks.question_id(self.question_index);
+                    self.notes.set(json_path, &id, &text)?;
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+        // editing the draft explanation, the same way
+        if let Some(text) = &mut self.draft_editor {
+            match key_event.code {
+                KeyCode::Char(c) => text.push(c),
+                KeyCode::Enter => text.push('\n'),
+                KeyCode::Backspace => {
+                    text.pop();
+                }
+                KeyCode::Esc => {
+                    let text = self.draft_editor.take().unwrap_or_default();
+                    self.record(|question| drafts::set_draft(question, &text))?;
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+        if let Some(message) = self.control(key_event.code) {
+            self.apply(message)?;
+        }
+        self.apply(Message::Respond(key_event.code))
+    }
+
+    // the common control of a key, if it's one
+    fn control(&self, code: KeyCode) -> Option<Message> {
+        let keymap = &self.config.keymap;
+        let last = self.banks.len().saturating_sub(1);
+        let message = match code {
+            code if code == keymap.quit.0 => Message::Quit,
+            code if code == keymap.save.0 => Message::Save,
+            code if code == keymap.metadata.0 => Message::ToggleMetadata,
+            code if code == keymap.reveal.0 => Message::ToggleReveal,
+            code if code == keymap.bookmark.0 => Message::ToggleBookmark,
+            code if code == keymap.bookmarks.0 => Message::OpenBookmarks,
+            // the dimension key goes before the sidebar, they're both Tab by default
+            code if code == keymap.dimension.0 && self.mode == Mode::Dimensions => {
+                Message::NextDimension
+            }
+            code if code == keymap.topic.0 && self.mode == Mode::Topic => Message::OpenTopicFinder,
+            code if code == keymap.edit.0 && self.mode == Mode::Author => Message::EditDraft,
+            code if code == keymap.scroll_down.0 => Message::ScrollDown,
+            code if code == keymap.scroll_up.0 => Message::ScrollUp,
+            code if code == keymap.eliminate.0 => Message::Eliminate,
+            code if code == keymap.tags.0 => Message::OpenTags,
+            code if code == keymap.sidebar.0 => Message::ToggleSidebar,
+            // its key taken by the mode's own action
+            KeyCode::BackTab if self.mode_key() == Some(keymap.sidebar) => Message::ToggleSidebar,
+            code if code == keymap.auto_advance.0 => Message::ToggleAutoAdvance,
+            // going through the question index
+            KeyCode::Up if self.show_sidebar => {
+                Message::GoTo(self.question_index.saturating_sub(1))
+            }
+            KeyCode::Down if self.show_sidebar => {
+                Message::GoTo((self.question_index + 1).min(last))
+            }
+            KeyCode::PageUp if self.show_sidebar => {
+                Message::GoTo(self.question_index.saturating_sub(10))
+            }
+            KeyCode::PageDown if self.show_sidebar => {
+                Message::GoTo((self.question_index + 10).min(last))
+            }
+            KeyCode::Home if self.show_sidebar => Message::GoTo(0),
+            KeyCode::End if self.show_sidebar => Message::GoTo(last),
+            code if code == keymap.sort.0 => Message::OpenSort,
+            code if code == keymap.grid.0 => Message::OpenGrid,
+            code if code == keymap.notes.0 => Message::OpenNote,
+            code if code == keymap.prev.0 => Message::Prev,
+            code if code == keymap.next.0 => Message::Next,
+            _ => return None,
+        };
+        Some(message)
+    }
+
+    // the key of the mode's own action, if it has one
+    fn mode_key(&self) -> Option<keymap::Key> {
+        let keymap = &self.config.keymap;
+        match self.mode {
+            Mode::Dimensions => Some(keymap.dimension),
+            Mode::Topic => Some(keymap.topic),
+            Mode::Author => Some(keymap.edit),
+            _ => None,
+        }
+    }
+
+    // mode specific controls
+    fn respond(&mut self, code: KeyCode) -> Result<()> {
+        let keymap = self.config.keymap.clone();
+        let mode = self.mode;
+        let before = self.banks.question(self.question_index).clone();
+        let was_done = mode.is_done(&before);
+        // with confirm_changes a response is only changed by pressing its key twice
+        let pending = self.pending_change.take();
+        if self.config.confirm_changes && was_done {
+            let mut after = before.clone();
+            let changes = mode.respond(&mut after, code, &keymap, self.dimension)
+                && !before.changed_responses(&after).is_empty();
+            let press = (self.question_index, code);
+            if changes && pending != Some(press) {
+                self.pending_change = Some(press);
+                let key = keymap::Key(code).label();
+                self.message = t!("status.confirm_change", key = key);
+                return Ok(());
+            }
+            if changes {
+                self.message.clear(); // confirmed
+            }
+        }
+        let dimension = self.dimension;
+        self.record(|question| mode.respond(question, code, &keymap, dimension))
+    }
+
+    // reload, merge or keep the file that changed on disk
+    fn resolve(&mut self, message: Message) -> Result<()> {
+        let Some(json_path) = self.conflict.clone() else {
+            return Ok(());
+        };
+        let path = json_path.display();
+        // the version on disk can't be used, the rater still has to pick
+        let failed = |err: color_eyre::Report| t!("watch.failed", error = format!("{:#}", err));
+        match message {
+            Message::Reload => {
+                if let Err(err) = self.replace(|banks| banks.reload(&json_path)) {
+                    self.message = failed(err);
+                    return Ok(());
+                }
+                self.message = t!("watch.reloaded", path = path);
+            }
+            Message::Merge => {
+                if let Err(err) = self.replace(|banks| banks.merge(&json_path)) {
+                    self.message = failed(err);
+                    return Ok(());
+                }
+                self.message = t!("watch.merged", path = path);
+                self.dirty = true;
+            }
+            _ => {
+                self.banks.keep(&json_path);
+                self.message = t!("watch.kept", path = path);
+                self.dirty = true;
+            }
+        }
+        self.conflict = None;
+        self.num_answered = get_num_answered(&self.mode, &self.banks);
+        Ok(())
+    }
+
+    // reloads or merges a file, the question on screen and the bookmarks stay
+    // the same questions when one before them is added or removed
+    fn replace(
+        &mut self,
+        change: impl FnOnce(&mut bank::Banks) -> Result<bank::Moved>,
+    ) -> Result<()> {
+        let current = self.banks.absolute(self.question_index);
+        let bookmarks: Vec<usize> = self
+            .bookmarks
+            .iter()
+            .map(|&index| self.banks.absolute(index))
+            .collect();
+        let moved = change(&mut self.banks)?;
+        let position = |banks: &bank::Banks, absolute| banks.position(moved.absolute(absolute)?);
+        // or the nearest one when it's gone
+        let last = self.banks.len().saturating_sub(1);
+        self.question_index =
+            position(&self.banks, current).unwrap_or(self.question_index.min(last));
+        self.bookmarks = bookmarks
+            .into_iter()
+            .filter_map(|index| position(&self.banks, index))
+            .collect();
+        Ok(())
+    }
+
+    // saves a response through change, logging it, sending it to a live host and
+    // keeping the progress up to date
+    fn record(&mut self, change: impl FnOnce(&mut bank::Question) -> bool) -> Result<()> {
+        let mode = self.mode;
+        let before = self.banks.question(self.question_index).clone();
+        let was_done = mode.is_done(&before);
+        let history = self.config.answer_history.then(proctor::now);
+        let reaction = match self.shown {
+            Some((_, at)) if self.config.reaction_time => Some(at.elapsed().as_millis()),
+            _ => None,
+        };
+        let changed = self
+            .banks
+            .update(self.question_index, mode.name(), |question| {
+                let before = question.clone();
+                let changed = change(question);
+                if let (true, Some(at)) = (changed, &history) {
+                    question.record_history(&before, at);
+                }
+                // only the first response, later changes don't count
+                if let (true, Some(ms)) = (changed, reaction) {
+                    if !question.extra.contains_key("reaction_ms") {
+                        question.extra.insert("reaction_ms".to_string(), json!(ms));
+                    }
+                }
+                changed
+            })?;
+        if changed {
+            let after = self.banks.question(self.question_index).clone();
+            for (field, old, new) in before.changed_responses(&after) {
+                let question = self.question_index + 1;
+                self.log_event(
+                    "response",
+                    json!({"question": question, "field": field, "old": old, "new": new}),
+                )?;
+                // the session goes on locally if the host has gone
+                if let Some(Err(_)) = self
+                    .live
+                    .as_mut()
+                    .map(|live| live.send(question, &field, new))
+                {
+                    self.live = None;
+                    self.message = t!("live.disconnected").to_string();
+                }
+            }
+            // only increment num_answered if not prev answered.
+            let done = mode.is_done(self.banks.question(self.question_index));
+            // on to the next axis still missing a value
+            if mode == Mode::Dimensions {
+                let question = self.banks.question(self.question_index);
+                if let Some(next) = dimensions::next_missing(question, self.dimension) {
+                    self.dimension = next;
+                }
+            }
+            if !was_done && done {
+                self.increment_num_answered()?;
+                let question = self.banks.question(self.question_index);
+                let value = serde_json::to_value(question)?[mode.field()].take();
+                let (json_path, id) = self.banks.question_id(self.question_index);
+                let answered = json!({
+                    "question": self.question_index + 1,
+                    "id": id,
+                    "file": json_path,
+                    "field": mode.field(),
+                    "value": value,
+                    "skipped": mode.is_skipped(question),
+                });
+                self.run_hooks("answered", &answered);
+            }
+            if was_done && !done {
+                self.num_answered -= 1;
+            }
+            self.dirty = true;
+            if self.auto_advance && done {
+                if let Some(next) = mode.next_missing(&self.banks, self.question_index) {
+                    self.go_to(next);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // recorded in the bank with record_eliminations, only on screen otherwise
+    fn toggle_eliminated(&mut self, option: usize) -> Result<()> {
+        let mut eliminated = self.eliminated();
+        if !eliminated.remove(&option) {
+            eliminated.insert(option);
+        }
+        let options: Vec<usize> = eliminated.iter().map(|i| i + 1).collect();
+        let question = self.question_index + 1;
+        self.log_event(
+            "eliminate",
+            json!({"question": question, "options": options}),
+        )?;
+        match self.config.record_eliminations {
+            true => {
+                self.banks
+                    .update(self.question_index, "eliminate", |question| {
+                        question.set_eliminated(&eliminated);
+                        true
+                    })?;
+                self.dirty = true;
+            }
+            false => {
+                self.eliminated.insert(self.question_index, eliminated);
+            }
+        }
+        Ok(())
+    }
+
+    // a proctored session can only be left early with the proctor code
+    fn quit(&mut self) -> Result<()> {
+        if self.proctor_code.is_some() && self.num_answered < self.banks.len() {
+            self.quit_prompt = Some(String::new());
+            return Ok(());
+        }
+        self.exit() // also saves
+    }
+
+    fn exit(&mut self) -> Result<()> {
+        if self.check_disk() {
+            return Ok(());
+        }
+        if let Err(err) = self.hooks.pre_save() {
+            self.message = t!("hooks.save_stopped", error = format!("{:#}", err));
+            return Ok(());
+        }
+        self.exit = true;
+        if self.proctor_code.is_some() {
+            self.banks.end_exam(&proctor::now());
+        }
+        self.write_banks()?;
+        let end = json!({"answered": self.num_answered, "total": self.banks.len()});
+        self.log_event("end", end)
+    }
+
+    // saves time of save in app.message for state
+    fn save(&mut self) -> Result<()> {
+        if self.check_disk() {
+            return Ok(());
+        }
+        if let Err(err) = self.hooks.pre_save() {
+            self.message = t!("hooks.save_stopped", error = format!("{:#}", err));
+            self.last_save = Utc::now(); // so autosave waits before trying again
+            return Ok(());
+        }
+        // Get the current UTC time
+        let now = Utc::now();
+        self.write_banks()?;
+        let message = t!("status.saved", time = now);
+        self.message = message;
+        self.dirty = false;
+        self.last_save = now;
+        self.log_event("save", json!({}))
+    }
+
+    // appends to the research event log, when it's on, and runs the event's hooks
+    pub fn log_event(&mut self, event: &str, fields: serde_json::Value) -> Result<()> {
+        self.run_hooks(event, &fields);
+        match &mut self.events {
+            Some(events) => events.log(event, fields),
+            None => Ok(()),
+        }
+    }
+
+    // a hook that failed shows in the status line
+    fn run_hooks(&mut self, event: &str, fields: &serde_json::Value) {
+        if let Some(failed) = self.hooks.run(event, fields) {
+            self.message = t!("hooks.failed", hooks = failed);
+        }
+    }
+
+    // the .json files, and the patch when --patch-out is given
+    fn write_banks(&mut self) -> Result<()> {
+        if let Some(timer) = &mut self.timer {
+            timer.flush(&mut self.banks);
+        }
+        self.banks.save().wrap_err("save_json failed")?;
+        if let Some(patch_path) = &self.patch_out {
+            self.banks.write_patch(patch_path)?;
+        }
+        Ok(())
+    }
+
+    // saves if autosave is on, there are changes and the interval has passed
+    fn autosave(&mut self) -> Result<()> {
+        let interval = self.config.autosave_interval as i64;
+        if interval > 0 && self.dirty && (Utc::now() - self.last_save).num_seconds() >= interval {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    // notices another program changing the file, returns whether the rater
+    // still has to pick between reloading, merging and keeping their version
+    fn check_disk(&mut self) -> bool {
+        if self.conflict.is_none() {
+            self.conflict = self.banks.changed_on_disk().map(|path| path.to_path_buf());
+            if self.conflict.is_some() {
+                self.message.clear();
+            }
+        }
+        self.conflict.is_some()
+    }
+
+    // faculty checking the key, hidden again on the next question
+    fn toggle_reveal(&mut self) -> Result<()> {
+        if !self.can_reveal() {
+            return Ok(());
+        }
+        self.revealed = !self.revealed;
+        if self.revealed {
+            let question = self.question_index + 1;
+            self.log_event("reveal", json!({ "question": question }))?;
+        }
+        Ok(())
+    }
+
+    // the question on screen and the bookmarks stay the same questions
+    fn sort(&mut self, order: sort::Order) {
+        let current = self.banks.absolute(self.question_index);
+        let bookmarks: Vec<usize> = self
+            .bookmarks
+            .iter()
+            .map(|&index| self.banks.absolute(index))
+            .collect();
+        // the timer counts by position in the session
+        if let Some(timer) = &mut self.timer {
+            timer.flush(&mut self.banks);
+        }
+        sort::apply(&mut self.banks, order);
+        self.order = order;
+        self.question_index = self.banks.position(current).unwrap_or_default();
+        self.bookmarks = bookmarks
+            .into_iter()
+            .filter_map(|index| self.banks.position(index))
+            .collect();
+        self.message = t!("sort.sorted", order = order.label());
+    }
+
+    fn go_to(&mut self, index: usize) {
+        self.revealed = false;
+        self.scroll = 0;
+        self.question_index = index;
+    }
+
+    // loops if goes below the first question
+    fn decrement_index(&mut self) -> Result<()> {
+        self.revealed = false;
+        self.scroll = 0;
+        self.question_index = match self.question_index.checked_sub(1) {
+            Some(new_index) => new_index,
+            None => self.banks.len() - 1,
+        };
+        Ok(())
+    }
+    // loops if goes above the last question
+    fn increment_index(&mut self) -> Result<()> {
+        self.revealed = false;
+        self.scroll = 0;
+        self.question_index = (self.question_index + 1) % self.banks.len();
+        Ok(())
+    }
+
+    fn increment_num_answered(&mut self) -> Result<()> {
+        self.num_answered += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+    use crate::{bank::Banks, config};
+
+    // (id, stem)
+    const QUESTIONS: [(&str, &str); 3] = [
+        (
+            "q1",
+            "Which drug is first line for hypertension in diabetes?",
+        ),
+        ("q2", "What is 2+2?"),
+        ("q3", "Which vitamin is fat soluble?"),
+    ];
+
+    fn write(json_path: &PathBuf, questions: &[(&str, &str)]) {
+        let questions: Vec<serde_json::Value> = questions
+            .iter()
+            .map(|(id, stem)| {
+                json!({
+                    "id": id,
+                    "question": stem,
+                    "options": ["one", "two", "three"],
+                    "answer": "one",
+                })
+            })
+            .collect();
+        fs::write(json_path, serde_json::to_string(&questions).unwrap()).unwrap();
+    }
+
+    // a session on its own copy of the questions
+    fn session(name: &str) -> (App, PathBuf) {
+        let (app, mut json_paths) = sessions(&[name]);
+        (app, json_paths.remove(0))
+    }
+
+    // or on several copies, one after the other
+    fn sessions(names: &[&str]) -> (App, Vec<PathBuf>) {
+        let json_paths: Vec<PathBuf> = names
+            .iter()
+            .map(|name| {
+                std::env::temp_dir().join(format!(
+                    "question_cli_update_{}_{}.json",
+                    name,
+                    std::process::id()
+                ))
+            })
+            .collect();
+        for json_path in &json_paths {
+            write(json_path, &QUESTIONS);
+        }
+        let banks = Banks::load(&json_paths).unwrap();
+        let app = App::new(
+            banks,
+            0,
+            Mode::Answer,
+            String::new(),
+            0,
+            config::Config::default(),
+        );
+        (app, json_paths)
+    }
+
+    fn key(c: char) -> Message {
+        Message::Key(KeyCode::Char(c).into())
+    }
+
+    #[test]
+    fn next_and_answer() {
+        let (mut app, json_path) = session("next");
+        app.update(Message::Next).unwrap();
+        assert_eq!(app.question_index, 1);
+        app.update(key('1')).unwrap();
+        assert_eq!(app.num_answered, 1);
+        assert!(app.dirty);
+        assert_eq!(app.banks.question(1).human_answer.as_deref(), Some("one"));
+        // answering again doesn't count twice
+        app.update(key('2')).unwrap();
+        assert_eq!(app.num_answered, 1);
+        // past the last question back to the first
+        app.update(Message::Next).unwrap();
+        app.update(Message::Next).unwrap();
+        assert_eq!(app.question_index, 0);
+        fs::remove_file(json_path).unwrap();
+    }
+
+    #[test]
+    fn go_to_and_sort_keep_the_question_on_screen() {
+        let (mut app, json_path) = session("sort");
+        app.update(Message::GoTo(0)).unwrap();
+        app.update(Message::Sort(sort::Order::Length)).unwrap();
+        // the longest stem goes last
+        assert_eq!(app.question_index, 2);
+        assert_eq!(app.banks.question(2).question, QUESTIONS[0].1);
+        assert_eq!(app.banks.question(0).question, QUESTIONS[1].1);
+        assert_eq!(
+            app.message,
+            t!("sort.sorted", order = sort::Order::Length.label())
+        );
+        app.update(Message::GoTo(1)).unwrap();
+        assert_eq!(app.question_index, 1);
+        assert!(!app.dirty);
+        fs::remove_file(json_path).unwrap();
+    }
+
+    #[test]
+    fn reload_drops_the_responses_made_here() {
+        let (mut app, json_path) = session("reload");
+        app.update(key('1')).unwrap();
+        write(&json_path, &QUESTIONS[..2]);
+        app.update(Message::Tick).unwrap();
+        assert_eq!(app.conflict.as_ref(), Some(&json_path));
+        let reload = app.config.keymap.reload.0;
+        app.update(Message::Key(reload.into())).unwrap();
+        assert_eq!(app.conflict, None);
+        assert_eq!(app.banks.len(), 2);
+        assert_eq!(app.num_answered, 0);
+        let path = json_path.display();
+        assert_eq!(app.message, t!("watch.reloaded", path = path));
+        fs::remove_file(json_path).unwrap();
+    }
+
+    #[test]
+    fn reloading_the_first_file_keeps_the_place_in_the_second() {
+        let (mut app, json_paths) = sessions(&["reload_first", "reload_second"]);
+        app.bookmarks.insert(4);
+        app.update(Message::GoTo(5)).unwrap();
+        write(&json_paths[0], &QUESTIONS[..1]);
+        app.update(Message::Tick).unwrap();
+        app.update(Message::Reload).unwrap();
+        assert_eq!(app.banks.len(), 4);
+        assert_eq!(app.question_index, 3);
+        assert_eq!(app.banks.question(3).question, QUESTIONS[2].1);
+        assert_eq!(app.bookmarks.iter().collect::<Vec<_>>(), [&2]);
+        // and in a filtered session, the first file getting longer again
+        app.banks.set_view(vec![0, 2, 3]);
+        app.update(Message::GoTo(2)).unwrap();
+        write(&json_paths[0], &QUESTIONS);
+        app.update(Message::Tick).unwrap();
+        app.update(Message::Merge).unwrap();
+        assert_eq!(app.banks.len(), 3);
+        assert_eq!(app.question_index, 2);
+        assert_eq!(app.banks.absolute(2), 5);
+        assert_eq!(app.banks.question(2).question, QUESTIONS[2].1);
+        for json_path in json_paths {
+            fs::remove_file(json_path).unwrap();
+        }
+    }
+
+    #[test]
+    fn merge_keeps_the_responses_made_here() {
+        let (mut app, json_path) = session("merge");
+        app.update(Message::GoTo(2)).unwrap();
+        app.update(key('1')).unwrap();
+        app.dirty = false;
+        // the same questions in another order, one of them reworded
+        let reworded = ("q3", "Which vitamin is soluble in fat?");
+        write(&json_path, &[reworded, QUESTIONS[1], QUESTIONS[0]]);
+        app.update(Message::Merge).unwrap();
+        // nothing to merge until it's noticed
+        assert_eq!(app.num_answered, 1);
+        assert!(!app.dirty);
+        app.update(Message::Tick).unwrap();
+        let merge = app.config.keymap.merge.0;
+        app.update(Message::Key(merge.into())).unwrap();
+        assert_eq!(app.conflict, None);
+        assert_eq!(app.num_answered, 1);
+        assert!(app.dirty);
+        assert_eq!(app.banks.question(0).human_answer.as_deref(), Some("one"));
+        fs::remove_file(json_path).unwrap();
+    }
+
+    #[test]
+    fn keep_and_unusable_versions_on_disk() {
+        let (mut app, json_path) = session("keep");
+        app.update(key('1')).unwrap();
+        fs::write(&json_path, "[]").unwrap();
+        app.update(Message::Tick).unwrap();
+        // saving waits for the rater to pick
+        app.update(Message::Save).unwrap();
+        assert!(app.dirty);
+        // no questions left, the conflict stays
+        app.update(Message::Reload).unwrap();
+        assert!(app.conflict.is_some());
+        assert_eq!(app.banks.len(), 3);
+        assert!(app.message.starts_with("Can't use it"), "{}", app.message);
+        app.update(Message::Keep).unwrap();
+        assert_eq!(app.conflict, None);
+        assert_eq!(app.num_answered, 1);
+        app.update(Message::Save).unwrap();
+        assert!(!app.dirty);
+        assert_eq!(
+            Banks::load(std::slice::from_ref(&json_path)).unwrap().len(),
+            3
+        );
+        fs::remove_file(json_path).unwrap();
+    }
+
+    #[test]
+    fn proctored_sessions_quit_with_the_code() {
+        let (mut app, json_path) = session("proctor");
+        app.proctor_code = Some("4321".to_string());
+        app.update(Message::Quit).unwrap();
+        assert!(!app.exit);
+        assert_eq!(app.quit_prompt.as_deref(), Some(""));
+        // the code's keys go in the prompt, not to the question
+        for c in "12".chars() {
+            app.update(key(c)).unwrap();
+        }
+        assert_eq!(app.num_answered, 0);
+        app.update(Message::Key(KeyCode::Enter.into())).unwrap();
+        assert!(!app.exit);
+        assert_eq!(app.message, t!("proctor.wrong_code"));
+        app.update(Message::Quit).unwrap();
+        for c in "4321".chars() {
+            app.update(key(c)).unwrap();
+        }
+        app.update(Message::Key(KeyCode::Enter.into())).unwrap();
+        assert!(app.exit);
+        fs::remove_file(json_path).unwrap();
+    }
+
+    #[test]
+    fn tab_goes_to_the_next_axis_in_dimensions_mode() {
+        let (mut app, json_path) = session("tab");
+        app.update(Message::Key(KeyCode::Tab.into())).unwrap();
+        assert!(app.show_sidebar);
+        app.update(Message::Key(KeyCode::Tab.into())).unwrap();
+        app.mode = Mode::Dimensions;
+        app.update(Message::Key(KeyCode::Tab.into())).unwrap();
+        assert!(!app.show_sidebar);
+        app.update(Message::Key(KeyCode::BackTab.into())).unwrap();
+        assert!(app.show_sidebar);
+        fs::remove_file(json_path).unwrap();
+    }
+
+    #[test]
+    fn next_dimension_without_dimensions() {
+        let (mut app, json_path) = session("dimension");
+        app.update(Message::NextDimension).unwrap();
+        assert_eq!(app.dimension, 0);
+        fs::remove_file(json_path).unwrap();
+    }
+}
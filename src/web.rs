@@ -0,0 +1,231 @@
+use std::{fs, io::Cursor, path::Path};
+
+use color_eyre::{
+    eyre::{bail, eyre},
+    Result,
+};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::{
+    bank::{Banks, Classification, CLASSIFICATIONS},
+    i18n::t,
+    markdown,
+    mode::Mode,
+};
+
+// `question_cli serve bank.json` answers the bank in a browser instead of the
+// terminal, for raters who won't install anything. Plain HTML forms, no
+// JavaScript: every response is a POST that is saved straight away, so closing
+// the tab loses nothing. One rater at a time, like the terminal interface.
+//
+//   GET  /          the first question without a response
+//   GET  /q/3       question 3
+//   POST /q/3       value=2 records the response and goes to question 4
+//   GET  /image/3   question 3's image
+
+type Page = Response<Cursor<Vec<u8>>>;
+
+pub fn serve(banks: &mut Banks, mode: Mode, address: &str, patch_out: Option<&Path>) -> Result<()> {
+    if !matches!(mode, Mode::Answer | Mode::Classify | Mode::Rate) {
+        bail!("serve works in answer, classify and rate modes");
+    }
+    let server =
+        Server::http(address).map_err(|err| eyre!("could not listen on {}: {}", address, err))?;
+    eprintln!(
+        "{}",
+        t!("web.listening", url = format!("http://{}", address))
+    );
+    for mut request in server.incoming_requests() {
+        let page = handle(banks, mode, &mut request, patch_out).unwrap_or_else(|err| {
+            Response::from_string(format!("{:#}", err)).with_status_code(500)
+        });
+        // the browser going away mid response isn't our problem
+        let _ = request.respond(page);
+    }
+    Ok(())
+}
+
+fn handle(
+    banks: &mut Banks,
+    mode: Mode,
+    request: &mut Request,
+    patch_out: Option<&Path>,
+) -> Result<Page> {
+    let url = request.url().to_string();
+    let parts: Vec<&str> = url.trim_matches('/').split('/').collect();
+    // question numbers in urls count from 1, like in the interface
+    let index = parts
+        .get(1)
+        .and_then(|n| n.parse::<usize>().ok())
+        .and_then(|n| n.checked_sub(1))
+        .filter(|&index| index < banks.len());
+    match (request.method(), parts[0], index) {
+        (Method::Get, "", _) => {
+            let first = banks
+                .questions()
+                .position(|q| !mode.is_done(q))
+                .unwrap_or(0);
+            Ok(redirect(first))
+        }
+        (Method::Get, "q", Some(index)) => Ok(html(page(banks, mode, index))),
+        (Method::Post, "q", Some(index)) => {
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body)?;
+            let value = form_urlencoded::parse(body.as_bytes())
+                .find(|(key, _)| key == "value")
+                .map(|(_, value)| value.into_owned())
+                .ok_or_else(|| eyre!("no value given"))?;
+            let mut result = Ok(());
+            banks.update(index, &format!("web {}", mode.name()), |question| {
+                result = mode.set_value(question, &value);
+                result.is_ok()
+            })?;
+            result?;
+            banks.save()?;
+            if let Some(patch_path) = patch_out {
+                banks.write_patch(patch_path)?;
+            }
+            Ok(redirect((index + 1).min(banks.len() - 1)))
+        }
+        (Method::Get, "image", Some(index)) => match banks.image_path(index) {
+            Some(image_path) => {
+                let data = fs::read(&image_path)?;
+                let content_type = match image_path.extension().and_then(|ext| ext.to_str()) {
+                    Some("png") => "image/png",
+                    _ => "image/jpeg",
+                };
+                Ok(Response::from_data(data).with_header(header("Content-Type", content_type)))
+            }
+            None => Ok(not_found()),
+        },
+        _ => Ok(not_found()),
+    }
+}
+
+// the question with buttons for the responses, the rater's pick highlighted
+fn page(banks: &Banks, mode: Mode, index: usize) -> String {
+    let question = banks.question(index);
+    let number = index + 1;
+    let buttons: Vec<(String, String, bool)> = match mode {
+        Mode::Classify => CLASSIFICATIONS
+            .into_iter()
+            .map(|value| {
+                let label = match value {
+                    Classification::HigherOrder => t!("controls.true"),
+                    Classification::LowerOrder => t!("controls.false"),
+                    Classification::Unsure => t!("controls.unsure"),
+                };
+                let selected = question.is_higher_order == Some(value);
+                (value.to_string(), escape(label.trim()), selected)
+            })
+            .collect(),
+        Mode::Rate => (1..=5)
+            .map(|rating| {
+                let selected = question.rating == Some(rating);
+                (rating.to_string(), rating.to_string(), selected)
+            })
+            .collect(),
+        _ => question
+            .options
+            .iter()
+            .enumerate()
+            .map(|(i, option)| {
+                let selected = question.human_answer.as_ref() == Some(option);
+                let label = format!("{}. {}", i + 1, markdown::to_html(option));
+                ((i + 1).to_string(), label, selected)
+            })
+            .collect(),
+    };
+    let buttons: String = buttons
+        .into_iter()
+        .map(|(value, label, selected)| {
+            let class = if selected { " class=\"selected\"" } else { "" };
+            format!("<button name=\"value\" value=\"{value}\"{class}>{label}</button>\n")
+        })
+        .collect();
+    let (_, bank) = banks.source(index);
+    let instructions = match bank.instructions.get(mode.name()) {
+        Some(instructions) => markdown::to_html(instructions),
+        None => mode
+            .instructions()
+            .iter()
+            .map(|line| format!("<p>{}</p>", escape(&line.to_string())))
+            .collect(),
+    };
+    let image = match question.image_path {
+        Some(_) => format!("<img src=\"/image/{number}\" alt=\"\">"),
+        None => String::new(),
+    };
+    let done = banks.questions().filter(|q| mode.is_done(q)).count();
+    let total = banks.len();
+    let title = format!(
+        "{} {}{}{}",
+        t!("question.label").trim(),
+        number,
+        t!("question.of"),
+        total
+    );
+    let progress = t!("status.progress", percent = done * 100 / total);
+    let prev = if number > 1 { number - 1 } else { total };
+    let next = if number < total { number + 1 } else { 1 };
+    let prev_label = escape(t!("controls.prev").trim());
+    let next_label = escape(t!("controls.next").trim());
+    let stem = markdown::to_html(&question.question);
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; max-width: 50em; margin: 1em auto; padding: 0 1em; line-height: 1.4; }}
+form button {{ display: block; width: 100%; text-align: left; margin: 0.4em 0; padding: 0.6em; font-size: 1em; border: 1px solid #888; border-radius: 4px; background: #f4f4f4; }}
+form button.selected {{ background: #2e7d32; color: white; font-weight: bold; }}
+button p {{ display: inline; margin: 0; }}
+img {{ max-width: 100%; }}
+nav {{ display: flex; justify-content: space-between; margin: 1em 0; }}
+aside {{ color: #555; border-top: 1px solid #ccc; margin-top: 2em; }}
+</style>
+</head>
+<body>
+<nav><a href="/q/{prev}">&larr; {prev_label}</a> <strong>{title}</strong> <a href="/q/{next}">{next_label} &rarr;</a></nav>
+<p>{progress} ({done}/{total})</p>
+{stem}
+{image}
+<form method="post" action="/q/{number}">
+{buttons}</form>
+<aside>{instructions}</aside>
+</body>
+</html>
+"#
+    )
+}
+
+fn html(body: String) -> Page {
+    Response::from_string(body).with_header(header("Content-Type", "text/html; charset=utf-8"))
+}
+
+// after a POST, so reloading the page doesn't send the response again
+fn redirect(index: usize) -> Page {
+    let location = format!("/q/{}", index + 1);
+    Response::from_data(vec![])
+        .with_status_code(303)
+        .with_header(header("Location", &location))
+}
+
+fn not_found() -> Page {
+    Response::from_string("not found").with_status_code(404)
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes())
+        .expect("header names and values are ASCII")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}